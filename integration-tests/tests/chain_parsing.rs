@@ -0,0 +1,567 @@
+//! Tests Zaino-Fetch's block and transaction parsing against the mainnet and testnet block test vectors from `zebra-test`.
+//!
+//! Zebra's block deserialization is used as the reference parser.
+
+use std::collections::HashMap;
+
+use zaino_fetch::chain::{
+    block::{FullBlock, FullBlockHeader, FullBlockRef},
+    transaction::{FullTransaction, JoinSplit, ScriptType, TxIn, TxOut},
+    utils::{ParseFromSlice, SerializeToVec},
+};
+use zebra_chain::{
+    block::Block,
+    parameters::Network,
+    serialization::{ZcashDeserializeInto, ZcashSerialize},
+    transparent,
+};
+
+/// Serializes value, checks that parsing the serialized bytes consumes all of them and serializes back to the same
+/// bytes, and returns them.
+fn assert_round_trip<T: ParseFromSlice + SerializeToVec>(
+    value: &T,
+    txid: Option<Vec<Vec<u8>>>,
+    tx_version: Option<u32>,
+) -> Vec<u8> {
+    let bytes = value.serialize_to_vec();
+    let (remaining_data, parsed) = T::parse_from_slice(&bytes, txid, tx_version).unwrap();
+    assert!(remaining_data.is_empty());
+    assert_eq!(parsed.serialize_to_vec(), bytes);
+    bytes
+}
+
+/// Returns the mainnet and testnet block test vectors, the testnet vectors include NU5 blocks with Orchard actions.
+fn test_blocks() -> impl Iterator<Item = (&'static u32, &'static &'static [u8])> {
+    zebra_test::vectors::MAINNET_BLOCKS
+        .iter()
+        .chain(zebra_test::vectors::TESTNET_BLOCKS.iter())
+}
+
+/// Parses a raw block with both Zebra and Zaino-Fetch.
+fn parse_block(bytes: &[u8]) -> (Block, FullBlock) {
+    let block: Block = bytes
+        .zcash_deserialize_into()
+        .expect("test vector should be a valid block");
+    let full_block =
+        FullBlock::parse_from_hex(bytes, None).expect("zaino-fetch should parse the test vector");
+    (block, full_block)
+}
+
+#[test]
+fn parse_mainnet_blocks() {
+    for (height, bytes) in zebra_test::vectors::MAINNET_BLOCKS.iter() {
+        let (block, full_block) = parse_block(bytes);
+        assert_eq!(full_block.height(), *height as i32);
+        assert_eq!(full_block.transactions().len(), block.transactions.len());
+        for (tx, full_tx) in block.transactions.iter().zip(full_block.transactions()) {
+            assert_eq!(full_tx.version(), tx.version(), "block {}", height);
+            assert_eq!(full_tx.tx_id(), tx.hash().0.to_vec(), "block {}", height);
+            assert_eq!(full_tx.f_overwintered(), tx.is_overwintered());
+            assert_eq!(full_tx.transparent_inputs().len(), tx.inputs().len());
+            for (input, full_input) in tx.inputs().iter().zip(full_tx.transparent_inputs()) {
+                assert_eq!(full_input.sequence(), input.sequence());
+                match input {
+                    transparent::Input::PrevOut { outpoint, .. } => {
+                        assert!(!full_input.is_coinbase());
+                        assert_eq!(full_input.prev_txid(), outpoint.hash.0.to_vec());
+                        assert_eq!(full_input.prev_index(), outpoint.index);
+                    }
+                    transparent::Input::Coinbase { .. } => assert!(full_input.is_coinbase()),
+                }
+            }
+            assert_eq!(full_tx.transparent_outputs().len(), tx.outputs().len());
+            for (output, full_output) in tx.outputs().iter().zip(full_tx.transparent_outputs()) {
+                assert_eq!(full_output.value(), u64::from(output.value));
+                assert_eq!(
+                    full_output.script_pubkey(),
+                    output.lock_script.as_raw_bytes().to_vec()
+                );
+                assert_eq!(
+                    full_output.address(&Network::Mainnet),
+                    output.address(&Network::Mainnet)
+                );
+            }
+            assert_eq!(full_tx.join_splits().len(), tx.joinsplit_count());
+            assert_eq!(
+                full_tx
+                    .join_splits()
+                    .iter()
+                    .flat_map(|join_split| join_split.nullifiers())
+                    .collect::<Vec<_>>(),
+                tx.sprout_nullifiers()
+                    .map(|nullifier| nullifier.0.to_vec())
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(
+                full_tx.raw_bytes(),
+                tx.zcash_serialize_to_vec().unwrap(),
+                "block {}",
+                height
+            );
+        }
+    }
+}
+
+#[test]
+fn check_block_txids() {
+    for bytes in zebra_test::vectors::MAINNET_BLOCKS.values() {
+        let block: Block = bytes.zcash_deserialize_into().unwrap();
+        let mut txids: Vec<Vec<u8>> = block
+            .transactions
+            .iter()
+            .map(|tx| tx.hash().0.to_vec())
+            .collect();
+        assert!(FullBlock::parse_from_hex(bytes, Some(txids.clone())).is_ok());
+
+        txids[0][0] ^= 0xff;
+        assert!(FullBlock::parse_from_hex(bytes, Some(txids)).is_err());
+    }
+}
+
+#[test]
+fn verify_mainnet_blocks() {
+    for bytes in zebra_test::vectors::MAINNET_BLOCKS.values() {
+        let (block, full_block) = parse_block(bytes);
+        assert_eq!(
+            full_block.compute_merkle_root(),
+            block.header.merkle_root.0.to_vec()
+        );
+        full_block
+            .verify(
+                Some(&block.hash().0),
+                Some(&block.header.previous_block_hash.0),
+            )
+            .unwrap();
+
+        assert!(full_block.verify(Some(&[1; 32]), None).is_err());
+        assert!(full_block.verify(None, Some(&[1; 32])).is_err());
+    }
+}
+
+#[test]
+fn verify_mainnet_block_pow() {
+    for bytes in zebra_test::vectors::MAINNET_BLOCKS.values() {
+        let (_, full_block) = parse_block(bytes);
+        full_block.header().verify_pow().unwrap();
+
+        // Nonce starts after the version, 3 hashes, time and nBits.
+        let mut bad_nonce = bytes.to_vec();
+        bad_nonce[108] ^= 0xff;
+        let (_, full_block) = parse_block(&bad_nonce);
+        assert!(full_block.header().verify_pow().is_err());
+    }
+}
+
+#[test]
+fn expand_genesis_target() {
+    let (_, full_block) = parse_block(&zebra_test::vectors::BLOCK_MAINNET_GENESIS_BYTES);
+    let mut expected = [0u8; 32];
+    expected[1..4].copy_from_slice(&[0x07, 0xff, 0xff]);
+    assert_eq!(full_block.header().target().unwrap(), expected);
+}
+
+#[test]
+fn round_trip_block_headers() {
+    for bytes in zebra_test::vectors::MAINNET_BLOCKS.values() {
+        let (block, full_block) = parse_block(bytes);
+        let header = full_block.header().to_binary().unwrap();
+        assert_eq!(header, block.header.zcash_serialize_to_vec().unwrap());
+
+        let full_header = FullBlockHeader::parse_from_hex(&header).unwrap();
+        assert_eq!(full_header.cached_hash(), block.hash().0.to_vec());
+        assert_eq!(full_header.to_binary().unwrap(), header);
+
+        let compact_block = full_block.clone().into_compact(0, 0, true).unwrap();
+        assert_eq!(compact_block.header, header);
+        assert!(full_block
+            .into_compact(0, 0, false)
+            .unwrap()
+            .header
+            .is_empty());
+    }
+}
+
+#[test]
+fn borrowed_blocks_match_owned_blocks() {
+    for bytes in zebra_test::vectors::MAINNET_BLOCKS.values() {
+        let (block, full_block) = parse_block(bytes);
+        let block_ref = FullBlockRef::parse_from_hex(bytes, None).unwrap();
+        assert_eq!(block_ref.height(), full_block.height());
+        assert_eq!(block_ref.hash(), &block.hash().0);
+        assert_eq!(
+            block_ref.compute_merkle_root(),
+            full_block.compute_merkle_root()
+        );
+        block_ref
+            .verify(
+                Some(&block.hash().0),
+                Some(&block.header.previous_block_hash.0),
+            )
+            .unwrap();
+        assert!(block_ref.verify(None, Some(&[1; 32])).is_err());
+
+        for (tx_ref, full_tx) in block_ref
+            .transactions()
+            .iter()
+            .zip(full_block.transactions())
+        {
+            assert_eq!(tx_ref.tx_id().to_vec(), full_tx.tx_id());
+            assert_eq!(tx_ref.raw_bytes(), full_tx.raw_bytes().as_slice());
+        }
+        assert_eq!(
+            block_ref.to_compact(1, 2, true).unwrap(),
+            full_block.into_compact(1, 2, true).unwrap()
+        );
+    }
+}
+
+#[test]
+fn round_trip_mainnet_blocks() {
+    for (height, bytes) in zebra_test::vectors::MAINNET_BLOCKS.iter() {
+        let (block, full_block) = parse_block(bytes);
+        assert_eq!(
+            assert_round_trip(&full_block, None, None),
+            bytes.to_vec(),
+            "block {}",
+            height
+        );
+        assert_eq!(
+            assert_round_trip(&full_block.header(), None, None),
+            block.header.zcash_serialize_to_vec().unwrap()
+        );
+
+        for full_tx in full_block.transactions() {
+            let tx_bytes = assert_round_trip(&full_tx, Some(vec![full_tx.tx_id()]), None);
+            assert_eq!(tx_bytes, full_tx.raw_bytes(), "block {}", height);
+            for input in full_tx.transparent_inputs() {
+                assert_round_trip::<TxIn>(&input, None, None);
+            }
+            for output in full_tx.transparent_outputs() {
+                assert_round_trip::<TxOut>(&output, None, None);
+            }
+            for join_split in full_tx.join_splits() {
+                assert_round_trip::<JoinSplit>(&join_split, None, Some(full_tx.version()));
+            }
+        }
+    }
+}
+
+#[test]
+fn reject_malformed_blocks() {
+    let bytes = zebra_test::vectors::BLOCK_MAINNET_GENESIS_BYTES.as_slice();
+    let (block, _) = parse_block(bytes);
+    let header = block.header.zcash_serialize_to_vec().unwrap();
+
+    // A block without a coinbase transaction has no height.
+    let empty_block = [header.as_slice(), &[0]].concat();
+    assert!(FullBlock::parse_from_hex(&empty_block, None).is_err());
+    assert!(FullBlockRef::parse_from_hex(&empty_block, None).is_err());
+
+    // The largest allowed transaction count must not be preallocated.
+    let oversized_block = [header.as_slice(), &[0xfe, 0x00, 0x00, 0x00, 0x02]].concat();
+    assert!(FullBlock::parse_from_hex(&oversized_block, None).is_err());
+
+    for len in 0..bytes.len() {
+        assert!(
+            FullBlock::parse_from_hex(&bytes[..len], None).is_err(),
+            "block truncated to {} bytes",
+            len
+        );
+    }
+}
+
+#[test]
+fn serialize_modified_transaction() {
+    // A v4 transaction without sapling or sprout fields ends with its lock time, expiry height, value balance and
+    // three zero counts.
+    let transaction = zebra_test::vectors::MAINNET_BLOCKS
+        .values()
+        .flat_map(|bytes| parse_block(bytes).1.transactions())
+        .find(|tx| {
+            tx.version() == 4
+                && tx.shielded_spends().is_empty()
+                && tx.shielded_outputs().is_empty()
+                && tx.join_splits().is_empty()
+        })
+        .expect("test vectors should contain a transparent v4 transaction");
+    let mut tx_bytes = transaction.serialize_to_vec();
+    let expiry_offset = tx_bytes.len() - 3 - 8 - 4;
+    tx_bytes[expiry_offset] ^= 0x01;
+
+    let (_, modified) = FullTransaction::parse_from_slice(&tx_bytes, None, None).unwrap();
+    assert_eq!(modified.expiry_height(), transaction.expiry_height() ^ 0x01);
+    assert_ne!(modified.tx_id(), transaction.tx_id());
+    assert_eq!(assert_round_trip(&modified, None, None), tx_bytes);
+}
+
+#[test]
+fn compute_transaction_fees() {
+    let blocks: Vec<(Block, FullBlock)> = zebra_test::vectors::MAINNET_BLOCKS
+        .values()
+        .map(|bytes| parse_block(bytes))
+        .collect();
+    let mut outputs = HashMap::new();
+    for (block, _) in blocks.iter() {
+        for tx in block.transactions.iter() {
+            for (index, output) in tx.outputs().iter().enumerate() {
+                outputs.insert(
+                    transparent::OutPoint {
+                        hash: tx.hash(),
+                        index: index as u32,
+                    },
+                    output.clone(),
+                );
+            }
+        }
+    }
+    for (block, full_block) in blocks.iter() {
+        for (tx, full_tx) in block.transactions.iter().zip(full_block.transactions()) {
+            if tx.is_coinbase() {
+                assert_eq!(full_tx.fee(&[]).unwrap(), 0);
+                continue;
+            }
+            let prevout_values: Option<Vec<u64>> = tx
+                .inputs()
+                .iter()
+                .map(|input| {
+                    input
+                        .outpoint()
+                        .and_then(|outpoint| outputs.get(&outpoint))
+                        .map(|output| u64::from(output.value))
+                })
+                .collect();
+            let Some(prevout_values) = prevout_values else {
+                continue;
+            };
+            let expected = tx
+                .value_balance_from_outputs(&outputs)
+                .unwrap()
+                .remaining_transaction_value()
+                .unwrap();
+            assert_eq!(full_tx.fee(&prevout_values).unwrap(), u64::from(expected));
+            if !prevout_values.is_empty() {
+                assert!(full_tx.fee(&prevout_values[1..]).is_err());
+            }
+        }
+    }
+}
+
+#[test]
+fn parse_transaction_fields() {
+    for (height, bytes) in zebra_test::vectors::MAINNET_BLOCKS.iter() {
+        let (block, full_block) = parse_block(bytes);
+        for (tx, full_tx) in block.transactions.iter().zip(full_block.transactions()) {
+            assert_eq!(full_tx.lock_time(), tx.raw_lock_time(), "block {}", height);
+            assert_eq!(
+                full_tx.expiry_height(),
+                tx.expiry_height().map(|height| height.0).unwrap_or(0),
+                "block {}",
+                height
+            );
+            assert_eq!(
+                full_tx.value_balance_sapling(),
+                i64::from(tx.sapling_value_balance().sapling_amount())
+            );
+            assert_eq!(
+                full_tx.value_balance_orchard(),
+                i64::from(tx.orchard_value_balance().orchard_amount())
+            );
+
+            let mut anchors = full_tx.sapling_spend_anchors();
+            anchors.dedup();
+            let mut expected_anchors: Vec<Vec<u8>> = tx
+                .sapling_anchors()
+                .map(|anchor| <[u8; 32]>::from(anchor).to_vec())
+                .collect();
+            expected_anchors.dedup();
+            assert_eq!(anchors, expected_anchors, "block {}", height);
+
+            match tx.orchard_shielded_data() {
+                Some(orchard) => {
+                    assert_eq!(full_tx.flags_orchard(), orchard.flags.bits());
+                    assert_eq!(
+                        full_tx.anchor_orchard(),
+                        Some(<[u8; 32]>::from(orchard.shared_anchor).to_vec())
+                    );
+                }
+                None => {
+                    assert_eq!(full_tx.flags_orchard(), 0);
+                    assert_eq!(full_tx.anchor_orchard(), None);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn compare_shielded_data_with_zebra() {
+    for (height, bytes) in test_blocks() {
+        let (block, full_block) = parse_block(bytes);
+        for (tx, full_tx) in block.transactions.iter().zip(full_block.transactions()) {
+            assert_eq!(full_tx.tx_id(), tx.hash().0.to_vec(), "block {}", height);
+
+            assert_eq!(
+                full_tx
+                    .join_splits()
+                    .iter()
+                    .flat_map(|join_split| join_split.commitments())
+                    .collect::<Vec<_>>(),
+                tx.sprout_note_commitments()
+                    .map(|commitment| <[u8; 32]>::from(*commitment).to_vec())
+                    .collect::<Vec<_>>(),
+                "block {}",
+                height
+            );
+
+            assert_eq!(
+                full_tx.shielded_spends(),
+                tx.sapling_nullifiers()
+                    .map(|nullifier| <[u8; 32]>::from(*nullifier).to_vec())
+                    .collect::<Vec<_>>(),
+                "block {}",
+                height
+            );
+            // A v4 output is encoded as cv, cmu, ephemeral_key, enc_ciphertext, out_ciphertext and zkproof.
+            let sapling_outputs: Vec<(Vec<u8>, Vec<u8>, Vec<u8>)> = tx
+                .sapling_outputs()
+                .map(|output| {
+                    let output = output.clone().into_v4().zcash_serialize_to_vec().unwrap();
+                    (
+                        output[32..64].to_vec(),
+                        output[64..96].to_vec(),
+                        output[96..676].to_vec(),
+                    )
+                })
+                .collect();
+            assert_eq!(
+                full_tx.shielded_outputs(),
+                sapling_outputs,
+                "block {}",
+                height
+            );
+
+            // An action is encoded as cv, nullifier, rk, cmx, ephemeral_key, enc_ciphertext and out_ciphertext.
+            let orchard_actions: Vec<(Vec<u8>, Vec<u8>, Vec<u8>, Vec<u8>)> = tx
+                .orchard_actions()
+                .map(|action| {
+                    let action = action.zcash_serialize_to_vec().unwrap();
+                    (
+                        action[32..64].to_vec(),
+                        action[96..128].to_vec(),
+                        action[128..160].to_vec(),
+                        action[160..740].to_vec(),
+                    )
+                })
+                .collect();
+            assert_eq!(
+                full_tx.orchard_actions(),
+                orchard_actions,
+                "block {}",
+                height
+            );
+            assert_eq!(
+                full_tx
+                    .orchard_actions()
+                    .into_iter()
+                    .map(|(nullifier, ..)| nullifier)
+                    .collect::<Vec<_>>(),
+                tx.orchard_nullifiers()
+                    .map(|nullifier| <[u8; 32]>::from(*nullifier).to_vec())
+                    .collect::<Vec<_>>()
+            );
+
+            for (output, full_output) in tx.outputs().iter().zip(full_tx.transparent_outputs()) {
+                assert_eq!(full_output.value(), u64::from(output.value));
+            }
+        }
+    }
+}
+
+#[test]
+fn compare_compact_blocks_with_zebra() {
+    for (height, bytes) in test_blocks() {
+        let (block, full_block) = parse_block(bytes);
+        let compact_block = full_block.into_compact(0, 0, false).unwrap();
+        assert_eq!(compact_block.hash, block.hash().0.to_vec());
+        for compact_tx in compact_block.vtx {
+            let tx = &block.transactions[compact_tx.index as usize];
+            assert_eq!(compact_tx.hash, tx.hash().0.to_vec(), "block {}", height);
+            assert_eq!(
+                compact_tx
+                    .spends
+                    .into_iter()
+                    .map(|spend| spend.nf)
+                    .collect::<Vec<_>>(),
+                tx.sapling_nullifiers()
+                    .map(|nullifier| <[u8; 32]>::from(*nullifier).to_vec())
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(compact_tx.outputs.len(), tx.sapling_outputs().count());
+            for (compact_output, output) in compact_tx.outputs.iter().zip(tx.sapling_outputs()) {
+                let output = output.clone().into_v4().zcash_serialize_to_vec().unwrap();
+                assert_eq!(compact_output.cmu, output[32..64].to_vec());
+                assert_eq!(compact_output.ephemeral_key, output[64..96].to_vec());
+                assert_eq!(compact_output.ciphertext, output[96..148].to_vec());
+            }
+            assert_eq!(compact_tx.actions.len(), tx.orchard_actions().count());
+            for (compact_action, action) in compact_tx.actions.iter().zip(tx.orchard_actions()) {
+                let action = action.zcash_serialize_to_vec().unwrap();
+                assert_eq!(compact_action.nullifier, action[32..64].to_vec());
+                assert_eq!(compact_action.cmx, action[96..128].to_vec());
+                assert_eq!(compact_action.ephemeral_key, action[128..160].to_vec());
+                assert_eq!(compact_action.ciphertext, action[160..212].to_vec());
+            }
+        }
+    }
+}
+
+#[test]
+fn parse_pre_overwinter_mainnet_block() {
+    let (_, full_block) = parse_block(&zebra_test::vectors::BLOCK_MAINNET_1_BYTES);
+    assert_eq!(full_block.height(), 1);
+    let coinbase = &full_block.transactions()[0];
+    assert!(!coinbase.f_overwintered());
+    assert_eq!(coinbase.version(), 1);
+    assert_eq!(coinbase.n_version_group_id(), 0);
+}
+
+#[test]
+fn parse_overwinter_mainnet_block() {
+    let (_, full_block) = parse_block(&zebra_test::vectors::BLOCK_MAINNET_347500_BYTES);
+    assert_eq!(full_block.height(), 347_500);
+    for full_tx in full_block.transactions() {
+        assert!(full_tx.f_overwintered());
+        assert_eq!(full_tx.version(), 3);
+        assert_eq!(full_tx.n_version_group_id(), 0x03C48270);
+    }
+}
+
+#[test]
+fn derive_transparent_addresses() {
+    // Value, p2pkh script_pubkey paying to the all zeros public key hash.
+    let mut p2pkh = vec![0u8; 8];
+    p2pkh.extend_from_slice(&[25, 0x76, 0xa9, 0x14]);
+    p2pkh.extend_from_slice(&[0u8; 20]);
+    p2pkh.extend_from_slice(&[0x88, 0xac]);
+    let (_, output) = TxOut::parse_from_slice(&p2pkh, None, None).unwrap();
+    assert_eq!(output.script_type(), ScriptType::P2pkh([0u8; 20]));
+    assert_eq!(
+        output.address(&Network::Mainnet).unwrap().to_string(),
+        "t1Hsc1LR8yKnbbe3twRp88p6vFfC5t7DLbs"
+    );
+
+    // Value, p2sh script_pubkey paying to the all zeros script hash.
+    let mut p2sh = vec![0u8; 8];
+    p2sh.extend_from_slice(&[23, 0xa9, 0x14]);
+    p2sh.extend_from_slice(&[0u8; 20]);
+    p2sh.push(0x87);
+    let (_, output) = TxOut::parse_from_slice(&p2sh, None, None).unwrap();
+    assert_eq!(output.script_type(), ScriptType::P2sh([0u8; 20]));
+    assert_eq!(
+        output
+            .address(&Network::new_default_testnet())
+            .unwrap()
+            .to_string(),
+        "t26YoyZ1iPgiMEWL4zGUm74eVWfhyDMXzY2"
+    );
+}
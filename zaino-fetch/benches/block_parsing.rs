@@ -0,0 +1,35 @@
+//! Benchmarks building compact blocks from the mainnet block test vectors in `zebra-test`.
+//!
+//! Compares parsing into an owned [`FullBlock`] with parsing into a borrowed [`FullBlockRef`].
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use zaino_fetch::chain::block::{FullBlock, FullBlockRef};
+
+fn compact_blocks(c: &mut Criterion) {
+    let blocks: Vec<&[u8]> = zebra_test::vectors::MAINNET_BLOCKS
+        .values()
+        .copied()
+        .collect();
+
+    let mut group = c.benchmark_group("compact_blocks");
+    group.bench_function("owned", |b| {
+        b.iter(|| {
+            for bytes in blocks.iter() {
+                let full_block = FullBlock::parse_from_hex(black_box(bytes), None).unwrap();
+                black_box(full_block.into_compact(0, 0, false).unwrap());
+            }
+        })
+    });
+    group.bench_function("borrowed", |b| {
+        b.iter(|| {
+            for bytes in blocks.iter() {
+                let full_block = FullBlockRef::parse_from_hex(black_box(bytes), None).unwrap();
+                black_box(full_block.to_compact(0, 0, false).unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, compact_blocks);
+criterion_main!(benches);
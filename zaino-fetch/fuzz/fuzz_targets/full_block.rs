@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zaino_fetch::chain::block::{FullBlock, FullBlockRef};
+use zaino_fetch_fuzz::check_parse;
+
+fuzz_target!(|data: &[u8]| {
+    if let Some(block) = check_parse::<FullBlock>(data, None) {
+        let (_, borrowed) = FullBlockRef::parse(data, None).expect("borrowed parse failed");
+        assert_eq!(borrowed.hash().as_slice(), block.header().cached_hash());
+        let _ = borrowed.verify(None, None);
+        let _ = borrowed.to_compact(0, 0, true);
+        let _ = block.into_compact(0, 0, true);
+    }
+});
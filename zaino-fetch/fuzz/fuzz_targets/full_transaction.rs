@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zaino_fetch::chain::transaction::{FullTransaction, FullTransactionRef};
+use zaino_fetch_fuzz::check_parse;
+
+fuzz_target!(|data: &[u8]| {
+    if let Some(transaction) = check_parse::<FullTransaction>(data, None) {
+        let (_, borrowed) = FullTransactionRef::parse(data, None).expect("borrowed parse failed");
+        assert_eq!(borrowed.tx_id().as_slice(), transaction.tx_id());
+        let _ = borrowed.to_compact(0);
+        let _ = transaction.to_compact(0);
+    }
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zaino_fetch::chain::transaction::Spend;
+use zaino_fetch_fuzz::{check_parse, split_tx_version};
+
+fuzz_target!(|data: &[u8]| {
+    if let Some((tx_version, data)) = split_tx_version(data) {
+        check_parse::<Spend>(data, Some(tx_version));
+    }
+});
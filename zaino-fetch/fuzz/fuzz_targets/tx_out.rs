@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zaino_fetch::chain::transaction::TxOut;
+use zaino_fetch_fuzz::check_parse;
+
+fuzz_target!(|data: &[u8]| {
+    check_parse::<TxOut>(data, None);
+});
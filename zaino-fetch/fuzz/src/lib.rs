@@ -0,0 +1,37 @@
+//! Shared checks for the zaino-fetch fuzz targets.
+
+use zaino_fetch::chain::utils::{ParseFromSlice, SerializeToVec};
+
+/// Splits the fuzzer input into a transaction version, taken from the first byte, and the data to parse.
+pub fn split_tx_version(data: &[u8]) -> Option<(u32, &[u8])> {
+    let (version, data) = data.split_first()?;
+    Some((u32::from(*version), data))
+}
+
+/// Parses data as T, the parser must return an error rather than panic on malformed input.
+///
+/// When parsing succeeds, serializing the parsed value must give back exactly the bytes consumed and parsing those
+/// bytes again must consume all of them.
+pub fn check_parse<T: ParseFromSlice + SerializeToVec>(
+    data: &[u8],
+    tx_version: Option<u32>,
+) -> Option<T> {
+    let (remaining_data, value) = T::parse_from_slice(data, None, tx_version).ok()?;
+    let consumed = &data[..data.len() - remaining_data.len()];
+
+    let serialized = value.serialize_to_vec();
+    assert_eq!(
+        serialized, consumed,
+        "serialization does not match the parsed bytes"
+    );
+
+    let (remaining_data, reparsed) = T::parse_from_slice(&serialized, None, tx_version)
+        .expect("serialized value fails to parse");
+    assert!(
+        remaining_data.is_empty(),
+        "serialized value is not fully consumed"
+    );
+    assert_eq!(reparsed.serialize_to_vec(), serialized);
+
+    Some(value)
+}
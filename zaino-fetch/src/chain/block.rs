@@ -3,20 +3,47 @@
 use crate::{
     chain::{
         error::{BlockCacheError, ParseError},
-        transaction::FullTransaction,
+        transaction::{get_transaction_fee, FullTransaction, FullTransactionRef},
         utils::{
-            display_txids_to_server, read_bytes, read_i32, read_u32, read_zcash_script_i64,
-            CompactSize, ParseFromSlice,
+            bounded_capacity, display_txids_to_server, read_i32, read_slice, read_u32,
+            read_zcash_script_i64, write_compact_size, CompactSize, ParseFromSlice, SerializeToVec,
         },
     },
     jsonrpc::{connector::JsonRpcConnector, response::GetBlockResponse},
 };
 use sha2::{Digest, Sha256};
-use std::io::Cursor;
+use std::{collections::HashMap, io::Cursor};
 use zaino_proto::proto::compact_formats::{
     ChainMetadata, CompactBlock, CompactOrchardAction, CompactTx,
 };
 
+/// Options applied to blocks and transactions fetched from the validator.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BlockOptions {
+    /// Verify the transaction merkle root, block hash and parent hash of fetched blocks.
+    pub verify: bool,
+    /// Verify the Equihash solution and difficulty target of fetched block headers.
+    ///
+    /// Regtest blocks are not mined with Equihash (200, 9), so this should not be set on regtest.
+    pub verify_pow: bool,
+    /// Fill `CompactBlock.header` with the serialized block header, left empty (as in LightWalletD) if not set.
+    pub compact_header: bool,
+    /// Fill `CompactTx.fee`, left as 0 (as in LightWalletD) if not set.
+    ///
+    /// Resolving the value of transparent inputs requires a `getrawtransaction` call per spent transaction.
+    pub fees: bool,
+}
+
+/// Smallest encoding of a transaction (v1, no inputs or outputs).
+const MIN_TRANSACTION_SIZE: usize = 10;
+
+/// Equihash `n` parameter used by Zcash mainnet and testnet.
+const EQUIHASH_N: u32 = 200;
+
+/// Equihash `k` parameter used by Zcash mainnet and testnet.
+const EQUIHASH_K: u32 = 9;
+
 /// A block header, containing metadata about a block.
 ///
 /// How are blocks chained together? They are chained together via the
@@ -113,44 +140,70 @@ impl ParseFromSlice for BlockHeaderData {
                 "tx_version must be None for BlockHeaderData::parse_from_slice".to_string(),
             ));
         }
+        let (remaining_data, header) = BlockHeaderRef::parse(data)?;
+        Ok((remaining_data, header.into_owned()))
+    }
+}
+
+/// Borrowed [`BlockHeaderData`], referencing the block bytes it was parsed from.
+///
+/// Fields are as documented on [`BlockHeaderData`].
+#[derive(Debug, Clone, Copy)]
+pub struct BlockHeaderRef<'a> {
+    version: i32,
+    hash_prev_block: &'a [u8],
+    hash_merkle_root: &'a [u8],
+    hash_final_sapling_root: &'a [u8],
+    time: u32,
+    n_bits_bytes: &'a [u8],
+    nonce: &'a [u8],
+    solution: &'a [u8],
+    /// The serialized header the fields above were parsed from.
+    raw_bytes: &'a [u8],
+}
+
+impl<'a> BlockHeaderRef<'a> {
+    /// Parses a block header, borrowing its byte fields from `data`.
+    pub fn parse(data: &'a [u8]) -> Result<(&'a [u8], Self), ParseError> {
         let mut cursor = Cursor::new(data);
 
         let version = read_i32(&mut cursor, "Error reading BlockHeaderData::version")?;
-        let hash_prev_block = read_bytes(
+        let hash_prev_block = read_slice(
             &mut cursor,
             32,
             "Error reading BlockHeaderData::hash_prev_block",
         )?;
-        let hash_merkle_root = read_bytes(
+        let hash_merkle_root = read_slice(
             &mut cursor,
             32,
             "Error reading BlockHeaderData::hash_merkle_root",
         )?;
-        let hash_final_sapling_root = read_bytes(
+        let hash_final_sapling_root = read_slice(
             &mut cursor,
             32,
             "Error reading BlockHeaderData::hash_final_sapling_root",
         )?;
         let time = read_u32(&mut cursor, "Error reading BlockHeaderData::time")?;
-        let n_bits_bytes = read_bytes(
+        let n_bits_bytes = read_slice(
             &mut cursor,
             4,
             "Error reading BlockHeaderData::n_bits_bytes",
         )?;
-        let nonce = read_bytes(&mut cursor, 32, "Error reading BlockHeaderData::nonce")?;
+        let nonce = read_slice(&mut cursor, 32, "Error reading BlockHeaderData::nonce")?;
 
         let solution = {
             let compact_length = CompactSize::read(&mut cursor)?;
-            read_bytes(
+            read_slice(
                 &mut cursor,
                 compact_length as usize,
                 "Error reading BlockHeaderData::solution",
             )?
         };
 
+        let header_len = cursor.position() as usize;
         Ok((
-            &data[cursor.position() as usize..],
-            BlockHeaderData {
+            &data[header_len..],
+            BlockHeaderRef {
                 version,
                 hash_prev_block,
                 hash_merkle_root,
@@ -159,16 +212,53 @@ impl ParseFromSlice for BlockHeaderData {
                 n_bits_bytes,
                 nonce,
                 solution,
+                raw_bytes: &data[..header_len],
             },
         ))
     }
-}
 
-impl BlockHeaderData {
-    /// Serializes the block header into a byte vector.
-    fn to_binary(&self) -> Result<Vec<u8>, ParseError> {
-        let mut buffer = Vec::new();
+    /// Returns the hash of the previous block.
+    pub fn hash_prev_block(&self) -> &'a [u8] {
+        self.hash_prev_block
+    }
+
+    /// Returns the root of the Bitcoin-inherited transaction Merkle tree.
+    pub fn hash_merkle_root(&self) -> &'a [u8] {
+        self.hash_merkle_root
+    }
+
+    /// Returns the time when the miner started hashing the header (according to the miner).
+    pub fn time(&self) -> u32 {
+        self.time
+    }
+
+    /// Returns the serialized block header, as sent in `CompactBlock.header`.
+    pub fn raw_bytes(&self) -> &'a [u8] {
+        self.raw_bytes
+    }
+
+    /// Returns the block hash, the double SHA256 hash of the serialized header.
+    pub fn hash(&self) -> [u8; 32] {
+        Sha256::digest(Sha256::digest(self.raw_bytes)).into()
+    }
+
+    /// Copies the header into an owned [`BlockHeaderData`].
+    fn into_owned(self) -> BlockHeaderData {
+        BlockHeaderData {
+            version: self.version,
+            hash_prev_block: self.hash_prev_block.to_vec(),
+            hash_merkle_root: self.hash_merkle_root.to_vec(),
+            hash_final_sapling_root: self.hash_final_sapling_root.to_vec(),
+            time: self.time,
+            n_bits_bytes: self.n_bits_bytes.to_vec(),
+            nonce: self.nonce.to_vec(),
+            solution: self.solution.to_vec(),
+        }
+    }
+}
 
+impl SerializeToVec for BlockHeaderData {
+    fn write_to_vec(&self, buffer: &mut Vec<u8>) {
         buffer.extend(&self.version.to_le_bytes());
         buffer.extend(&self.hash_prev_block);
         buffer.extend(&self.hash_merkle_root);
@@ -176,12 +266,15 @@ impl BlockHeaderData {
         buffer.extend(&self.time.to_le_bytes());
         buffer.extend(&self.n_bits_bytes);
         buffer.extend(&self.nonce);
-        let mut solution_compact_size = Vec::new();
-        CompactSize::write(&mut solution_compact_size, self.solution.len())?;
-        buffer.extend(solution_compact_size);
+        write_compact_size(buffer, self.solution.len());
         buffer.extend(&self.solution);
+    }
+}
 
-        Ok(buffer)
+impl BlockHeaderData {
+    /// Serializes the block header into a byte vector.
+    fn to_binary(&self) -> Result<Vec<u8>, ParseError> {
+        Ok(self.serialize_to_vec())
     }
 
     /// Extracts the block hash from the block header.
@@ -196,6 +289,65 @@ impl BlockHeaderData {
 
         Ok(final_digest.to_vec())
     }
+
+    /// Returns the header fields hashed into the Equihash input (every field before the nonce).
+    fn equihash_input(&self) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(108);
+
+        buffer.extend(&self.version.to_le_bytes());
+        buffer.extend(&self.hash_prev_block);
+        buffer.extend(&self.hash_merkle_root);
+        buffer.extend(&self.hash_final_sapling_root);
+        buffer.extend(&self.time.to_le_bytes());
+        buffer.extend(&self.n_bits_bytes);
+
+        buffer
+    }
+
+    /// Expands the compact nBits encoding into a 256 bit big-endian target threshold.
+    ///
+    /// Returns an error if the encoded target is negative, zero or overflows 256 bits.
+    fn target(&self) -> Result<[u8; 32], ParseError> {
+        let bits_bytes: [u8; 4] = self
+            .n_bits_bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| ParseError::InvalidProofOfWork("nBits must be 4 bytes".to_string()))?;
+        let bits = u32::from_le_bytes(bits_bytes);
+        let exponent = (bits >> 24) as usize;
+        let mantissa = bits & 0x007f_ffff;
+        if bits & 0x0080_0000 != 0 {
+            return Err(ParseError::InvalidProofOfWork(format!(
+                "negative target in nBits {:08x}",
+                bits
+            )));
+        }
+
+        let mut target = [0u8; 32];
+        for (index, byte) in mantissa.to_be_bytes()[1..].iter().enumerate() {
+            // Position of the byte counting from the least significant byte of the target.
+            let Some(position) = (exponent + 2).checked_sub(index + 3) else {
+                continue;
+            };
+            if position >= 32 {
+                if *byte != 0 {
+                    return Err(ParseError::InvalidProofOfWork(format!(
+                        "target in nBits {:08x} overflows 256 bits",
+                        bits
+                    )));
+                }
+                continue;
+            }
+            target[31 - position] = *byte;
+        }
+        if target == [0u8; 32] {
+            return Err(ParseError::InvalidProofOfWork(format!(
+                "zero target in nBits {:08x}",
+                bits
+            )));
+        }
+        Ok(target)
+    }
 }
 
 /// Complete block header.
@@ -208,6 +360,42 @@ pub struct FullBlockHeader {
     cached_hash: Vec<u8>,
 }
 
+impl ParseFromSlice for FullBlockHeader {
+    fn parse_from_slice(
+        data: &[u8],
+        txid: Option<Vec<Vec<u8>>>,
+        tx_version: Option<u32>,
+    ) -> Result<(&[u8], Self), ParseError> {
+        if txid.is_some() {
+            return Err(ParseError::InvalidData(
+                "txid must be None for FullBlockHeader::parse_from_slice".to_string(),
+            ));
+        }
+        if tx_version.is_some() {
+            return Err(ParseError::InvalidData(
+                "tx_version must be None for FullBlockHeader::parse_from_slice".to_string(),
+            ));
+        }
+        let (remaining_data, raw_block_header) =
+            BlockHeaderData::parse_from_slice(data, None, None)?;
+        let cached_hash = raw_block_header.get_hash()?;
+
+        Ok((
+            remaining_data,
+            FullBlockHeader {
+                raw_block_header,
+                cached_hash,
+            },
+        ))
+    }
+}
+
+impl SerializeToVec for FullBlockHeader {
+    fn write_to_vec(&self, buffer: &mut Vec<u8>) {
+        self.raw_block_header.write_to_vec(buffer);
+    }
+}
+
 impl FullBlockHeader {
     /// Returns the Zcash block version.
     pub fn version(&self) -> i32 {
@@ -248,6 +436,59 @@ impl FullBlockHeader {
     pub fn cached_hash(&self) -> Vec<u8> {
         self.cached_hash.clone()
     }
+
+    /// Serializes the block header, as sent in `CompactBlock.header`.
+    pub fn to_binary(&self) -> Result<Vec<u8>, ParseError> {
+        self.raw_block_header.to_binary()
+    }
+
+    /// Decodes a serialized block header (eg. `CompactBlock.header`) into a FullBlockHeader struct.
+    pub fn parse_from_hex(data: &[u8]) -> Result<Self, ParseError> {
+        let (remaining_data, header) = Self::parse_from_slice(data, None, None)?;
+        if !remaining_data.is_empty() {
+            return Err(ParseError::InvalidData(format!(
+                "Error decoding block header - {} bytes of Remaining data.",
+                remaining_data.len()
+            )));
+        }
+        Ok(header)
+    }
+
+    /// Returns the target threshold encoded in nBits, as a big-endian 256 bit number.
+    pub fn target(&self) -> Result<[u8; 32], ParseError> {
+        self.raw_block_header.target()
+    }
+
+    /// Verifies the header's proof of work.
+    ///
+    /// - The Equihash (200, 9) solution must be valid for the header.
+    /// - The header hash must be less than or equal to the target threshold encoded in nBits.
+    ///
+    /// The nBits value itself is not checked against the difficulty adjustment, as this requires the previous
+    /// blocks' headers.
+    pub fn verify_pow(&self) -> Result<(), ParseError> {
+        equihash::is_valid_solution(
+            EQUIHASH_N,
+            EQUIHASH_K,
+            &self.raw_block_header.equihash_input(),
+            &self.raw_block_header.nonce,
+            &self.raw_block_header.solution,
+        )
+        .map_err(|e| ParseError::InvalidProofOfWork(format!("invalid Equihash solution: {}", e)))?;
+
+        let target = self.target()?;
+        let hash = self.raw_block_header.get_hash()?;
+        // Block hashes are little-endian numbers.
+        let hash_be: Vec<u8> = hash.iter().rev().copied().collect();
+        if hash_be.as_slice() > target.as_slice() {
+            return Err(ParseError::InvalidProofOfWork(format!(
+                "block hash {} is above the target {}",
+                hex::encode(hash_be),
+                hex::encode(target)
+            )));
+        }
+        Ok(())
+    }
 }
 
 /// Zingo-Indexer Block.
@@ -271,58 +512,23 @@ impl ParseFromSlice for FullBlock {
         txid: Option<Vec<Vec<u8>>>,
         tx_version: Option<u32>,
     ) -> Result<(&[u8], Self), ParseError> {
-        let txid = txid.ok_or_else(|| {
-            ParseError::InvalidData("txid must be used for FullBlock::parse_from_slice".to_string())
-        })?;
         if tx_version.is_some() {
             return Err(ParseError::InvalidData(
                 "tx_version must be None for FullBlock::parse_from_slice".to_string(),
             ));
         }
-        let mut cursor = Cursor::new(data);
+        let (remaining_data, block) = FullBlockRef::parse(data, txid.as_deref())?;
+        Ok((remaining_data, block.into_owned()))
+    }
+}
 
-        let (remaining_data, block_header_data) =
-            BlockHeaderData::parse_from_slice(&data[cursor.position() as usize..], None, None)?;
-        cursor.set_position(data.len() as u64 - remaining_data.len() as u64);
-        let tx_count = CompactSize::read(&mut cursor)?;
-        if txid.len() != tx_count as usize {
-            return Err(ParseError::InvalidData(format!(
-                "number of txids ({}) does not match tx_count ({})",
-                txid.len(),
-                tx_count
-            )));
+impl SerializeToVec for FullBlock {
+    fn write_to_vec(&self, buffer: &mut Vec<u8>) {
+        self.hdr.write_to_vec(buffer);
+        write_compact_size(buffer, self.vtx.len());
+        for transaction in self.vtx.iter() {
+            transaction.write_to_vec(buffer);
         }
-        let mut transactions = Vec::with_capacity(tx_count as usize);
-        let mut remaining_data = &data[cursor.position() as usize..];
-        for txid_item in txid.iter() {
-            if remaining_data.is_empty() {
-                return Err(ParseError::InvalidData(
-                    "parsing block transactions: not enough data for transaction.".to_string(),
-                ));
-            }
-            let (new_remaining_data, tx) = FullTransaction::parse_from_slice(
-                &data[cursor.position() as usize..],
-                Some(vec![txid_item.clone()]),
-                None,
-            )?;
-            transactions.push(tx);
-            remaining_data = new_remaining_data;
-            cursor.set_position(data.len() as u64 - remaining_data.len() as u64);
-        }
-        let block_height = Self::get_block_height(&transactions)?;
-        let block_hash = block_header_data.get_hash()?;
-
-        Ok((
-            remaining_data,
-            FullBlock {
-                hdr: FullBlockHeader {
-                    raw_block_header: block_header_data,
-                    cached_hash: block_hash,
-                },
-                vtx: transactions,
-                height: block_height,
-            },
-        ))
     }
 }
 
@@ -349,23 +555,88 @@ impl FullBlock {
     }
 
     /// Decodes a hex encoded zcash full block into a FullBlock struct.
+    ///
+    /// Txids are computed from the block data, if txids are given they are checked against the computed txids.
     pub fn parse_from_hex(data: &[u8], txid: Option<Vec<Vec<u8>>>) -> Result<Self, ParseError> {
         let (remaining_data, full_block) = Self::parse_from_slice(data, txid, None)?;
         if !remaining_data.is_empty() {
             return Err(ParseError::InvalidData(format!(
                 "Error decoding full block - {} bytes of Remaining data. Compact Block Created: ({:?})",
                 remaining_data.len(),
-                full_block.into_compact(0, 0)
+                full_block.into_compact(0, 0, false)
             )));
         }
         Ok(full_block)
     }
 
+    /// Computes the root of the transaction merkle tree from the block's txids.
+    ///
+    /// Odd numbered levels of the tree are padded by duplicating their last hash, as in Bitcoin.
+    pub fn compute_merkle_root(&self) -> Vec<u8> {
+        merkle_root(self.vtx.iter().map(|tx| tx.tx_id()).collect())
+    }
+
+    /// Verifies the block is internally consistent, and consistent with the given block and parent hashes.
+    ///
+    /// - The transaction merkle root computed from the block's txids must match `hash_merkle_root`.
+    /// - The hash computed from the block header must match the cached block hash, and `expected_hash` if given.
+    /// - `hash_prev_block` must match `parent_hash` if given.
+    ///
+    /// Hashes are given in internal byte order.
+    pub fn verify(
+        &self,
+        expected_hash: Option<&[u8]>,
+        parent_hash: Option<&[u8]>,
+    ) -> Result<(), ParseError> {
+        let merkle_root = self.compute_merkle_root();
+        if merkle_root != self.hdr.raw_block_header.hash_merkle_root {
+            return Err(ParseError::InvalidBlock(format!(
+                "block {} merkle root mismatch: header {}, computed {}",
+                self.height,
+                hex::encode(&self.hdr.raw_block_header.hash_merkle_root),
+                hex::encode(merkle_root)
+            )));
+        }
+        let block_hash = self.hdr.raw_block_header.get_hash()?;
+        if block_hash != self.hdr.cached_hash {
+            return Err(ParseError::InvalidBlock(format!(
+                "block {} hash mismatch: cached {}, computed {}",
+                self.height,
+                hex::encode(&self.hdr.cached_hash),
+                hex::encode(&block_hash)
+            )));
+        }
+        if let Some(expected_hash) = expected_hash {
+            if block_hash != expected_hash {
+                return Err(ParseError::InvalidBlock(format!(
+                    "block {} hash mismatch: expected {}, computed {}",
+                    self.height,
+                    hex::encode(expected_hash),
+                    hex::encode(&block_hash)
+                )));
+            }
+        }
+        if let Some(parent_hash) = parent_hash {
+            if self.hdr.raw_block_header.hash_prev_block != parent_hash {
+                return Err(ParseError::InvalidBlock(format!(
+                    "block {} does not link to its parent: hash_prev_block {}, parent {}",
+                    self.height,
+                    hex::encode(&self.hdr.raw_block_header.hash_prev_block),
+                    hex::encode(parent_hash)
+                )));
+            }
+        }
+        Ok(())
+    }
+
     /// Converts a zcash full block into a compact block.
+    ///
+    /// The serialized block header is only included if `include_header` is set.
     pub fn into_compact(
         self,
         sapling_commitment_tree_size: u32,
         orchard_commitment_tree_size: u32,
+        include_header: bool,
     ) -> Result<CompactBlock, ParseError> {
         let vtx = self
             .vtx
@@ -380,9 +651,12 @@ impl FullBlock {
             })
             .collect::<Result<Vec<_>, _>>()?;
 
-        // NOTE: LightWalletD doesnt return a compact block header, however this could be used to return data if useful.
-        // let header = self.hdr.raw_block_header.to_binary()?;
-        let header = Vec::new();
+        // NOTE: LightWalletD doesnt return a compact block header, so it is only included when requested.
+        let header = if include_header {
+            self.hdr.raw_block_header.to_binary()?
+        } else {
+            Vec::new()
+        };
 
         let compact_block = CompactBlock {
             proto_version: 0,
@@ -400,11 +674,254 @@ impl FullBlock {
 
         Ok(compact_block)
     }
+}
+
+/// Computes the root of a Bitcoin-style merkle tree over the given txids.
+fn merkle_root(mut hashes: Vec<Vec<u8>>) -> Vec<u8> {
+    if hashes.is_empty() {
+        return vec![0; 32];
+    }
+    while hashes.len() > 1 {
+        if hashes.len() % 2 == 1 {
+            hashes.push(hashes[hashes.len() - 1].clone());
+        }
+        hashes = hashes
+            .chunks(2)
+            .map(|pair| {
+                let mut hasher = Sha256::new();
+                hasher.update(&pair[0]);
+                hasher.update(&pair[1]);
+                Sha256::digest(hasher.finalize()).to_vec()
+            })
+            .collect();
+    }
+    hashes.remove(0)
+}
+
+/// Borrowed [`FullBlock`], referencing the block bytes it was parsed from.
+///
+/// Parsing into a `FullBlockRef` does not copy any byte fields out of the block data, compact blocks are built
+/// directly from the borrowed fields. The block data can be any contiguous buffer, eg. a `Vec<u8>` or `bytes::Bytes`.
+#[derive(Debug, Clone)]
+pub struct FullBlockRef<'a> {
+    /// The block header, containing block metadata.
+    header: BlockHeaderRef<'a>,
+
+    /// Hash of the block.
+    hash: [u8; 32],
+
+    /// The block transactions.
+    vtx: Vec<FullTransactionRef<'a>>,
+
+    /// Block height.
+    height: i32,
+}
+
+impl<'a> FullBlockRef<'a> {
+    /// Parses a block from the start of `data`, returning it along with the remaining data.
+    ///
+    /// Txids are computed from the block data, if txids are given they are checked against the computed txids.
+    pub fn parse(data: &'a [u8], txid: Option<&[Vec<u8>]>) -> Result<(&'a [u8], Self), ParseError> {
+        let mut cursor = Cursor::new(data);
+
+        let (remaining_data, header) = BlockHeaderRef::parse(data)?;
+        cursor.set_position(data.len() as u64 - remaining_data.len() as u64);
+        let tx_count = CompactSize::read(&mut cursor)?;
+        if let Some(txid) = txid {
+            if txid.len() != tx_count as usize {
+                return Err(ParseError::InvalidData(format!(
+                    "number of txids ({}) does not match tx_count ({})",
+                    txid.len(),
+                    tx_count
+                )));
+            }
+        }
+        let mut transactions = Vec::with_capacity(bounded_capacity(
+            tx_count,
+            data.len() - cursor.position() as usize,
+            MIN_TRANSACTION_SIZE,
+        ));
+        let mut remaining_data = &data[cursor.position() as usize..];
+        for tx_index in 0..tx_count as usize {
+            if remaining_data.is_empty() {
+                return Err(ParseError::InvalidData(
+                    "parsing block transactions: not enough data for transaction.".to_string(),
+                ));
+            }
+            let (new_remaining_data, tx) = FullTransactionRef::parse(
+                remaining_data,
+                txid.map(|txid| txid[tx_index].as_slice()),
+            )?;
+            transactions.push(tx);
+            remaining_data = new_remaining_data;
+        }
+        let height = Self::get_block_height(&transactions)?;
+
+        Ok((
+            remaining_data,
+            FullBlockRef {
+                hash: header.hash(),
+                header,
+                vtx: transactions,
+                height,
+            },
+        ))
+    }
+
+    /// Decodes a zcash full block, which must span all of `data`.
+    ///
+    /// Txids are computed from the block data, if txids are given they are checked against the computed txids.
+    pub fn parse_from_hex(data: &'a [u8], txid: Option<&[Vec<u8>]>) -> Result<Self, ParseError> {
+        let (remaining_data, full_block) = Self::parse(data, txid)?;
+        if !remaining_data.is_empty() {
+            return Err(ParseError::InvalidData(format!(
+                "Error decoding full block {} - {} bytes of Remaining data.",
+                full_block.height,
+                remaining_data.len()
+            )));
+        }
+        Ok(full_block)
+    }
+
+    /// Returns the block header.
+    pub fn header(&self) -> &BlockHeaderRef<'a> {
+        &self.header
+    }
+
+    /// Returns the block hash (in internal byte order).
+    pub fn hash(&self) -> &[u8; 32] {
+        &self.hash
+    }
+
+    /// Returns the transactions held in the block.
+    pub fn transactions(&self) -> &[FullTransactionRef<'a>] {
+        &self.vtx
+    }
+
+    /// Returns the block height.
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// Computes the root of the transaction merkle tree from the block's txids, see [`FullBlock::compute_merkle_root`].
+    pub fn compute_merkle_root(&self) -> Vec<u8> {
+        merkle_root(self.vtx.iter().map(|tx| tx.tx_id().to_vec()).collect())
+    }
+
+    /// Verifies the block's merkle root, and that it is consistent with the given block and parent hashes.
+    ///
+    /// See [`FullBlock::verify`], the block hash is computed from the borrowed header bytes so is not re-checked
+    /// against a re-serialized header.
+    pub fn verify(
+        &self,
+        expected_hash: Option<&[u8]>,
+        parent_hash: Option<&[u8]>,
+    ) -> Result<(), ParseError> {
+        let merkle_root = self.compute_merkle_root();
+        if merkle_root != self.header.hash_merkle_root {
+            return Err(ParseError::InvalidBlock(format!(
+                "block {} merkle root mismatch: header {}, computed {}",
+                self.height,
+                hex::encode(self.header.hash_merkle_root),
+                hex::encode(merkle_root)
+            )));
+        }
+        if let Some(expected_hash) = expected_hash {
+            if self.hash != expected_hash {
+                return Err(ParseError::InvalidBlock(format!(
+                    "block {} hash mismatch: expected {}, computed {}",
+                    self.height,
+                    hex::encode(expected_hash),
+                    hex::encode(self.hash)
+                )));
+            }
+        }
+        if let Some(parent_hash) = parent_hash {
+            if self.header.hash_prev_block != parent_hash {
+                return Err(ParseError::InvalidBlock(format!(
+                    "block {} does not link to its parent: hash_prev_block {}, parent {}",
+                    self.height,
+                    hex::encode(self.header.hash_prev_block),
+                    hex::encode(parent_hash)
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Verifies the header's proof of work, see [`FullBlockHeader::verify_pow`].
+    pub fn verify_pow(&self) -> Result<(), ParseError> {
+        FullBlockHeader {
+            raw_block_header: self.header.into_owned(),
+            cached_hash: self.hash.to_vec(),
+        }
+        .verify_pow()
+    }
+
+    /// Converts the block into a compact block, copying only the fields the compact block holds.
+    ///
+    /// The serialized block header is only included if `include_header` is set.
+    pub fn to_compact(
+        &self,
+        sapling_commitment_tree_size: u32,
+        orchard_commitment_tree_size: u32,
+        include_header: bool,
+    ) -> Result<CompactBlock, ParseError> {
+        let vtx = self
+            .vtx
+            .iter()
+            .enumerate()
+            .filter(|(_, tx)| tx.has_shielded_elements())
+            .map(|(index, tx)| tx.to_compact(index as u64))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // NOTE: LightWalletD doesnt return a compact block header, so it is only included when requested.
+        let header = if include_header {
+            self.header.raw_bytes.to_vec()
+        } else {
+            Vec::new()
+        };
+
+        Ok(CompactBlock {
+            proto_version: 0,
+            height: self.height as u64,
+            hash: self.hash.to_vec(),
+            prev_hash: self.header.hash_prev_block.to_vec(),
+            time: self.header.time,
+            header,
+            vtx,
+            chain_metadata: Some(ChainMetadata {
+                sapling_commitment_tree_size,
+                orchard_commitment_tree_size,
+            }),
+        })
+    }
+
+    /// Copies the block into an owned [`FullBlock`].
+    pub fn into_owned(self) -> FullBlock {
+        FullBlock {
+            hdr: FullBlockHeader {
+                raw_block_header: self.header.into_owned(),
+                cached_hash: self.hash.to_vec(),
+            },
+            vtx: self
+                .vtx
+                .into_iter()
+                .map(FullTransactionRef::into_owned)
+                .collect(),
+            height: self.height,
+        }
+    }
 
     /// Extracts the block height from the coinbase transaction.
-    fn get_block_height(transactions: &[FullTransaction]) -> Result<i32, ParseError> {
-        let transparent_inputs = transactions[0].transparent_inputs();
-        let coinbase_script = transparent_inputs[0].as_slice();
+    fn get_block_height(transactions: &[FullTransactionRef]) -> Result<i32, ParseError> {
+        let coinbase_script = transactions
+            .first()
+            .and_then(|transaction| transaction.transparent_inputs().first())
+            .map(|input| input.script_sig())
+            .ok_or_else(|| {
+                ParseError::InvalidData("block does not start with a coinbase input".to_string())
+            })?;
 
         let mut cursor = Cursor::new(coinbase_script);
 
@@ -426,21 +943,25 @@ impl FullBlock {
 /// Returns a compact block.
 ///
 /// Retrieves a full block from zebrad/zcashd using 2 get_block calls.
-/// This is because a get_block verbose = 1 call is require to fetch txids.
+/// The get_block verbose = 1 call is required to fetch the commitment tree sizes returned in
+/// `CompactBlock.chain_metadata`, which can not be computed from the block data alone. The txids it returns are
+/// checked against those computed from the block data, and the raw block is then fetched by the returned hash so both
+/// calls refer to the same block.
+///
+/// The block is parsed into a [`FullBlockRef`], so the compact block is built without copying the full block.
+///
+/// If `options.verify` is set the block is checked against the hash returned by the node, see [`FullBlockRef::verify`].
+/// If `options.verify_pow` is set the block header's proof of work is checked, see [`FullBlockHeader::verify_pow`].
+/// If `options.compact_header` is set the serialized block header is returned in `CompactBlock.header`.
+/// If `options.fees` is set the fee of each compact transaction is computed, see [`get_transaction_fee`].
 /// TODO: Save retrieved CompactBlock to the BlockCache.
 /// TODO: Return more representative error type.
 pub async fn get_block_from_node(
-    zebra_uri: &http::Uri,
+    zebrad_client: &JsonRpcConnector,
     height: &u32,
+    options: &BlockOptions,
 ) -> Result<CompactBlock, BlockCacheError> {
-    let zebrad_client = JsonRpcConnector::new(
-        zebra_uri.clone(),
-        Some("xxxxxx".to_string()),
-        Some("xxxxxx".to_string()),
-    )
-    .await?;
-    let block_1 = zebrad_client.get_block(height.to_string(), Some(1)).await;
-    match block_1 {
+    match zebrad_client.get_block(height.to_string(), Some(1)).await {
         Ok(GetBlockResponse::Object {
             hash,
             confirmations: _,
@@ -449,28 +970,19 @@ pub async fn get_block_from_node(
             tx,
             trees,
         }) => {
-            let block_0 = zebrad_client.get_block(hash.0.to_string(), Some(0)).await;
-            match block_0 {
-                Ok(GetBlockResponse::Object {
-                    hash: _,
-                    confirmations: _,
-                    height: _,
-                    time: _,
-                    tx: _,
-                    trees: _,
-                }) => Err(BlockCacheError::ParseError(ParseError::InvalidData(
-                    "Received object block type, this should not be possible here.".to_string(),
-                ))),
-                Ok(GetBlockResponse::Raw(block_hex)) => Ok(FullBlock::parse_from_hex(
-                    block_hex.as_ref(),
-                    Some(display_txids_to_server(tx)?),
-                )?
-                .into_compact(
-                    u32::try_from(trees.sapling()).map_err(ParseError::from)?,
-                    u32::try_from(trees.orchard()).map_err(ParseError::from)?,
-                )?),
-                Err(e) => Err(e.into()),
-            }
+            let block_data = get_raw_block_from_node(zebrad_client, hash.0.to_string()).await?;
+            let txids = display_txids_to_server(tx)?;
+            let full_block =
+                FullBlockRef::parse_from_hex(block_data.as_ref(), Some(txids.as_slice()))?;
+            build_compact_block(
+                zebrad_client,
+                full_block,
+                Some(hash.0 .0.as_slice()),
+                u32::try_from(trees.sapling()).map_err(ParseError::from)?,
+                u32::try_from(trees.orchard()).map_err(ParseError::from)?,
+                options,
+            )
+            .await
         }
         Ok(GetBlockResponse::Raw(_)) => Err(BlockCacheError::ParseError(ParseError::InvalidData(
             "Received raw block type, this should not be possible here.".to_string(),
@@ -479,17 +991,99 @@ pub async fn get_block_from_node(
     }
 }
 
+/// Returns the serialized block at the given hash or height, using a get_block verbose = 0 call.
+async fn get_raw_block_from_node(
+    zebrad_client: &JsonRpcConnector,
+    hash_or_height: String,
+) -> Result<zebra_chain::block::SerializedBlock, BlockCacheError> {
+    match zebrad_client.get_block(hash_or_height, Some(0)).await {
+        Ok(GetBlockResponse::Raw(block_data)) => Ok(block_data),
+        Ok(GetBlockResponse::Object {
+            hash: _,
+            confirmations: _,
+            height: _,
+            time: _,
+            tx: _,
+            trees: _,
+        }) => Err(BlockCacheError::ParseError(ParseError::InvalidData(
+            "Received object block type, this should not be possible here.".to_string(),
+        ))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Verifies a parsed block and builds its compact block, see [`get_block_from_node`] for the `options` applied.
+///
+/// `expected_hash` is the block hash returned by the node, in internal byte order, if known.
+async fn build_compact_block(
+    zebrad_client: &JsonRpcConnector,
+    full_block: FullBlockRef<'_>,
+    expected_hash: Option<&[u8]>,
+    sapling_commitment_tree_size: u32,
+    orchard_commitment_tree_size: u32,
+    options: &BlockOptions,
+) -> Result<CompactBlock, BlockCacheError> {
+    if options.verify {
+        full_block.verify(expected_hash, None)?;
+    }
+    if options.verify_pow {
+        full_block.verify_pow()?;
+    }
+    let mut fees = HashMap::new();
+    if options.fees {
+        // Fee lookups work on owned transactions, so they are only copied out when requested.
+        let transactions: Vec<FullTransaction> = full_block
+            .transactions()
+            .iter()
+            .cloned()
+            .map(FullTransactionRef::into_owned)
+            .collect();
+        let mut prevout_cache = HashMap::new();
+        for (index, transaction) in transactions.iter().enumerate() {
+            if transaction.has_shielded_elements() {
+                let fee = get_transaction_fee(
+                    zebrad_client,
+                    transaction,
+                    &transactions,
+                    &mut prevout_cache,
+                )
+                .await?;
+                fees.insert(index as u64, fee);
+            }
+        }
+    }
+    let mut compact_block = full_block.to_compact(
+        sapling_commitment_tree_size,
+        orchard_commitment_tree_size,
+        options.compact_header,
+    )?;
+    for compact_tx in compact_block.vtx.iter_mut() {
+        compact_tx.fee = fees.get(&compact_tx.index).copied().unwrap_or_default();
+    }
+    Ok(compact_block)
+}
+
 /// Returns a compact block holding only action nullifiers.
 ///
-/// Retrieves a full block from zebrad/zcashd using 2 get_block calls.
-/// This is because a get_block verbose = 1 call is require to fetch txids.
+/// Retrieves a full block from zebrad/zcashd using a single get_block verbose = 0 call. Nullifier blocks do not
+/// return commitment tree sizes, so the verbose = 1 call made by [`get_block_from_node`] is not needed. The txids
+/// computed from the block data are therefore not cross-checked against the node's, and `options.verify` checks the
+/// block is internally consistent only.
 ///
 /// TODO / NOTE: This should be rewritten when the BlockCache is added.
 pub async fn get_nullifiers_from_node(
-    zebra_uri: &http::Uri,
+    zebrad_client: &JsonRpcConnector,
     height: &u32,
+    options: &BlockOptions,
 ) -> Result<CompactBlock, BlockCacheError> {
-    match get_block_from_node(zebra_uri, height).await {
+    // Fees are not returned in nullifier blocks.
+    let options = BlockOptions {
+        fees: false,
+        ..*options
+    };
+    let block_data = get_raw_block_from_node(zebrad_client, height.to_string()).await?;
+    let full_block = FullBlockRef::parse_from_hex(block_data.as_ref(), None)?;
+    match build_compact_block(zebrad_client, full_block, None, 0, 0, &options).await {
         Ok(block) => Ok(CompactBlock {
             proto_version: block.proto_version,
             height: block.height,
@@ -526,3 +1120,19 @@ pub async fn get_nullifiers_from_node(
         Err(e) => Err(e),
     }
 }
+
+/// Checks that `child` links to `parent`, ie. that the child's prev_hash is the parent's hash.
+///
+/// Used to verify consecutive blocks fetched from the node when [`BlockOptions::verify`] is set.
+pub fn verify_block_link(parent: &CompactBlock, child: &CompactBlock) -> Result<(), ParseError> {
+    if child.prev_hash != parent.hash {
+        return Err(ParseError::InvalidBlock(format!(
+            "block {} does not link to block {}: prev_hash {}, parent hash {}",
+            child.height,
+            parent.height,
+            hex::encode(&child.prev_hash),
+            hex::encode(&parent.hash)
+        )));
+    }
+    Ok(())
+}
@@ -32,6 +32,14 @@ pub enum ParseError {
     /// Integer conversion error.
     #[error("Integer conversion error: {0}")]
     TryFromIntError(#[from] std::num::TryFromIntError),
+
+    /// Block verification error, the block data is inconsistent.
+    #[error("Invalid Block Error: {0}")]
+    InvalidBlock(String),
+
+    /// Proof of work error, the block header's Equihash solution or hash is invalid.
+    #[error("Invalid Proof of Work Error: {0}")]
+    InvalidProofOfWork(String),
 }
 
 /// Parser Error Type.
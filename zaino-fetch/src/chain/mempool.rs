@@ -1,6 +1,9 @@
 //! Zingo-Indexer mempool state functionality.
 
-use std::{collections::HashSet, time::SystemTime};
+use std::{
+    collections::{HashMap, HashSet},
+    time::SystemTime,
+};
 use tokio::sync::{Mutex, RwLock};
 
 use crate::{chain::error::MempoolError, jsonrpc::connector::JsonRpcConnector};
@@ -11,10 +14,14 @@ pub struct Mempool {
     txids: RwLock<Vec<String>>,
     /// Txids that have already been added to Zingo-Indexer's mempool.
     txids_seen: Mutex<HashSet<String>>,
+    /// Expiry heights of the transactions in the mempool, transactions that do not expire are not held.
+    expiry_heights: Mutex<HashMap<String, u32>>,
     /// System time when the mempool was last updated.
     last_sync_time: Mutex<SystemTime>,
     /// Blockchain data, used to check when a new block has been mined.
     best_block_hash: RwLock<Option<zebra_chain::block::Hash>>,
+    /// Height of the best block, used to evict expired transactions.
+    best_block_height: RwLock<Option<u32>>,
 }
 
 impl Default for Mempool {
@@ -29,47 +36,115 @@ impl Mempool {
         Mempool {
             txids: RwLock::new(Vec::new()),
             txids_seen: Mutex::new(HashSet::new()),
+            expiry_heights: Mutex::new(HashMap::new()),
             last_sync_time: Mutex::new(SystemTime::now()),
             best_block_hash: RwLock::new(None),
+            best_block_height: RwLock::new(None),
         }
     }
 
     /// Updates the mempool, returns true if the current block in the mempool has been mined.
-    pub async fn update(&self, zebrad_uri: &http::Uri) -> Result<bool, MempoolError> {
+    ///
+    /// When a new block has been mined, transactions that have expired at the new best block are evicted before
+    /// refreshing (see [`Mempool::record_expiry_height`]), and transactions that have since left the node's mempool
+    /// are dropped.
+    pub async fn update(&self, zebrad_client: &JsonRpcConnector) -> Result<bool, MempoolError> {
         self.update_last_sync_time().await?;
-        let mined = self.check_and_update_best_block_hash(zebrad_uri).await?;
+        let mined = self.check_and_update_best_block_hash(zebrad_client).await?;
         if mined {
-            self.reset_txids().await?;
-            self.update_txids(zebrad_uri).await?;
-            Ok(true)
-        } else {
-            self.update_txids(zebrad_uri).await?;
-            Ok(false)
+            if let Some(height) = *self.best_block_height.read().await {
+                self.evict_expired(height).await?;
+            }
         }
+        self.update_txids(zebrad_client, mined).await?;
+        Ok(mined)
     }
 
     /// Updates the txids in the mempool.
-    async fn update_txids(&self, zebrad_uri: &http::Uri) -> Result<(), MempoolError> {
-        let node_txids = JsonRpcConnector::new(
-            zebrad_uri.clone(),
-            Some("xxxxxx".to_string()),
-            Some("xxxxxx".to_string()),
-        )
-        .await?
-        .get_raw_mempool()
-        .await?
-        .transactions;
+    ///
+    /// Txids that have not been seen before are added. If `prune` is set, txids that are no longer in the node's
+    /// mempool (mined or dropped) are removed.
+    async fn update_txids(
+        &self,
+        zebrad_client: &JsonRpcConnector,
+        prune: bool,
+    ) -> Result<(), MempoolError> {
+        let node_txids = zebrad_client.get_raw_mempool().await?.transactions;
+        if prune {
+            let node_txid_set: HashSet<&String> = node_txids.iter().collect();
+            let mut txids_seen = self.txids_seen.lock().await;
+            let mut txids = self.txids.write().await;
+            let mut expiry_heights = self.expiry_heights.lock().await;
+            txids_seen.retain(|txid| node_txid_set.contains(txid));
+            txids.retain(|txid| node_txid_set.contains(txid));
+            expiry_heights.retain(|txid, _| node_txid_set.contains(txid));
+        }
+        let new_txids: Vec<String> = {
+            let txids_seen = self.txids_seen.lock().await;
+            node_txids
+                .into_iter()
+                .filter(|txid| !txids_seen.contains(txid))
+                .collect()
+        };
         let mut txids_seen = self.txids_seen.lock().await;
         let mut txids = self.txids.write().await;
-        for txid in node_txids {
-            if !txids_seen.contains(&txid) {
-                txids.push(txid.clone());
+        for txid in new_txids {
+            if txids_seen.insert(txid.clone()) {
+                txids.push(txid);
             }
-            txids_seen.insert(txid);
         }
         Ok(())
     }
 
+    /// Records the expiry height of a transaction held in the mempool, used by [`Mempool::evict_expired`].
+    ///
+    /// Expiry heights are not fetched by [`Mempool::update`], callers record them from the transactions they fetch.
+    /// Transactions that do not expire (expiry height 0) or are not held in the mempool are ignored.
+    pub async fn record_expiry_height(
+        &self,
+        txid: &str,
+        expiry_height: u32,
+    ) -> Result<(), MempoolError> {
+        if expiry_height == 0 {
+            return Ok(());
+        }
+        let txids = self.txids.read().await;
+        if txids.iter().any(|held_txid| held_txid == txid) {
+            self.expiry_heights
+                .lock()
+                .await
+                .insert(txid.to_string(), expiry_height);
+        }
+        Ok(())
+    }
+
+    /// Removes transactions that can not be mined in the block after `height` from the mempool, returns their txids.
+    ///
+    /// A transaction with expiry height `h` may only be mined in blocks up to and including height `h`.
+    pub async fn evict_expired(&self, height: u32) -> Result<Vec<String>, MempoolError> {
+        let mut txids = self.txids.write().await;
+        let mut expiry_heights = self.expiry_heights.lock().await;
+        let expired: Vec<String> = txids
+            .iter()
+            .filter(|txid| {
+                expiry_heights
+                    .get(*txid)
+                    .is_some_and(|expiry_height| *expiry_height <= height)
+            })
+            .cloned()
+            .collect();
+        txids.retain(|txid| !expired.contains(txid));
+        for txid in expired.iter() {
+            expiry_heights.remove(txid);
+        }
+        Ok(expired)
+    }
+
+    /// Returns the expiry height of a transaction in the mempool, None if the transaction does not expire or is unknown.
+    pub async fn get_expiry_height(&self, txid: &str) -> Result<Option<u32>, MempoolError> {
+        Ok(self.expiry_heights.lock().await.get(txid).copied())
+    }
+
     /// Updates the system last sync time.
     async fn update_last_sync_time(&self) -> Result<(), MempoolError> {
         let mut last_sync_time = self.last_sync_time.lock().await;
@@ -80,17 +155,11 @@ impl Mempool {
     /// Updates the mempool blockchain info, returns true if the current block in the mempool has been mined.
     async fn check_and_update_best_block_hash(
         &self,
-        zebrad_uri: &http::Uri,
+        zebrad_client: &JsonRpcConnector,
     ) -> Result<bool, MempoolError> {
-        let node_best_block_hash = JsonRpcConnector::new(
-            zebrad_uri.clone(),
-            Some("xxxxxx".to_string()),
-            Some("xxxxxx".to_string()),
-        )
-        .await?
-        .get_blockchain_info()
-        .await?
-        .best_block_hash;
+        let blockchain_info = zebrad_client.get_blockchain_info().await?;
+        let node_best_block_hash = blockchain_info.best_block_hash;
+        *self.best_block_height.write().await = Some(blockchain_info.blocks.0);
 
         let mut last_best_block_hash = self.best_block_hash.write().await;
 
@@ -104,15 +173,6 @@ impl Mempool {
         Ok(true)
     }
 
-    /// Clears the txids currently held in the mempool.
-    async fn reset_txids(&self) -> Result<(), MempoolError> {
-        let mut txids = self.txids.write().await;
-        txids.clear();
-        let mut txids_seen = self.txids_seen.lock().await;
-        txids_seen.clear();
-        Ok(())
-    }
-
     /// Returns the txids currently in the mempool.
     pub async fn get_mempool_txids(&self) -> Result<Vec<String>, MempoolError> {
         let txids = self.txids.read().await;
@@ -156,3 +216,62 @@ impl Mempool {
         Ok(*best_block_hash)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn insert(mempool: &Mempool, txid: &str, expiry_height: Option<u32>) {
+        mempool.txids_seen.lock().await.insert(txid.to_string());
+        mempool.txids.write().await.push(txid.to_string());
+        if let Some(expiry_height) = expiry_height {
+            mempool
+                .record_expiry_height(txid, expiry_height)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn evict_expired_boundary() {
+        let mempool = Mempool::new();
+        insert(&mempool, "expired", Some(99)).await;
+        insert(&mempool, "expires_at_height", Some(100)).await;
+        insert(&mempool, "expires_after_height", Some(101)).await;
+        insert(&mempool, "never_expires", None).await;
+
+        let evicted = mempool.evict_expired(100).await.unwrap();
+        assert_eq!(evicted, vec!["expired", "expires_at_height"]);
+        assert_eq!(
+            mempool.get_mempool_txids().await.unwrap(),
+            vec!["expires_after_height", "never_expires"]
+        );
+        assert_eq!(mempool.get_expiry_height("expired").await.unwrap(), None);
+        assert_eq!(
+            mempool
+                .get_expiry_height("expires_after_height")
+                .await
+                .unwrap(),
+            Some(101)
+        );
+    }
+
+    #[tokio::test]
+    async fn record_expiry_height_ignores_unknown_and_non_expiring() {
+        let mempool = Mempool::new();
+        insert(&mempool, "held", Some(0)).await;
+        mempool.record_expiry_height("unknown", 100).await.unwrap();
+        assert_eq!(mempool.get_expiry_height("held").await.unwrap(), None);
+        assert_eq!(mempool.get_expiry_height("unknown").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn evict_expired_keeps_unexpired() {
+        let mempool = Mempool::new();
+        insert(&mempool, "a", Some(200)).await;
+        insert(&mempool, "b", None).await;
+
+        assert!(mempool.evict_expired(199).await.unwrap().is_empty());
+        assert_eq!(mempool.get_mempool_txids().await.unwrap(), vec!["a", "b"]);
+    }
+}
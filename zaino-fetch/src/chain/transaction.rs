@@ -1,29 +1,96 @@
 //! Transaction fetching and deserialization functionality.
 
-use crate::chain::{
-    error::ParseError,
-    utils::{read_bytes, read_u32, read_u64, skip_bytes, CompactSize, ParseFromSlice},
+use crate::{
+    chain::{
+        error::{BlockCacheError, ParseError},
+        utils::{
+            bounded_capacity, read_i64, read_slice, read_u32, read_u64, write_compact_size,
+            CompactSize, ParseFromSlice, SerializeToVec,
+        },
+    },
+    jsonrpc::{connector::JsonRpcConnector, response::GetTransactionResponse},
 };
-use std::io::Cursor;
+use sha2::{Digest, Sha256};
+use std::{collections::HashMap, io::Cursor};
 use zaino_proto::proto::compact_formats::{
     CompactOrchardAction, CompactSaplingOutput, CompactSaplingSpend, CompactTx,
 };
+use zebra_chain::{parameters::Network, transparent::Address};
+
+/// Smallest encoding of a transparent input (empty script_sig).
+const MIN_TX_IN_SIZE: usize = 41;
+
+/// Smallest encoding of a transparent output (empty script_pubkey).
+const MIN_TX_OUT_SIZE: usize = 9;
+
+/// Smallest encoding of a JoinSplit description (v4, Groth16 proof).
+const MIN_JOIN_SPLIT_SIZE: usize = 1698;
+
+/// Smallest encoding of a Sapling spend description (v5, proof and signature held separately).
+const MIN_SPEND_SIZE: usize = 96;
+
+/// Smallest encoding of a Sapling output description (v5, proof held separately).
+const MIN_OUTPUT_SIZE: usize = 756;
+
+/// Encoding size of an Orchard action.
+const ACTION_SIZE: usize = 820;
+
+/// Size of a note ciphertext, every parsed Sapling output and Orchard action holds exactly this many bytes.
+const ENC_CIPHERTEXT_SIZE: usize = 580;
+
+/// Size of the note plaintext prefix sent in compact outputs and actions, see ZIP 307.
+const COMPACT_NOTE_SIZE: usize = 52;
 
 /// Txin format as described in https://en.bitcoin.it/wiki/Transaction
 #[derive(Debug, Clone)]
-struct TxIn {
-    // PrevTxHash [IGNORED] - Size[bytes]: 32
-    // PrevTxOutIndex [IGNORED] - Size[bytes]: 4
+pub struct TxIn {
+    /// Txid of the transaction holding the output being spent, all zeros for coinbase inputs.
+    ///
+    /// Size[bytes]: 32
+    prev_txid: Vec<u8>,
+    /// Index of the output being spent, 0xFFFFFFFF for coinbase inputs.
+    ///
+    /// Size[bytes]: 4
+    prev_index: u32,
     /// CompactSize-prefixed, could be a pubkey or a script
     ///
     /// Size[bytes]: CompactSize
     script_sig: Vec<u8>,
-    // SequenceNumber [IGNORED] - Size[bytes]: 4
+    /// Sequence number of the input.
+    ///
+    /// Size[bytes]: 4
+    sequence: u32,
 }
 
 impl TxIn {
-    fn into_inner(self) -> Vec<u8> {
-        self.script_sig
+    /// Returns the txid of the transaction holding the output being spent.
+    pub fn prev_txid(&self) -> Vec<u8> {
+        self.prev_txid.clone()
+    }
+
+    /// Returns the index of the output being spent.
+    pub fn prev_index(&self) -> u32 {
+        self.prev_index
+    }
+
+    /// Returns the outpoint (txid, index) of the output being spent.
+    pub fn outpoint(&self) -> (Vec<u8>, u32) {
+        (self.prev_txid.clone(), self.prev_index)
+    }
+
+    /// Returns the input's script_sig, for coinbase inputs this holds the block height and miner data.
+    pub fn script_sig(&self) -> Vec<u8> {
+        self.script_sig.clone()
+    }
+
+    /// Returns the input's sequence number.
+    pub fn sequence(&self) -> u32 {
+        self.sequence
+    }
+
+    /// Returns true if this is a coinbase input (spends the null outpoint).
+    pub fn is_coinbase(&self) -> bool {
+        self.prev_index == u32::MAX && self.prev_txid.iter().all(|byte| *byte == 0)
     }
 }
 
@@ -43,38 +110,173 @@ impl ParseFromSlice for TxIn {
                 "tx_version must be None for TxIn::parse_from_slice".to_string(),
             ));
         }
+        let (remaining_data, tx_in) = TxInRef::parse(data)?;
+        Ok((remaining_data, tx_in.into_owned()))
+    }
+}
+
+impl SerializeToVec for TxIn {
+    fn write_to_vec(&self, buffer: &mut Vec<u8>) {
+        buffer.extend(&self.prev_txid);
+        buffer.extend(&self.prev_index.to_le_bytes());
+        write_compact_size(buffer, self.script_sig.len());
+        buffer.extend(&self.script_sig);
+        buffer.extend(&self.sequence.to_le_bytes());
+    }
+}
+
+/// Borrowed [`TxIn`], referencing the transaction bytes it was parsed from.
+#[derive(Debug, Clone, Copy)]
+pub struct TxInRef<'a> {
+    /// Txid of the transaction holding the output being spent, all zeros for coinbase inputs.
+    prev_txid: &'a [u8],
+    /// Index of the output being spent, 0xFFFFFFFF for coinbase inputs.
+    prev_index: u32,
+    /// Script_sig of the input, without its CompactSize prefix.
+    script_sig: &'a [u8],
+    /// Sequence number of the input.
+    sequence: u32,
+}
+
+impl<'a> TxInRef<'a> {
+    /// Parses a transparent input, borrowing its byte fields from `data`.
+    pub fn parse(data: &'a [u8]) -> Result<(&'a [u8], Self), ParseError> {
         let mut cursor = Cursor::new(data);
 
-        skip_bytes(&mut cursor, 32, "Error skipping TxIn::PrevTxHash")?;
-        skip_bytes(&mut cursor, 4, "Error skipping TxIn::PrevTxOutIndex")?;
+        let prev_txid = read_slice(&mut cursor, 32, "Error reading TxIn::PrevTxHash")?;
+        let prev_index = read_u32(&mut cursor, "Error reading TxIn::PrevTxOutIndex")?;
         let script_sig = {
             let compact_length = CompactSize::read(&mut cursor)?;
-            read_bytes(
+            read_slice(
                 &mut cursor,
                 compact_length as usize,
                 "Error reading TxIn::ScriptSig",
             )?
         };
-        skip_bytes(&mut cursor, 4, "Error skipping TxIn::SequenceNumber")?;
+        let sequence = read_u32(&mut cursor, "Error reading TxIn::SequenceNumber")?;
 
-        Ok((&data[cursor.position() as usize..], TxIn { script_sig }))
+        Ok((
+            &data[cursor.position() as usize..],
+            TxInRef {
+                prev_txid,
+                prev_index,
+                script_sig,
+                sequence,
+            },
+        ))
+    }
+
+    /// Returns the txid of the transaction holding the output being spent.
+    pub fn prev_txid(&self) -> &'a [u8] {
+        self.prev_txid
+    }
+
+    /// Returns the index of the output being spent.
+    pub fn prev_index(&self) -> u32 {
+        self.prev_index
+    }
+
+    /// Returns the input's script_sig, for coinbase inputs this holds the block height and miner data.
+    pub fn script_sig(&self) -> &'a [u8] {
+        self.script_sig
+    }
+
+    /// Returns the input's sequence number.
+    pub fn sequence(&self) -> u32 {
+        self.sequence
+    }
+
+    /// Returns true if this is a coinbase input (spends the null outpoint).
+    pub fn is_coinbase(&self) -> bool {
+        self.prev_index == u32::MAX && self.prev_txid.iter().all(|byte| *byte == 0)
+    }
+
+    /// Copies the input into an owned [`TxIn`].
+    pub fn into_owned(self) -> TxIn {
+        TxIn {
+            prev_txid: self.prev_txid.to_vec(),
+            prev_index: self.prev_index,
+            script_sig: self.script_sig.to_vec(),
+            sequence: self.sequence,
+        }
+    }
+}
+
+/// Standard transparent output script templates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptType {
+    /// Pay to public key hash, holds the 20 byte public key hash.
+    ///
+    /// OP_DUP OP_HASH160 <20 bytes> OP_EQUALVERIFY OP_CHECKSIG
+    P2pkh([u8; 20]),
+    /// Pay to script hash, holds the 20 byte script hash.
+    ///
+    /// OP_HASH160 <20 bytes> OP_EQUAL
+    P2sh([u8; 20]),
+    /// Any other script.
+    NonStandard,
+}
+
+impl ScriptType {
+    /// Classifies a script_pubkey.
+    pub fn from_script(script: &[u8]) -> Self {
+        match script {
+            [0x76, 0xa9, 0x14, hash @ .., 0x88, 0xac] if hash.len() == 20 => {
+                let mut pub_key_hash = [0u8; 20];
+                pub_key_hash.copy_from_slice(hash);
+                ScriptType::P2pkh(pub_key_hash)
+            }
+            [0xa9, 0x14, hash @ .., 0x87] if hash.len() == 20 => {
+                let mut script_hash = [0u8; 20];
+                script_hash.copy_from_slice(hash);
+                ScriptType::P2sh(script_hash)
+            }
+            _ => ScriptType::NonStandard,
+        }
     }
 }
 
 /// Txout format as described in https://en.bitcoin.it/wiki/Transaction
 #[derive(Debug, Clone)]
-struct TxOut {
+pub struct TxOut {
     /// Non-negative int giving the number of zatoshis to be transferred
     ///
     /// Size[bytes]: 8
     value: u64,
-    // Script [IGNORED] - Size[bytes]: CompactSize
+    /// CompactSize-prefixed script the output is locked by.
+    ///
+    /// Size[bytes]: CompactSize
+    script_pubkey: Vec<u8>,
 }
 
 impl TxOut {
-    fn into_inner(self) -> u64 {
+    /// Returns the value of the output in zatoshis.
+    pub fn value(&self) -> u64 {
         self.value
     }
+
+    /// Returns the output's script_pubkey.
+    pub fn script_pubkey(&self) -> Vec<u8> {
+        self.script_pubkey.clone()
+    }
+
+    /// Returns the type of the output's script_pubkey.
+    pub fn script_type(&self) -> ScriptType {
+        ScriptType::from_script(&self.script_pubkey)
+    }
+
+    /// Returns the transparent address the output pays to on the given network, None for non-standard scripts.
+    pub fn address(&self, network: &Network) -> Option<Address> {
+        match self.script_type() {
+            ScriptType::P2pkh(pub_key_hash) => {
+                Some(Address::from_pub_key_hash(network.kind(), pub_key_hash))
+            }
+            ScriptType::P2sh(script_hash) => {
+                Some(Address::from_script_hash(network.kind(), script_hash))
+            }
+            ScriptType::NonStandard => None,
+        }
+    }
 }
 
 impl ParseFromSlice for TxOut {
@@ -93,37 +295,101 @@ impl ParseFromSlice for TxOut {
                 "tx_version must be None for TxOut::parse_from_slice".to_string(),
             ));
         }
+        let (remaining_data, tx_out) = TxOutRef::parse(data)?;
+        Ok((remaining_data, tx_out.into_owned()))
+    }
+}
+
+impl SerializeToVec for TxOut {
+    fn write_to_vec(&self, buffer: &mut Vec<u8>) {
+        buffer.extend(&self.value.to_le_bytes());
+        write_compact_size(buffer, self.script_pubkey.len());
+        buffer.extend(&self.script_pubkey);
+    }
+}
+
+/// Borrowed [`TxOut`], referencing the transaction bytes it was parsed from.
+#[derive(Debug, Clone, Copy)]
+pub struct TxOutRef<'a> {
+    /// Number of zatoshis transferred.
+    value: u64,
+    /// Script the output is locked by, without its CompactSize prefix.
+    script_pubkey: &'a [u8],
+}
+
+impl<'a> TxOutRef<'a> {
+    /// Parses a transparent output, borrowing its script from `data`.
+    pub fn parse(data: &'a [u8]) -> Result<(&'a [u8], Self), ParseError> {
         let mut cursor = Cursor::new(data);
 
         let value = read_u64(&mut cursor, "Error TxOut::reading Value")?;
-        let compact_length = CompactSize::read(&mut cursor)?;
-        skip_bytes(
-            &mut cursor,
-            compact_length as usize,
-            "Error skipping TxOut::Script",
-        )?;
+        let script_pubkey = {
+            let compact_length = CompactSize::read(&mut cursor)?;
+            read_slice(
+                &mut cursor,
+                compact_length as usize,
+                "Error reading TxOut::Script",
+            )?
+        };
+
+        Ok((
+            &data[cursor.position() as usize..],
+            TxOutRef {
+                value,
+                script_pubkey,
+            },
+        ))
+    }
+
+    /// Returns the value of the output in zatoshis.
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// Returns the output's script_pubkey.
+    pub fn script_pubkey(&self) -> &'a [u8] {
+        self.script_pubkey
+    }
+
+    /// Returns the type of the output's script_pubkey.
+    pub fn script_type(&self) -> ScriptType {
+        ScriptType::from_script(self.script_pubkey)
+    }
 
-        Ok((&data[cursor.position() as usize..], TxOut { value }))
+    /// Copies the output into an owned [`TxOut`].
+    pub fn into_owned(self) -> TxOut {
+        TxOut {
+            value: self.value,
+            script_pubkey: self.script_pubkey.to_vec(),
+        }
     }
 }
 
 #[allow(clippy::type_complexity)]
-fn parse_transparent(data: &[u8]) -> Result<(&[u8], Vec<TxIn>, Vec<TxOut>), ParseError> {
+fn parse_transparent(
+    data: &[u8],
+) -> Result<(&[u8], Vec<TxInRef<'_>>, Vec<TxOutRef<'_>>), ParseError> {
     let mut cursor = Cursor::new(data);
 
     let tx_in_count = CompactSize::read(&mut cursor)?;
-    let mut tx_ins = Vec::with_capacity(tx_in_count as usize);
+    let mut tx_ins = Vec::with_capacity(bounded_capacity(
+        tx_in_count,
+        data.len() - cursor.position() as usize,
+        MIN_TX_IN_SIZE,
+    ));
     for _ in 0..tx_in_count {
-        let (remaining_data, tx_in) =
-            TxIn::parse_from_slice(&data[cursor.position() as usize..], None, None)?;
+        let (remaining_data, tx_in) = TxInRef::parse(&data[cursor.position() as usize..])?;
         tx_ins.push(tx_in);
         cursor.set_position(data.len() as u64 - remaining_data.len() as u64);
     }
     let tx_out_count = CompactSize::read(&mut cursor)?;
-    let mut tx_outs = Vec::with_capacity(tx_out_count as usize);
+    let mut tx_outs = Vec::with_capacity(bounded_capacity(
+        tx_out_count,
+        data.len() - cursor.position() as usize,
+        MIN_TX_OUT_SIZE,
+    ));
     for _ in 0..tx_out_count {
-        let (remaining_data, tx_out) =
-            TxOut::parse_from_slice(&data[cursor.position() as usize..], None, None)?;
+        let (remaining_data, tx_out) = TxOutRef::parse(&data[cursor.position() as usize..])?;
         tx_outs.push(tx_out);
         cursor.set_position(data.len() as u64 - remaining_data.len() as u64);
     }
@@ -131,19 +397,46 @@ fn parse_transparent(data: &[u8]) -> Result<(&[u8], Vec<TxIn>, Vec<TxOut>), Pars
     Ok((&data[cursor.position() as usize..], tx_ins, tx_outs))
 }
 
+/// Writes the transparent inputs and outputs of a transaction.
+fn write_transparent(buffer: &mut Vec<u8>, tx_ins: &[TxIn], tx_outs: &[TxOut]) {
+    write_compact_size(buffer, tx_ins.len());
+    for tx_in in tx_ins.iter() {
+        tx_in.write_to_vec(buffer);
+    }
+    write_compact_size(buffer, tx_outs.len());
+    for tx_out in tx_outs.iter() {
+        tx_out.write_to_vec(buffer);
+    }
+}
+
 /// spend is a Sapling Spend Description as described in 7.3 of the Zcash
 /// protocol specification.
 #[derive(Debug, Clone)]
-struct Spend {
-    // Cv [IGNORED] - Size[bytes]: 32
-    // Anchor [IGNORED] - Size[bytes]: 32
+pub struct Spend {
+    /// Value commitment to the input note.
+    ///
+    /// Size[bytes]: 32
+    cv: Vec<u8>,
+    /// Sapling note commitment tree root, v4 only (v5 spends share `anchorSapling`).
+    ///
+    /// Size[bytes]: 32
+    anchor: Option<Vec<u8>>,
     /// A nullifier to a sapling note.
     ///
     /// Size[bytes]: 32
     nullifier: Vec<u8>,
-    // Rk [IGNORED] - Size[bytes]: 32
-    // Zkproof [IGNORED] - Size[bytes]: 192
-    // SpendAuthSig [IGNORED] - Size[bytes]: 64
+    /// Randomized validating key.
+    ///
+    /// Size[bytes]: 32
+    rk: Vec<u8>,
+    /// Groth16 spend proof, v4 only (v5 proofs are held in `vSpendProofsSapling`).
+    ///
+    /// Size[bytes]: 192
+    zkproof: Option<Vec<u8>>,
+    /// Spend authorization signature, v4 only (v5 signatures are held in `vSpendAuthSigsSapling`).
+    ///
+    /// Size[bytes]: 64
+    spend_auth_sig: Option<Vec<u8>>,
 }
 
 impl Spend {
@@ -168,28 +461,109 @@ impl ParseFromSlice for Spend {
                 "tx_version must be used for Spend::parse_from_slice".to_string(),
             )
         })?;
-        let mut cursor = Cursor::new(data);
+        let (remaining_data, spend) = SpendRef::parse(data, tx_version)?;
+        Ok((remaining_data, spend.into_owned()))
+    }
+}
 
-        skip_bytes(&mut cursor, 32, "Error skipping Spend::Cv")?;
-        if tx_version <= 4 {
-            skip_bytes(&mut cursor, 32, "Error skipping Spend::Anchor")?;
+impl SerializeToVec for Spend {
+    /// Writes the spend description in the format it was parsed from, v4 descriptions hold their anchor, proof and
+    /// signature.
+    fn write_to_vec(&self, buffer: &mut Vec<u8>) {
+        buffer.extend(&self.cv);
+        if let Some(anchor) = &self.anchor {
+            buffer.extend(anchor);
+        }
+        buffer.extend(&self.nullifier);
+        buffer.extend(&self.rk);
+        if let Some(zkproof) = &self.zkproof {
+            buffer.extend(zkproof);
         }
-        let nullifier = read_bytes(&mut cursor, 32, "Error reading Spend::nullifier")?;
-        skip_bytes(&mut cursor, 32, "Error skipping Spend::Rk")?;
-        if tx_version <= 4 {
-            skip_bytes(&mut cursor, 192, "Error skipping Spend::Zkproof")?;
-            skip_bytes(&mut cursor, 64, "Error skipping Spend::SpendAuthSig")?;
+        if let Some(spend_auth_sig) = &self.spend_auth_sig {
+            buffer.extend(spend_auth_sig);
         }
+    }
+}
+
+/// Borrowed [`Spend`], referencing the transaction bytes it was parsed from.
+#[derive(Debug, Clone, Copy)]
+struct SpendRef<'a> {
+    /// Value commitment to the input note.
+    cv: &'a [u8],
+    /// Sapling note commitment tree root, v4 only.
+    anchor: Option<&'a [u8]>,
+    /// A nullifier to a sapling note.
+    nullifier: &'a [u8],
+    /// Randomized validating key.
+    rk: &'a [u8],
+    /// Groth16 spend proof, v4 only.
+    zkproof: Option<&'a [u8]>,
+    /// Spend authorization signature, v4 only.
+    spend_auth_sig: Option<&'a [u8]>,
+}
+
+impl<'a> SpendRef<'a> {
+    fn parse(data: &'a [u8], tx_version: u32) -> Result<(&'a [u8], Self), ParseError> {
+        let mut cursor = Cursor::new(data);
+
+        let cv = read_slice(&mut cursor, 32, "Error reading Spend::Cv")?;
+        let anchor = if tx_version <= 4 {
+            Some(read_slice(&mut cursor, 32, "Error reading Spend::Anchor")?)
+        } else {
+            None
+        };
+        let nullifier = read_slice(&mut cursor, 32, "Error reading Spend::nullifier")?;
+        let rk = read_slice(&mut cursor, 32, "Error reading Spend::Rk")?;
+        let (zkproof, spend_auth_sig) = if tx_version <= 4 {
+            (
+                Some(read_slice(
+                    &mut cursor,
+                    192,
+                    "Error reading Spend::Zkproof",
+                )?),
+                Some(read_slice(
+                    &mut cursor,
+                    64,
+                    "Error reading Spend::SpendAuthSig",
+                )?),
+            )
+        } else {
+            (None, None)
+        };
 
-        Ok((&data[cursor.position() as usize..], Spend { nullifier }))
+        Ok((
+            &data[cursor.position() as usize..],
+            SpendRef {
+                cv,
+                anchor,
+                nullifier,
+                rk,
+                zkproof,
+                spend_auth_sig,
+            },
+        ))
+    }
+
+    fn into_owned(self) -> Spend {
+        Spend {
+            cv: self.cv.to_vec(),
+            anchor: self.anchor.map(|anchor| anchor.to_vec()),
+            nullifier: self.nullifier.to_vec(),
+            rk: self.rk.to_vec(),
+            zkproof: self.zkproof.map(|zkproof| zkproof.to_vec()),
+            spend_auth_sig: self.spend_auth_sig.map(|sig| sig.to_vec()),
+        }
     }
 }
 
 /// output is a Sapling Output Description as described in section 7.4 of the
 /// Zcash protocol spec.
 #[derive(Debug, Clone)]
-struct Output {
-    // Cv [IGNORED] - Size[bytes]: 32
+pub struct Output {
+    /// Value commitment to the output note.
+    ///
+    /// Size[bytes]: 32
+    cv: Vec<u8>,
     /// U-coordinate of the note commitment, derived from the note's value, recipient, and a
     /// random value.
     ///
@@ -203,8 +577,14 @@ struct Output {
     ///
     /// Size[bytes]: 580
     enc_ciphertext: Vec<u8>,
-    // OutCiphertext [IGNORED] - Size[bytes]: 80
-    // Zkproof [IGNORED] - Size[bytes]: 192
+    /// Encrypted output recovery details.
+    ///
+    /// Size[bytes]: 80
+    out_ciphertext: Vec<u8>,
+    /// Groth16 output proof, v4 only (v5 proofs are held in `vOutputProofsSapling`).
+    ///
+    /// Size[bytes]: 192
+    zkproof: Option<Vec<u8>>,
 }
 
 impl Output {
@@ -229,45 +609,174 @@ impl ParseFromSlice for Output {
                 "tx_version must be used for Output::parse_from_slice".to_string(),
             )
         })?;
-        let mut cursor = Cursor::new(data);
+        let (remaining_data, output) = OutputRef::parse(data, tx_version)?;
+        Ok((remaining_data, output.into_owned()))
+    }
+}
 
-        skip_bytes(&mut cursor, 32, "Error skipping Output::Cv")?;
-        let cmu = read_bytes(&mut cursor, 32, "Error reading Output::cmu")?;
-        let ephemeral_key = read_bytes(&mut cursor, 32, "Error reading Output::ephemeral_key")?;
-        let enc_ciphertext = read_bytes(&mut cursor, 580, "Error reading Output::enc_ciphertext")?;
-        skip_bytes(&mut cursor, 80, "Error skipping Output::OutCiphertext")?;
-        if tx_version <= 4 {
-            skip_bytes(&mut cursor, 192, "Error skipping Output::Zkproof")?;
+impl SerializeToVec for Output {
+    /// Writes the output description in the format it was parsed from, v4 descriptions hold their proof.
+    fn write_to_vec(&self, buffer: &mut Vec<u8>) {
+        buffer.extend(&self.cv);
+        buffer.extend(&self.cmu);
+        buffer.extend(&self.ephemeral_key);
+        buffer.extend(&self.enc_ciphertext);
+        buffer.extend(&self.out_ciphertext);
+        if let Some(zkproof) = &self.zkproof {
+            buffer.extend(zkproof);
         }
+    }
+}
+
+/// Borrowed [`Output`], referencing the transaction bytes it was parsed from.
+#[derive(Debug, Clone, Copy)]
+struct OutputRef<'a> {
+    /// Value commitment to the output note.
+    cv: &'a [u8],
+    /// U-coordinate of the note commitment.
+    cmu: &'a [u8],
+    /// Ephemeral public key for Diffie-Hellman key exchange.
+    ephemeral_key: &'a [u8],
+    /// Encrypted transaction details including value transferred and an optional memo.
+    enc_ciphertext: &'a [u8],
+    /// Encrypted output recovery details.
+    out_ciphertext: &'a [u8],
+    /// Groth16 output proof, v4 only.
+    zkproof: Option<&'a [u8]>,
+}
+
+impl<'a> OutputRef<'a> {
+    fn parse(data: &'a [u8], tx_version: u32) -> Result<(&'a [u8], Self), ParseError> {
+        let mut cursor = Cursor::new(data);
+
+        let cv = read_slice(&mut cursor, 32, "Error reading Output::Cv")?;
+        let cmu = read_slice(&mut cursor, 32, "Error reading Output::cmu")?;
+        let ephemeral_key = read_slice(&mut cursor, 32, "Error reading Output::ephemeral_key")?;
+        let enc_ciphertext = read_slice(
+            &mut cursor,
+            ENC_CIPHERTEXT_SIZE,
+            "Error reading Output::enc_ciphertext",
+        )?;
+        let out_ciphertext = read_slice(&mut cursor, 80, "Error reading Output::OutCiphertext")?;
+        let zkproof = if tx_version <= 4 {
+            Some(read_slice(
+                &mut cursor,
+                192,
+                "Error reading Output::Zkproof",
+            )?)
+        } else {
+            None
+        };
 
         Ok((
             &data[cursor.position() as usize..],
-            Output {
+            OutputRef {
+                cv,
                 cmu,
                 ephemeral_key,
                 enc_ciphertext,
+                out_ciphertext,
+                zkproof,
             },
         ))
     }
+
+    fn into_owned(self) -> Output {
+        Output {
+            cv: self.cv.to_vec(),
+            cmu: self.cmu.to_vec(),
+            ephemeral_key: self.ephemeral_key.to_vec(),
+            enc_ciphertext: self.enc_ciphertext.to_vec(),
+            out_ciphertext: self.out_ciphertext.to_vec(),
+            zkproof: self.zkproof.map(|zkproof| zkproof.to_vec()),
+        }
+    }
 }
 
 /// joinSplit is a JoinSplit description as described in 7.2 of the Zcash
 /// protocol spec. Its exact contents differ by transaction version and network
-/// upgrade level. Only version 4 is supported, no need for proofPHGR13.
-///
-/// NOTE: Legacy, no longer used but included for consistency.
+/// upgrade level, v2 and v3 transactions use BCTV14 (proofPHGR13) proofs and v4
+/// transactions use Groth16 proofs.
 #[derive(Debug, Clone)]
-struct JoinSplit {
-    //vpubOld [IGNORED] - Size[bytes]: 8
-    //vpubNew [IGNORED] - Size[bytes]: 8
-    //anchor [IGNORED] - Size[bytes]: 32
-    //nullifiers [IGNORED] - Size[bytes]: 64/32
-    //commitments [IGNORED] - Size[bytes]: 64/32
-    //ephemeralKey [IGNORED] - Size[bytes]: 32
-    //randomSeed [IGNORED] - Size[bytes]: 32
-    //vmacs [IGNORED] - Size[bytes]: 64/32
-    //proofGroth16 [IGNORED] - Size[bytes]: 192
-    //encCiphertexts [IGNORED] - Size[bytes]: 1202
+pub struct JoinSplit {
+    /// Value removed from the transparent value pool.
+    ///
+    /// Size[bytes]: 8
+    vpub_old: u64,
+    /// Value inserted into the transparent value pool.
+    ///
+    /// Size[bytes]: 8
+    vpub_new: u64,
+    /// Root of the Sprout note commitment tree at some block height in the past, or the root produced by a previous
+    /// JoinSplit in the transaction.
+    ///
+    /// Size[bytes]: 32
+    anchor: Vec<u8>,
+    /// Nullifiers of the two input notes.
+    ///
+    /// Size[bytes]: 64 (2*32)
+    nullifiers: Vec<Vec<u8>>,
+    /// Note commitments of the two output notes.
+    ///
+    /// Size[bytes]: 64 (2*32)
+    commitments: Vec<Vec<u8>>,
+    /// Ephemeral public key used to encrypt the output notes.
+    ///
+    /// Size[bytes]: 32
+    ephemeral_key: Vec<u8>,
+    /// Seed used to derive the input notes' h_sig.
+    ///
+    /// Size[bytes]: 32
+    random_seed: Vec<u8>,
+    /// MACs binding the input notes' spending keys to h_sig.
+    ///
+    /// Size[bytes]: 64 (2*32)
+    vmacs: Vec<u8>,
+    /// Zero knowledge proof, BCTV14 (proofPHGR13) for v2-v3 and Groth16 for v4 transactions.
+    ///
+    /// Size[bytes]: 296 (v2-v3) / 192 (v4)
+    proof: Vec<u8>,
+    /// Encrypted output notes.
+    ///
+    /// Size[bytes]: 1202 (2*601)
+    enc_ciphertexts: Vec<Vec<u8>>,
+}
+
+impl JoinSplit {
+    /// Returns the value removed from the transparent value pool.
+    pub fn vpub_old(&self) -> u64 {
+        self.vpub_old
+    }
+
+    /// Returns the value inserted into the transparent value pool.
+    pub fn vpub_new(&self) -> u64 {
+        self.vpub_new
+    }
+
+    /// Returns the Sprout note commitment tree root the JoinSplit was created against.
+    pub fn anchor(&self) -> Vec<u8> {
+        self.anchor.clone()
+    }
+
+    /// Returns the nullifiers of the two input notes.
+    pub fn nullifiers(&self) -> Vec<Vec<u8>> {
+        self.nullifiers.clone()
+    }
+
+    /// Returns the note commitments of the two output notes.
+    pub fn commitments(&self) -> Vec<Vec<u8>> {
+        self.commitments.clone()
+    }
+
+    /// Returns the ephemeral public key used to encrypt the output notes.
+    pub fn ephemeral_key(&self) -> Vec<u8> {
+        self.ephemeral_key.clone()
+    }
+
+    /// Returns the two encrypted output notes.
+    pub fn enc_ciphertexts(&self) -> Vec<Vec<u8>> {
+        self.enc_ciphertexts.clone()
+    }
 }
 
 impl ParseFromSlice for JoinSplit {
@@ -281,41 +790,237 @@ impl ParseFromSlice for JoinSplit {
                 "txid must be None for JoinSplit::parse_from_slice".to_string(),
             ));
         }
-        if tx_version.is_some() {
-            return Err(ParseError::InvalidData(
-                "tx_version must be None for JoinSplit::parse_from_slice".to_string(),
-            ));
+        let tx_version = tx_version.ok_or_else(|| {
+            ParseError::InvalidData(
+                "tx_version must be used for JoinSplit::parse_from_slice".to_string(),
+            )
+        })?;
+        let (remaining_data, join_split) = JoinSplitRef::parse(data, tx_version)?;
+        Ok((remaining_data, join_split.into_owned()))
+    }
+}
+
+impl SerializeToVec for JoinSplit {
+    fn write_to_vec(&self, buffer: &mut Vec<u8>) {
+        buffer.extend(&self.vpub_old.to_le_bytes());
+        buffer.extend(&self.vpub_new.to_le_bytes());
+        buffer.extend(&self.anchor);
+        for nullifier in self.nullifiers.iter() {
+            buffer.extend(nullifier);
+        }
+        for commitment in self.commitments.iter() {
+            buffer.extend(commitment);
+        }
+        buffer.extend(&self.ephemeral_key);
+        buffer.extend(&self.random_seed);
+        buffer.extend(&self.vmacs);
+        buffer.extend(&self.proof);
+        for enc_ciphertext in self.enc_ciphertexts.iter() {
+            buffer.extend(enc_ciphertext);
         }
+    }
+}
+
+/// Borrowed [`JoinSplit`], referencing the transaction bytes it was parsed from.
+#[derive(Debug, Clone, Copy)]
+pub struct JoinSplitRef<'a> {
+    /// Value removed from the transparent value pool.
+    vpub_old: u64,
+    /// Value inserted into the transparent value pool.
+    vpub_new: u64,
+    /// Root of the Sprout note commitment tree the JoinSplit was created against.
+    anchor: &'a [u8],
+    /// Nullifiers of the two input notes.
+    nullifiers: [&'a [u8]; 2],
+    /// Note commitments of the two output notes.
+    commitments: [&'a [u8]; 2],
+    /// Ephemeral public key used to encrypt the output notes.
+    ephemeral_key: &'a [u8],
+    /// Seed used to derive the input notes' h_sig.
+    random_seed: &'a [u8],
+    /// MACs binding the input notes' spending keys to h_sig.
+    vmacs: &'a [u8],
+    /// Zero knowledge proof.
+    proof: &'a [u8],
+    /// Encrypted output notes.
+    enc_ciphertexts: [&'a [u8]; 2],
+}
+
+impl<'a> JoinSplitRef<'a> {
+    /// Parses a JoinSplit description of a v2-v4 transaction, borrowing its byte fields from `data`.
+    pub fn parse(data: &'a [u8], tx_version: u32) -> Result<(&'a [u8], Self), ParseError> {
         let mut cursor = Cursor::new(data);
 
-        skip_bytes(&mut cursor, 8, "Error skipping JoinSplit::vpubOld")?;
-        skip_bytes(&mut cursor, 8, "Error skipping JoinSplit::vpubNew")?;
-        skip_bytes(&mut cursor, 32, "Error skipping JoinSplit::anchor")?;
-        skip_bytes(&mut cursor, 64, "Error skipping JoinSplit::nullifiers")?;
-        skip_bytes(&mut cursor, 64, "Error skipping JoinSplit::commitments")?;
-        skip_bytes(&mut cursor, 32, "Error skipping JoinSplit::ephemeralKey")?;
-        skip_bytes(&mut cursor, 32, "Error skipping JoinSplit::randomSeed")?;
-        skip_bytes(&mut cursor, 64, "Error skipping JoinSplit::vmacs")?;
-        skip_bytes(&mut cursor, 192, "Error skipping JoinSplit::proofGroth16")?;
-        skip_bytes(
+        let vpub_old = read_u64(&mut cursor, "Error reading JoinSplit::vpubOld")?;
+        let vpub_new = read_u64(&mut cursor, "Error reading JoinSplit::vpubNew")?;
+        let anchor = read_slice(&mut cursor, 32, "Error reading JoinSplit::anchor")?;
+        let nullifiers = [
+            read_slice(&mut cursor, 32, "Error reading JoinSplit::nullifiers")?,
+            read_slice(&mut cursor, 32, "Error reading JoinSplit::nullifiers")?,
+        ];
+        let commitments = [
+            read_slice(&mut cursor, 32, "Error reading JoinSplit::commitments")?,
+            read_slice(&mut cursor, 32, "Error reading JoinSplit::commitments")?,
+        ];
+        let ephemeral_key = read_slice(&mut cursor, 32, "Error reading JoinSplit::ephemeralKey")?;
+        let random_seed = read_slice(&mut cursor, 32, "Error reading JoinSplit::randomSeed")?;
+        let vmacs = read_slice(&mut cursor, 64, "Error reading JoinSplit::vmacs")?;
+        let proof = if tx_version <= 3 {
+            read_slice(&mut cursor, 296, "Error reading JoinSplit::proofPHGR13")?
+        } else {
+            read_slice(&mut cursor, 192, "Error reading JoinSplit::proofGroth16")?
+        };
+        let enc_ciphertexts = [
+            read_slice(&mut cursor, 601, "Error reading JoinSplit::encCiphertexts")?,
+            read_slice(&mut cursor, 601, "Error reading JoinSplit::encCiphertexts")?,
+        ];
+
+        Ok((
+            &data[cursor.position() as usize..],
+            JoinSplitRef {
+                vpub_old,
+                vpub_new,
+                anchor,
+                nullifiers,
+                commitments,
+                ephemeral_key,
+                random_seed,
+                vmacs,
+                proof,
+                enc_ciphertexts,
+            },
+        ))
+    }
+
+    /// Returns the value removed from the transparent value pool.
+    pub fn vpub_old(&self) -> u64 {
+        self.vpub_old
+    }
+
+    /// Returns the value inserted into the transparent value pool.
+    pub fn vpub_new(&self) -> u64 {
+        self.vpub_new
+    }
+
+    /// Returns the Sprout note commitment tree root the JoinSplit was created against.
+    pub fn anchor(&self) -> &'a [u8] {
+        self.anchor
+    }
+
+    /// Returns the nullifiers of the two input notes.
+    pub fn nullifiers(&self) -> [&'a [u8]; 2] {
+        self.nullifiers
+    }
+
+    /// Returns the note commitments of the two output notes.
+    pub fn commitments(&self) -> [&'a [u8]; 2] {
+        self.commitments
+    }
+
+    /// Returns the ephemeral public key used to encrypt the output notes.
+    pub fn ephemeral_key(&self) -> &'a [u8] {
+        self.ephemeral_key
+    }
+
+    /// Returns the two encrypted output notes.
+    pub fn enc_ciphertexts(&self) -> [&'a [u8]; 2] {
+        self.enc_ciphertexts
+    }
+
+    /// Copies the JoinSplit description into an owned [`JoinSplit`].
+    pub fn into_owned(self) -> JoinSplit {
+        JoinSplit {
+            vpub_old: self.vpub_old,
+            vpub_new: self.vpub_new,
+            anchor: self.anchor.to_vec(),
+            nullifiers: self.nullifiers.iter().map(|nf| nf.to_vec()).collect(),
+            commitments: self.commitments.iter().map(|cm| cm.to_vec()).collect(),
+            ephemeral_key: self.ephemeral_key.to_vec(),
+            random_seed: self.random_seed.to_vec(),
+            vmacs: self.vmacs.to_vec(),
+            proof: self.proof.to_vec(),
+            enc_ciphertexts: self.enc_ciphertexts.iter().map(|ct| ct.to_vec()).collect(),
+        }
+    }
+}
+
+/// Parses the JoinSplit descriptions of a v2-v4 transaction, along with the joinSplitPubKey and joinSplitSig (present
+/// if there are any JoinSplits).
+#[allow(clippy::type_complexity)]
+fn parse_join_splits(
+    data: &[u8],
+    tx_version: u32,
+) -> Result<(&[u8], Vec<JoinSplitRef<'_>>, Option<(&[u8], &[u8])>), ParseError> {
+    let mut cursor = Cursor::new(data);
+
+    let join_split_count = CompactSize::read(&mut cursor)?;
+    let mut join_splits = Vec::with_capacity(bounded_capacity(
+        join_split_count,
+        data.len() - cursor.position() as usize,
+        MIN_JOIN_SPLIT_SIZE,
+    ));
+    for _ in 0..join_split_count {
+        let (remaining_data, join_split) =
+            JoinSplitRef::parse(&data[cursor.position() as usize..], tx_version)?;
+        join_splits.push(join_split);
+        cursor.set_position(data.len() as u64 - remaining_data.len() as u64);
+    }
+
+    let join_split_signature = if join_split_count > 0 {
+        let pub_key = read_slice(
+            &mut cursor,
+            32,
+            "Error reading TransactionData::joinSplitPubKey",
+        )?;
+        let sig = read_slice(
             &mut cursor,
-            1202,
-            "Error skipping JoinSplit::encCiphertexts",
+            64,
+            "Error reading TransactionData::joinSplitSig",
         )?;
+        Some((pub_key, sig))
+    } else {
+        None
+    };
+
+    Ok((
+        &data[cursor.position() as usize..],
+        join_splits,
+        join_split_signature,
+    ))
+}
 
-        Ok((&data[cursor.position() as usize..], JoinSplit {}))
+/// Writes the JoinSplit descriptions of a v2-v4 transaction, along with the joinSplitPubKey and joinSplitSig.
+fn write_join_splits(
+    buffer: &mut Vec<u8>,
+    join_splits: &[JoinSplit],
+    join_split_pub_key: Option<&[u8]>,
+    join_split_sig: Option<&[u8]>,
+) {
+    write_compact_size(buffer, join_splits.len());
+    for join_split in join_splits.iter() {
+        join_split.write_to_vec(buffer);
+    }
+    if let (Some(pub_key), Some(sig)) = (join_split_pub_key, join_split_sig) {
+        buffer.extend(pub_key);
+        buffer.extend(sig);
     }
 }
 
 /// An Orchard action.
 #[derive(Debug, Clone)]
-struct Action {
-    // Cv [IGNORED] - Size[bytes]: 32
+pub struct Action {
+    /// Value commitment to the net value of the input and output notes.
+    ///
+    /// Size[bytes]: 32
+    cv: Vec<u8>,
     /// A nullifier to a orchard note.
     ///
     /// Size[bytes]: 32
     nullifier: Vec<u8>,
-    // Rk [IGNORED] - Size[bytes]: 32
+    /// Randomized validating key.
+    ///
+    /// Size[bytes]: 32
+    rk: Vec<u8>,
     /// X-coordinate of the commitment to the note.
     ///
     /// Size[bytes]: 32
@@ -328,7 +1033,10 @@ struct Action {
     ///
     /// Size[bytes]: 580
     enc_ciphertext: Vec<u8>,
-    // OutCiphertext [IGNORED] - Size[bytes]: 80
+    /// Encrypted output recovery details.
+    ///
+    /// Size[bytes]: 80
+    out_ciphertext: Vec<u8>,
 }
 
 impl Action {
@@ -358,26 +1066,83 @@ impl ParseFromSlice for Action {
                 "tx_version must be None for Action::parse_from_slice".to_string(),
             ));
         }
+        let (remaining_data, action) = ActionRef::parse(data)?;
+        Ok((remaining_data, action.into_owned()))
+    }
+}
+
+impl SerializeToVec for Action {
+    fn write_to_vec(&self, buffer: &mut Vec<u8>) {
+        buffer.extend(&self.cv);
+        buffer.extend(&self.nullifier);
+        buffer.extend(&self.rk);
+        buffer.extend(&self.cmx);
+        buffer.extend(&self.ephemeral_key);
+        buffer.extend(&self.enc_ciphertext);
+        buffer.extend(&self.out_ciphertext);
+    }
+}
+
+/// Borrowed [`Action`], referencing the transaction bytes it was parsed from.
+#[derive(Debug, Clone, Copy)]
+struct ActionRef<'a> {
+    /// Value commitment to the net value of the input and output notes.
+    cv: &'a [u8],
+    /// A nullifier to a orchard note.
+    nullifier: &'a [u8],
+    /// Randomized validating key.
+    rk: &'a [u8],
+    /// X-coordinate of the commitment to the note.
+    cmx: &'a [u8],
+    /// Ephemeral public key.
+    ephemeral_key: &'a [u8],
+    /// Encrypted details of the new note, including its value and recipient's data.
+    enc_ciphertext: &'a [u8],
+    /// Encrypted output recovery details.
+    out_ciphertext: &'a [u8],
+}
+
+impl<'a> ActionRef<'a> {
+    fn parse(data: &'a [u8]) -> Result<(&'a [u8], Self), ParseError> {
         let mut cursor = Cursor::new(data);
 
-        skip_bytes(&mut cursor, 32, "Error skipping Action::Cv")?;
-        let nullifier = read_bytes(&mut cursor, 32, "Error reading Action::nullifier")?;
-        skip_bytes(&mut cursor, 32, "Error skipping Action::Rk")?;
-        let cmx = read_bytes(&mut cursor, 32, "Error reading Action::cmx")?;
-        let ephemeral_key = read_bytes(&mut cursor, 32, "Error reading Action::ephemeral_key")?;
-        let enc_ciphertext = read_bytes(&mut cursor, 580, "Error reading Action::enc_ciphertext")?;
-        skip_bytes(&mut cursor, 80, "Error skipping Action::OutCiphertext")?;
+        let cv = read_slice(&mut cursor, 32, "Error reading Action::Cv")?;
+        let nullifier = read_slice(&mut cursor, 32, "Error reading Action::nullifier")?;
+        let rk = read_slice(&mut cursor, 32, "Error reading Action::Rk")?;
+        let cmx = read_slice(&mut cursor, 32, "Error reading Action::cmx")?;
+        let ephemeral_key = read_slice(&mut cursor, 32, "Error reading Action::ephemeral_key")?;
+        let enc_ciphertext = read_slice(
+            &mut cursor,
+            ENC_CIPHERTEXT_SIZE,
+            "Error reading Action::enc_ciphertext",
+        )?;
+        let out_ciphertext = read_slice(&mut cursor, 80, "Error reading Action::OutCiphertext")?;
 
         Ok((
             &data[cursor.position() as usize..],
-            Action {
+            ActionRef {
+                cv,
                 nullifier,
+                rk,
                 cmx,
                 ephemeral_key,
                 enc_ciphertext,
+                out_ciphertext,
             },
         ))
     }
+
+    fn into_owned(self) -> Action {
+        Action {
+            cv: self.cv.to_vec(),
+            nullifier: self.nullifier.to_vec(),
+            rk: self.rk.to_vec(),
+            cmx: self.cmx.to_vec(),
+            ephemeral_key: self.ephemeral_key.to_vec(),
+            enc_ciphertext: self.enc_ciphertext.to_vec(),
+            out_ciphertext: self.out_ciphertext.to_vec(),
+        }
+    }
 }
 
 /// Full Zcash Transactrion data.
@@ -407,9 +1172,22 @@ struct TransactionData {
     ///
     /// Size[bytes]: Vec<8+CompactSize>
     transparent_outputs: Vec<TxOut>,
-    // NLockTime [IGNORED] - Size[bytes]: 4
-    // NExpiryHeight [IGNORED] - Size[bytes]: 4
-    // ValueBalanceSapling [IGNORED] - Size[bytes]: 8
+    /// Earliest block height or time the transaction can be mined at.
+    ///
+    /// Size[bytes]: 4
+    lock_time: u32,
+    /// Block height after which the transaction expires, 0 if the transaction does not expire (v3 onwards).
+    ///
+    /// Size[bytes]: 4
+    expiry_height: u32,
+    /// Net value of Sapling spends minus outputs (v4 onwards).
+    ///
+    /// Size[bytes]: 8
+    value_balance_sapling: i64,
+    /// Sapling note commitment tree root shared by all spends (v5 onwards, spends only).
+    ///
+    /// Size[bytes]: 32
+    anchor_sapling: Option<Vec<u8>>,
     /// List of shielded spends from the Sapling pool
     ///
     /// Size[bytes]: Vec<384>
@@ -418,26 +1196,308 @@ struct TransactionData {
     ///
     /// Size[bytes]: Vec<948>
     shielded_outputs: Vec<Output>,
-    /// List of JoinSplit descriptions in a transaction, no longer supported.
+    /// List of JoinSplit descriptions in a transaction (v2-v4).
     ///
-    /// Size[bytes]: Vec<1602-1698>
-    #[allow(dead_code)]
+    /// Size[bytes]: Vec<1802 (v2-v3) / 1698 (v4)>
     join_splits: Vec<JoinSplit>,
-    //joinSplitPubKey [IGNORED] - Size[bytes]: 32
-    //joinSplitSig [IGNORED] - Size[bytes]: 64
-    //bindingSigSapling [IGNORED] - Size[bytes]: 64
+    /// Key used to sign the JoinSplit descriptions (JoinSplits only).
+    ///
+    /// Size[bytes]: 32
+    join_split_pub_key: Option<Vec<u8>>,
+    /// Signature over the transaction by `join_split_pub_key` (JoinSplits only).
+    ///
+    /// Size[bytes]: 64
+    join_split_sig: Option<Vec<u8>>,
+    /// Spend proofs of a v5 transaction, in spend order.
+    ///
+    /// Size[bytes]: 192*spendCount
+    spend_proofs_sapling: Vec<u8>,
+    /// Spend authorization signatures of a v5 transaction, in spend order.
+    ///
+    /// Size[bytes]: 64*spendCount
+    spend_auth_sigs_sapling: Vec<u8>,
+    /// Output proofs of a v5 transaction, in output order.
+    ///
+    /// Size[bytes]: 192*outputCount
+    output_proofs_sapling: Vec<u8>,
+    /// Sapling binding signature (v4 onwards, spends or outputs only).
+    ///
+    /// Size[bytes]: 64
+    binding_sig_sapling: Option<Vec<u8>>,
     ///List of Orchard actions.
     ///
     /// Size[bytes]: Vec<820>
     orchard_actions: Vec<Action>,
+    /// Orchard flags (enableSpends, enableOutputs), v5 onwards.
+    ///
+    /// Size[bytes]: 1
+    flags_orchard: u8,
+    /// Net value of Orchard spends minus outputs (v5 onwards).
+    ///
+    /// Size[bytes]: 8
+    value_balance_orchard: i64,
+    /// Orchard note commitment tree root (v5 onwards, actions only).
+    ///
+    /// Size[bytes]: 32
+    anchor_orchard: Option<Vec<u8>>,
+    /// Aggregated Halo 2 proof of the Orchard actions (v5 onwards, actions only).
+    ///
+    /// Size[bytes]: CompactSize
+    proofs_orchard: Vec<u8>,
+    /// Spend authorization signatures of the Orchard actions, in action order.
+    ///
+    /// Size[bytes]: 64*actionsCount
+    spend_auth_sigs_orchard: Vec<u8>,
+    /// Orchard binding signature (v5 onwards, actions only).
+    ///
+    /// Size[bytes]: 64
+    binding_sig_orchard: Option<Vec<u8>>,
+}
+
+impl SerializeToVec for TransactionData {
+    fn write_to_vec(&self, buffer: &mut Vec<u8>) {
+        let header = if self.f_overwintered {
+            self.version | (1 << 31)
+        } else {
+            self.version
+        };
+        buffer.extend(&header.to_le_bytes());
+        if self.f_overwintered {
+            buffer.extend(&self.n_version_group_id.to_le_bytes());
+        }
+        if self.version >= 5 {
+            self.write_v5(buffer);
+            return;
+        }
+
+        write_transparent(buffer, &self.transparent_inputs, &self.transparent_outputs);
+        buffer.extend(&self.lock_time.to_le_bytes());
+        if self.f_overwintered {
+            buffer.extend(&self.expiry_height.to_le_bytes());
+        }
+        if self.version >= 4 {
+            buffer.extend(&self.value_balance_sapling.to_le_bytes());
+            write_compact_size(buffer, self.shielded_spends.len());
+            for spend in self.shielded_spends.iter() {
+                spend.write_to_vec(buffer);
+            }
+            write_compact_size(buffer, self.shielded_outputs.len());
+            for output in self.shielded_outputs.iter() {
+                output.write_to_vec(buffer);
+            }
+        }
+        if self.version >= 2 {
+            write_join_splits(
+                buffer,
+                &self.join_splits,
+                self.join_split_pub_key.as_deref(),
+                self.join_split_sig.as_deref(),
+            );
+        }
+        if let Some(binding_sig) = &self.binding_sig_sapling {
+            buffer.extend(binding_sig);
+        }
+    }
 }
 
-impl TransactionData {
+impl TransactionData {
+    /// Writes the fields of a v5 transaction following the header and version group id.
+    fn write_v5(&self, buffer: &mut Vec<u8>) {
+        buffer.extend(&self.consensus_branch_id.to_le_bytes());
+        buffer.extend(&self.lock_time.to_le_bytes());
+        buffer.extend(&self.expiry_height.to_le_bytes());
+        write_transparent(buffer, &self.transparent_inputs, &self.transparent_outputs);
+
+        write_compact_size(buffer, self.shielded_spends.len());
+        for spend in self.shielded_spends.iter() {
+            spend.write_to_vec(buffer);
+        }
+        write_compact_size(buffer, self.shielded_outputs.len());
+        for output in self.shielded_outputs.iter() {
+            output.write_to_vec(buffer);
+        }
+        if !self.shielded_spends.is_empty() || !self.shielded_outputs.is_empty() {
+            buffer.extend(&self.value_balance_sapling.to_le_bytes());
+        }
+        if let Some(anchor) = &self.anchor_sapling {
+            buffer.extend(anchor);
+        }
+        buffer.extend(&self.spend_proofs_sapling);
+        buffer.extend(&self.spend_auth_sigs_sapling);
+        buffer.extend(&self.output_proofs_sapling);
+        if let Some(binding_sig) = &self.binding_sig_sapling {
+            buffer.extend(binding_sig);
+        }
+
+        write_compact_size(buffer, self.orchard_actions.len());
+        for action in self.orchard_actions.iter() {
+            action.write_to_vec(buffer);
+        }
+        if !self.orchard_actions.is_empty() {
+            buffer.push(self.flags_orchard);
+            buffer.extend(&self.value_balance_orchard.to_le_bytes());
+            if let Some(anchor) = &self.anchor_orchard {
+                buffer.extend(anchor);
+            }
+            write_compact_size(buffer, self.proofs_orchard.len());
+            buffer.extend(&self.proofs_orchard);
+            buffer.extend(&self.spend_auth_sigs_orchard);
+            if let Some(binding_sig) = &self.binding_sig_orchard {
+                buffer.extend(binding_sig);
+            }
+        }
+    }
+}
+
+/// Borrowed [`TransactionData`], referencing the transaction bytes it was parsed from.
+///
+/// Fields are as documented on [`TransactionData`].
+#[derive(Debug, Clone)]
+struct TransactionDataRef<'a> {
+    f_overwintered: bool,
+    version: u32,
+    n_version_group_id: u32,
+    consensus_branch_id: u32,
+    transparent_inputs: Vec<TxInRef<'a>>,
+    transparent_outputs: Vec<TxOutRef<'a>>,
+    lock_time: u32,
+    expiry_height: u32,
+    value_balance_sapling: i64,
+    anchor_sapling: Option<&'a [u8]>,
+    shielded_spends: Vec<SpendRef<'a>>,
+    shielded_outputs: Vec<OutputRef<'a>>,
+    join_splits: Vec<JoinSplitRef<'a>>,
+    join_split_pub_key: Option<&'a [u8]>,
+    join_split_sig: Option<&'a [u8]>,
+    spend_proofs_sapling: &'a [u8],
+    spend_auth_sigs_sapling: &'a [u8],
+    output_proofs_sapling: &'a [u8],
+    binding_sig_sapling: Option<&'a [u8]>,
+    orchard_actions: Vec<ActionRef<'a>>,
+    flags_orchard: u8,
+    value_balance_orchard: i64,
+    anchor_orchard: Option<&'a [u8]>,
+    proofs_orchard: &'a [u8],
+    spend_auth_sigs_orchard: &'a [u8],
+    binding_sig_orchard: Option<&'a [u8]>,
+}
+
+impl<'a> TransactionDataRef<'a> {
+    /// Parses a pre-Overwinter (v1 or v2) transaction, v2 transactions may contain JoinSplits.
+    fn parse_v1_v2(data: &'a [u8], version: u32) -> Result<(&'a [u8], Self), ParseError> {
+        let mut cursor = Cursor::new(data);
+
+        let (remaining_data, transparent_inputs, transparent_outputs) =
+            parse_transparent(&data[cursor.position() as usize..])?;
+        cursor.set_position(data.len() as u64 - remaining_data.len() as u64);
+
+        let lock_time = read_u32(&mut cursor, "Error reading TransactionData::nLockTime")?;
+
+        let (join_splits, join_split_signature) = if version >= 2 {
+            let (remaining_data, join_splits, join_split_signature) =
+                parse_join_splits(&data[cursor.position() as usize..], version)?;
+            cursor.set_position(data.len() as u64 - remaining_data.len() as u64);
+            (join_splits, join_split_signature)
+        } else {
+            (Vec::new(), None)
+        };
+
+        Ok((
+            &data[cursor.position() as usize..],
+            TransactionDataRef {
+                f_overwintered: false,
+                version,
+                n_version_group_id: 0,
+                consensus_branch_id: 0,
+                transparent_inputs,
+                transparent_outputs,
+                lock_time,
+                expiry_height: 0,
+                value_balance_sapling: 0,
+                anchor_sapling: None,
+                shielded_spends: Vec::new(),
+                shielded_outputs: Vec::new(),
+                join_splits,
+                join_split_pub_key: join_split_signature.map(|(pub_key, _)| pub_key),
+                join_split_sig: join_split_signature.map(|(_, sig)| sig),
+                spend_proofs_sapling: &[],
+                spend_auth_sigs_sapling: &[],
+                output_proofs_sapling: &[],
+                binding_sig_sapling: None,
+                orchard_actions: Vec::new(),
+                flags_orchard: 0,
+                value_balance_orchard: 0,
+                anchor_orchard: None,
+                proofs_orchard: &[],
+                spend_auth_sigs_orchard: &[],
+                binding_sig_orchard: None,
+            },
+        ))
+    }
+
+    /// Parses an Overwinter (v3) transaction.
+    fn parse_v3(
+        data: &'a [u8],
+        version: u32,
+        n_version_group_id: u32,
+    ) -> Result<(&'a [u8], Self), ParseError> {
+        if n_version_group_id != 0x03C48270 {
+            return Err(ParseError::InvalidData(format!(
+                "version group ID {:x} must be 0x03C48270 for v3 transactions",
+                n_version_group_id
+            )));
+        }
+        let mut cursor = Cursor::new(data);
+
+        let (remaining_data, transparent_inputs, transparent_outputs) =
+            parse_transparent(&data[cursor.position() as usize..])?;
+        cursor.set_position(data.len() as u64 - remaining_data.len() as u64);
+
+        let lock_time = read_u32(&mut cursor, "Error reading TransactionData::nLockTime")?;
+        let expiry_height = read_u32(&mut cursor, "Error reading TransactionData::nExpiryHeight")?;
+
+        let (remaining_data, join_splits, join_split_signature) =
+            parse_join_splits(&data[cursor.position() as usize..], version)?;
+        cursor.set_position(data.len() as u64 - remaining_data.len() as u64);
+
+        Ok((
+            &data[cursor.position() as usize..],
+            TransactionDataRef {
+                f_overwintered: true,
+                version,
+                n_version_group_id,
+                consensus_branch_id: 0,
+                transparent_inputs,
+                transparent_outputs,
+                lock_time,
+                expiry_height,
+                value_balance_sapling: 0,
+                anchor_sapling: None,
+                shielded_spends: Vec::new(),
+                shielded_outputs: Vec::new(),
+                join_splits,
+                join_split_pub_key: join_split_signature.map(|(pub_key, _)| pub_key),
+                join_split_sig: join_split_signature.map(|(_, sig)| sig),
+                spend_proofs_sapling: &[],
+                spend_auth_sigs_sapling: &[],
+                output_proofs_sapling: &[],
+                binding_sig_sapling: None,
+                orchard_actions: Vec::new(),
+                flags_orchard: 0,
+                value_balance_orchard: 0,
+                anchor_orchard: None,
+                proofs_orchard: &[],
+                spend_auth_sigs_orchard: &[],
+                binding_sig_orchard: None,
+            },
+        ))
+    }
+
     fn parse_v4(
-        data: &[u8],
+        data: &'a [u8],
         version: u32,
         n_version_group_id: u32,
-    ) -> Result<(&[u8], Self), ParseError> {
+    ) -> Result<(&'a [u8], Self), ParseError> {
         if n_version_group_id != 0x892F2085 {
             return Err(ParseError::InvalidData(format!(
                 "version group ID {:x} must be 0x892F2085 for v4 transactions",
@@ -450,88 +1510,89 @@ impl TransactionData {
             parse_transparent(&data[cursor.position() as usize..])?;
         cursor.set_position(data.len() as u64 - remaining_data.len() as u64);
 
-        skip_bytes(&mut cursor, 4, "Error skipping TransactionData::nLockTime")?;
-        skip_bytes(
-            &mut cursor,
-            4,
-            "Error skipping TransactionData::nExpiryHeight",
-        )?;
-        skip_bytes(
-            &mut cursor,
-            8,
-            "Error skipping TransactionData::valueBalance",
-        )?;
+        let lock_time = read_u32(&mut cursor, "Error reading TransactionData::nLockTime")?;
+        let expiry_height = read_u32(&mut cursor, "Error reading TransactionData::nExpiryHeight")?;
+        let value_balance_sapling =
+            read_i64(&mut cursor, "Error reading TransactionData::valueBalance")?;
 
         let spend_count = CompactSize::read(&mut cursor)?;
-        let mut shielded_spends = Vec::with_capacity(spend_count as usize);
+        let mut shielded_spends = Vec::with_capacity(bounded_capacity(
+            spend_count,
+            data.len() - cursor.position() as usize,
+            MIN_SPEND_SIZE,
+        ));
         for _ in 0..spend_count {
-            let (remaining_data, spend) =
-                Spend::parse_from_slice(&data[cursor.position() as usize..], None, Some(4))?;
+            let (remaining_data, spend) = SpendRef::parse(&data[cursor.position() as usize..], 4)?;
             shielded_spends.push(spend);
             cursor.set_position(data.len() as u64 - remaining_data.len() as u64);
         }
         let output_count = CompactSize::read(&mut cursor)?;
-        let mut shielded_outputs = Vec::with_capacity(output_count as usize);
+        let mut shielded_outputs = Vec::with_capacity(bounded_capacity(
+            output_count,
+            data.len() - cursor.position() as usize,
+            MIN_OUTPUT_SIZE,
+        ));
         for _ in 0..output_count {
             let (remaining_data, output) =
-                Output::parse_from_slice(&data[cursor.position() as usize..], None, Some(4))?;
+                OutputRef::parse(&data[cursor.position() as usize..], 4)?;
             shielded_outputs.push(output);
             cursor.set_position(data.len() as u64 - remaining_data.len() as u64);
         }
-        let join_split_count = CompactSize::read(&mut cursor)?;
-        let mut join_splits = Vec::with_capacity(join_split_count as usize);
-        for _ in 0..join_split_count {
-            let (remaining_data, join_split) =
-                JoinSplit::parse_from_slice(&data[cursor.position() as usize..], None, None)?;
-            join_splits.push(join_split);
-            cursor.set_position(data.len() as u64 - remaining_data.len() as u64);
-        }
+        let (remaining_data, join_splits, join_split_signature) =
+            parse_join_splits(&data[cursor.position() as usize..], 4)?;
+        cursor.set_position(data.len() as u64 - remaining_data.len() as u64);
 
-        if join_split_count > 0 {
-            skip_bytes(
-                &mut cursor,
-                32,
-                "Error skipping TransactionData::joinSplitPubKey",
-            )?;
-            skip_bytes(
-                &mut cursor,
-                64,
-                "could not skip TransactionData::joinSplitSig",
-            )?;
-        }
-        if spend_count + output_count > 0 {
-            skip_bytes(
+        let binding_sig_sapling = if spend_count + output_count > 0 {
+            Some(read_slice(
                 &mut cursor,
                 64,
-                "Error skipping TransactionData::bindingSigSapling",
-            )?;
-        }
+                "Error reading TransactionData::bindingSigSapling",
+            )?)
+        } else {
+            None
+        };
 
         Ok((
             &data[cursor.position() as usize..],
-            TransactionData {
+            TransactionDataRef {
                 f_overwintered: true,
                 version,
                 n_version_group_id,
                 consensus_branch_id: 0,
                 transparent_inputs,
                 transparent_outputs,
+                lock_time,
+                expiry_height,
+                value_balance_sapling,
+                anchor_sapling: None,
                 shielded_spends,
                 shielded_outputs,
                 join_splits,
+                join_split_pub_key: join_split_signature.map(|(pub_key, _)| pub_key),
+                join_split_sig: join_split_signature.map(|(_, sig)| sig),
+                spend_proofs_sapling: &[],
+                spend_auth_sigs_sapling: &[],
+                output_proofs_sapling: &[],
+                binding_sig_sapling,
                 orchard_actions: Vec::new(),
+                flags_orchard: 0,
+                value_balance_orchard: 0,
+                anchor_orchard: None,
+                proofs_orchard: &[],
+                spend_auth_sigs_orchard: &[],
+                binding_sig_orchard: None,
             },
         ))
     }
 
     fn parse_v5(
-        data: &[u8],
+        data: &'a [u8],
         version: u32,
         n_version_group_id: u32,
-    ) -> Result<(&[u8], Self), ParseError> {
+    ) -> Result<(&'a [u8], Self), ParseError> {
         if n_version_group_id != 0x26A7270A {
             return Err(ParseError::InvalidData(format!(
-                "version group ID {:x} must be 0x892F2085 for v5 transactions",
+                "version group ID {:x} must be 0x26A7270A for v5 transactions",
                 n_version_group_id
             )));
         }
@@ -542,12 +1603,8 @@ impl TransactionData {
             "Error reading TransactionData::ConsensusBranchId",
         )?;
 
-        skip_bytes(&mut cursor, 4, "Error skipping TransactionData::nLockTime")?;
-        skip_bytes(
-            &mut cursor,
-            4,
-            "Error skipping TransactionData::nExpiryHeight",
-        )?;
+        let lock_time = read_u32(&mut cursor, "Error reading TransactionData::nLockTime")?;
+        let expiry_height = read_u32(&mut cursor, "Error reading TransactionData::nExpiryHeight")?;
 
         let (remaining_data, transparent_inputs, transparent_outputs) =
             parse_transparent(&data[cursor.position() as usize..])?;
@@ -560,10 +1617,13 @@ impl TransactionData {
                 spend_count
             )));
         }
-        let mut shielded_spends = Vec::with_capacity(spend_count as usize);
+        let mut shielded_spends = Vec::with_capacity(bounded_capacity(
+            spend_count,
+            data.len() - cursor.position() as usize,
+            MIN_SPEND_SIZE,
+        ));
         for _ in 0..spend_count {
-            let (remaining_data, spend) =
-                Spend::parse_from_slice(&data[cursor.position() as usize..], None, Some(5))?;
+            let (remaining_data, spend) = SpendRef::parse(&data[cursor.position() as usize..], 5)?;
             shielded_spends.push(spend);
             cursor.set_position(data.len() as u64 - remaining_data.len() as u64);
         }
@@ -574,52 +1634,56 @@ impl TransactionData {
                 output_count
             )));
         }
-        let mut shielded_outputs = Vec::with_capacity(output_count as usize);
+        let mut shielded_outputs = Vec::with_capacity(bounded_capacity(
+            output_count,
+            data.len() - cursor.position() as usize,
+            MIN_OUTPUT_SIZE,
+        ));
         for _ in 0..output_count {
             let (remaining_data, output) =
-                Output::parse_from_slice(&data[cursor.position() as usize..], None, Some(5))?;
+                OutputRef::parse(&data[cursor.position() as usize..], 5)?;
             shielded_outputs.push(output);
             cursor.set_position(data.len() as u64 - remaining_data.len() as u64);
         }
 
-        if spend_count + output_count > 0 {
-            skip_bytes(
-                &mut cursor,
-                8,
-                "Error skipping TransactionData::valueBalance",
-            )?;
-        }
-        if spend_count > 0 {
-            skip_bytes(
+        let value_balance_sapling = if spend_count + output_count > 0 {
+            read_i64(&mut cursor, "Error reading TransactionData::valueBalance")?
+        } else {
+            0
+        };
+        let anchor_sapling = if spend_count > 0 {
+            Some(read_slice(
                 &mut cursor,
                 32,
-                "Error skipping TransactionData::anchorSapling",
-            )?;
-            skip_bytes(
-                &mut cursor,
-                (192 * spend_count) as usize,
-                "Error skipping TransactionData::vSpendProofsSapling",
-            )?;
-            skip_bytes(
-                &mut cursor,
-                (64 * spend_count) as usize,
-                "Error skipping TransactionData::vSpendAuthSigsSapling",
-            )?;
-        }
-        if output_count > 0 {
-            skip_bytes(
-                &mut cursor,
-                (192 * output_count) as usize,
-                "Error skipping TransactionData::vOutputProofsSapling",
-            )?;
-        }
-        if spend_count + output_count > 0 {
-            skip_bytes(
+                "Error reading TransactionData::anchorSapling",
+            )?)
+        } else {
+            None
+        };
+        let spend_proofs_sapling = read_slice(
+            &mut cursor,
+            (192 * spend_count) as usize,
+            "Error reading TransactionData::vSpendProofsSapling",
+        )?;
+        let spend_auth_sigs_sapling = read_slice(
+            &mut cursor,
+            (64 * spend_count) as usize,
+            "Error reading TransactionData::vSpendAuthSigsSapling",
+        )?;
+        let output_proofs_sapling = read_slice(
+            &mut cursor,
+            (192 * output_count) as usize,
+            "Error reading TransactionData::vOutputProofsSapling",
+        )?;
+        let binding_sig_sapling = if spend_count + output_count > 0 {
+            Some(read_slice(
                 &mut cursor,
                 64,
-                "Error skipping TransactionData::bindingSigSapling",
-            )?;
-        }
+                "Error reading TransactionData::bindingSigSapling",
+            )?)
+        } else {
+            None
+        };
 
         let actions_count = CompactSize::read(&mut cursor)?;
         if actions_count >= (1 << 16) {
@@ -628,65 +1692,294 @@ impl TransactionData {
                 actions_count
             )));
         }
-        let mut orchard_actions = Vec::with_capacity(actions_count as usize);
+        let mut orchard_actions = Vec::with_capacity(bounded_capacity(
+            actions_count,
+            data.len() - cursor.position() as usize,
+            ACTION_SIZE,
+        ));
         for _ in 0..actions_count {
-            let (remaining_data, action) =
-                Action::parse_from_slice(&data[cursor.position() as usize..], None, None)?;
+            let (remaining_data, action) = ActionRef::parse(&data[cursor.position() as usize..])?;
             orchard_actions.push(action);
             cursor.set_position(data.len() as u64 - remaining_data.len() as u64);
         }
 
+        let mut flags_orchard = 0;
+        let mut value_balance_orchard = 0;
+        let mut anchor_orchard = None;
+        let mut proofs_orchard: &[u8] = &[];
+        let mut spend_auth_sigs_orchard: &[u8] = &[];
+        let mut binding_sig_orchard = None;
         if actions_count > 0 {
-            skip_bytes(
+            flags_orchard = read_slice(
                 &mut cursor,
                 1,
-                "Error skipping TransactionData::flagsOrchard",
-            )?;
-            skip_bytes(
+                "Error reading TransactionData::flagsOrchard",
+            )?[0];
+            value_balance_orchard = read_i64(
                 &mut cursor,
-                8,
-                "Error skipping TransactionData::valueBalanceOrchard",
+                "Error reading TransactionData::valueBalanceOrchard",
             )?;
-            skip_bytes(
+            anchor_orchard = Some(read_slice(
                 &mut cursor,
                 32,
-                "Error skipping TransactionData::anchorOrchard",
-            )?;
+                "Error reading TransactionData::anchorOrchard",
+            )?);
 
             let proofs_count = CompactSize::read(&mut cursor)?;
-            skip_bytes(
+            proofs_orchard = read_slice(
                 &mut cursor,
                 proofs_count as usize,
-                "Error skipping TransactionData::proofsOrchard",
+                "Error reading TransactionData::proofsOrchard",
             )?;
-            skip_bytes(
+            spend_auth_sigs_orchard = read_slice(
                 &mut cursor,
                 (64 * actions_count) as usize,
-                "Error skipping TransactionData::vSpendAuthSigsOrchard",
+                "Error reading TransactionData::vSpendAuthSigsOrchard",
             )?;
-            skip_bytes(
+            binding_sig_orchard = Some(read_slice(
                 &mut cursor,
                 64,
-                "Error skipping TransactionData::bindingSigOrchard",
-            )?;
+                "Error reading TransactionData::bindingSigOrchard",
+            )?);
         }
 
         Ok((
             &data[cursor.position() as usize..],
-            TransactionData {
+            TransactionDataRef {
                 f_overwintered: true,
                 version,
                 n_version_group_id,
                 consensus_branch_id,
                 transparent_inputs,
                 transparent_outputs,
+                lock_time,
+                expiry_height,
+                value_balance_sapling,
+                anchor_sapling,
                 shielded_spends,
                 shielded_outputs,
                 join_splits: Vec::new(),
+                join_split_pub_key: None,
+                join_split_sig: None,
+                spend_proofs_sapling,
+                spend_auth_sigs_sapling,
+                output_proofs_sapling,
+                binding_sig_sapling,
                 orchard_actions,
+                flags_orchard,
+                value_balance_orchard,
+                anchor_orchard,
+                proofs_orchard,
+                spend_auth_sigs_orchard,
+                binding_sig_orchard,
             },
         ))
     }
+
+    /// Copies the transaction data into an owned [`TransactionData`].
+    fn into_owned(self) -> TransactionData {
+        TransactionData {
+            f_overwintered: self.f_overwintered,
+            version: self.version,
+            n_version_group_id: self.n_version_group_id,
+            consensus_branch_id: self.consensus_branch_id,
+            transparent_inputs: self
+                .transparent_inputs
+                .into_iter()
+                .map(TxInRef::into_owned)
+                .collect(),
+            transparent_outputs: self
+                .transparent_outputs
+                .into_iter()
+                .map(TxOutRef::into_owned)
+                .collect(),
+            lock_time: self.lock_time,
+            expiry_height: self.expiry_height,
+            value_balance_sapling: self.value_balance_sapling,
+            anchor_sapling: self.anchor_sapling.map(|anchor| anchor.to_vec()),
+            shielded_spends: self
+                .shielded_spends
+                .into_iter()
+                .map(SpendRef::into_owned)
+                .collect(),
+            shielded_outputs: self
+                .shielded_outputs
+                .into_iter()
+                .map(OutputRef::into_owned)
+                .collect(),
+            join_splits: self
+                .join_splits
+                .into_iter()
+                .map(JoinSplitRef::into_owned)
+                .collect(),
+            join_split_pub_key: self.join_split_pub_key.map(|pub_key| pub_key.to_vec()),
+            join_split_sig: self.join_split_sig.map(|sig| sig.to_vec()),
+            spend_proofs_sapling: self.spend_proofs_sapling.to_vec(),
+            spend_auth_sigs_sapling: self.spend_auth_sigs_sapling.to_vec(),
+            output_proofs_sapling: self.output_proofs_sapling.to_vec(),
+            binding_sig_sapling: self.binding_sig_sapling.map(|sig| sig.to_vec()),
+            orchard_actions: self
+                .orchard_actions
+                .into_iter()
+                .map(ActionRef::into_owned)
+                .collect(),
+            flags_orchard: self.flags_orchard,
+            value_balance_orchard: self.value_balance_orchard,
+            anchor_orchard: self.anchor_orchard.map(|anchor| anchor.to_vec()),
+            proofs_orchard: self.proofs_orchard.to_vec(),
+            spend_auth_sigs_orchard: self.spend_auth_sigs_orchard.to_vec(),
+            binding_sig_orchard: self.binding_sig_orchard.map(|sig| sig.to_vec()),
+        }
+    }
+
+    /// Returns the txid of the transaction.
+    ///
+    /// The txid of v5 transactions is the ZIP-244 transaction digest, earlier versions use the double SHA256 hash of
+    /// the raw transaction.
+    fn txid(&self, raw_bytes: &[u8]) -> [u8; 32] {
+        if self.version >= 5 {
+            self.zip244_txid()
+        } else {
+            Sha256::digest(Sha256::digest(raw_bytes)).into()
+        }
+    }
+
+    /// Returns the ZIP-244 transaction digest of a v5 transaction.
+    fn zip244_txid(&self) -> [u8; 32] {
+        let header_digest = {
+            let mut hasher = zip244_hasher(b"ZTxIdHeadersHash");
+            hasher.update(&(self.version | (1 << 31)).to_le_bytes());
+            hasher.update(&self.n_version_group_id.to_le_bytes());
+            hasher.update(&self.consensus_branch_id.to_le_bytes());
+            hasher.update(&self.lock_time.to_le_bytes());
+            hasher.update(&self.expiry_height.to_le_bytes());
+            hasher.finalize()
+        };
+
+        let mut personal = *b"ZcashTxHash_\0\0\0\0";
+        personal[12..].copy_from_slice(&self.consensus_branch_id.to_le_bytes());
+        let mut hasher = zip244_hasher(&personal);
+        hasher.update(header_digest.as_bytes());
+        hasher.update(self.zip244_transparent_digest().as_bytes());
+        hasher.update(self.zip244_sapling_digest().as_bytes());
+        hasher.update(self.zip244_orchard_digest().as_bytes());
+        let mut txid = [0; 32];
+        txid.copy_from_slice(hasher.finalize().as_bytes());
+        txid
+    }
+
+    /// Returns the ZIP-244 digest of the transparent inputs and outputs.
+    fn zip244_transparent_digest(&self) -> blake2b_simd::Hash {
+        let mut hasher = zip244_hasher(b"ZTxIdTranspaHash");
+        if !self.transparent_inputs.is_empty() || !self.transparent_outputs.is_empty() {
+            let mut prevouts = zip244_hasher(b"ZTxIdPrevoutHash");
+            let mut sequences = zip244_hasher(b"ZTxIdSequencHash");
+            for input in self.transparent_inputs.iter() {
+                prevouts.update(&input.prev_txid);
+                prevouts.update(&input.prev_index.to_le_bytes());
+                sequences.update(&input.sequence.to_le_bytes());
+            }
+            let mut outputs = zip244_hasher(b"ZTxIdOutputsHash");
+            for output in self.transparent_outputs.iter() {
+                outputs.update(&output.value.to_le_bytes());
+                outputs.update(&compact_size_bytes(output.script_pubkey.len()));
+                outputs.update(&output.script_pubkey);
+            }
+            hasher.update(prevouts.finalize().as_bytes());
+            hasher.update(sequences.finalize().as_bytes());
+            hasher.update(outputs.finalize().as_bytes());
+        }
+        hasher.finalize()
+    }
+
+    /// Returns the ZIP-244 digest of the Sapling spends and outputs.
+    fn zip244_sapling_digest(&self) -> blake2b_simd::Hash {
+        let mut hasher = zip244_hasher(b"ZTxIdSaplingHash");
+        if !self.shielded_spends.is_empty() || !self.shielded_outputs.is_empty() {
+            let mut spends = zip244_hasher(b"ZTxIdSSpendsHash");
+            if !self.shielded_spends.is_empty() {
+                let mut compact = zip244_hasher(b"ZTxIdSSpendCHash");
+                let mut noncompact = zip244_hasher(b"ZTxIdSSpendNHash");
+                let anchor = self.anchor_sapling.unwrap_or(&[0; 32]);
+                for spend in self.shielded_spends.iter() {
+                    compact.update(&spend.nullifier);
+                    noncompact.update(&spend.cv);
+                    noncompact.update(anchor);
+                    noncompact.update(&spend.rk);
+                }
+                spends.update(compact.finalize().as_bytes());
+                spends.update(noncompact.finalize().as_bytes());
+            }
+
+            let mut outputs = zip244_hasher(b"ZTxIdSOutputHash");
+            if !self.shielded_outputs.is_empty() {
+                let mut compact = zip244_hasher(b"ZTxIdSOutC__Hash");
+                let mut memos = zip244_hasher(b"ZTxIdSOutM__Hash");
+                let mut noncompact = zip244_hasher(b"ZTxIdSOutN__Hash");
+                for output in self.shielded_outputs.iter() {
+                    compact.update(&output.cmu);
+                    compact.update(&output.ephemeral_key);
+                    compact.update(&output.enc_ciphertext[..52]);
+                    memos.update(&output.enc_ciphertext[52..564]);
+                    noncompact.update(&output.cv);
+                    noncompact.update(&output.enc_ciphertext[564..]);
+                    noncompact.update(&output.out_ciphertext);
+                }
+                outputs.update(compact.finalize().as_bytes());
+                outputs.update(memos.finalize().as_bytes());
+                outputs.update(noncompact.finalize().as_bytes());
+            }
+
+            hasher.update(spends.finalize().as_bytes());
+            hasher.update(outputs.finalize().as_bytes());
+            hasher.update(&self.value_balance_sapling.to_le_bytes());
+        }
+        hasher.finalize()
+    }
+
+    /// Returns the ZIP-244 digest of the Orchard actions.
+    fn zip244_orchard_digest(&self) -> blake2b_simd::Hash {
+        let mut hasher = zip244_hasher(b"ZTxIdOrchardHash");
+        if !self.orchard_actions.is_empty() {
+            let mut compact = zip244_hasher(b"ZTxIdOrcActCHash");
+            let mut memos = zip244_hasher(b"ZTxIdOrcActMHash");
+            let mut noncompact = zip244_hasher(b"ZTxIdOrcActNHash");
+            for action in self.orchard_actions.iter() {
+                compact.update(&action.nullifier);
+                compact.update(&action.cmx);
+                compact.update(&action.ephemeral_key);
+                compact.update(&action.enc_ciphertext[..52]);
+                memos.update(&action.enc_ciphertext[52..564]);
+                noncompact.update(&action.cv);
+                noncompact.update(&action.rk);
+                noncompact.update(&action.enc_ciphertext[564..]);
+                noncompact.update(&action.out_ciphertext);
+            }
+            hasher.update(compact.finalize().as_bytes());
+            hasher.update(memos.finalize().as_bytes());
+            hasher.update(noncompact.finalize().as_bytes());
+            hasher.update(&[self.flags_orchard]);
+            hasher.update(&self.value_balance_orchard.to_le_bytes());
+            hasher.update(self.anchor_orchard.unwrap_or(&[0; 32]));
+        }
+        hasher.finalize()
+    }
+}
+
+/// Returns a BLAKE2b-256 hasher with the given ZIP-244 personalization.
+fn zip244_hasher(personal: &[u8; 16]) -> blake2b_simd::State {
+    blake2b_simd::Params::new()
+        .hash_length(32)
+        .personal(personal)
+        .to_state()
+}
+
+/// Returns the CompactSize encoding of the given length.
+fn compact_size_bytes(size: usize) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(9);
+    // Writing to a Vec can not fail.
+    let _ = CompactSize::write(&mut bytes, size);
+    bytes
 }
 
 /// Zingo-Indexer struct for a full zcash transaction.
@@ -698,7 +1991,7 @@ pub struct FullTransaction {
     /// Raw transaction bytes.
     raw_bytes: Vec<u8>,
 
-    /// Transaction Id, fetched using get_block JsonRPC with verbose = 1.
+    /// Transaction Id, computed from the transaction data.
     tx_id: Vec<u8>,
 }
 
@@ -708,58 +2001,197 @@ impl ParseFromSlice for FullTransaction {
         txid: Option<Vec<Vec<u8>>>,
         tx_version: Option<u32>,
     ) -> Result<(&[u8], Self), ParseError> {
-        let txid = txid.ok_or_else(|| {
-            ParseError::InvalidData(
-                "txid must be used for FullTransaction::parse_from_slice".to_string(),
-            )
-        })?;
         if tx_version.is_some() {
             return Err(ParseError::InvalidData(
                 "tx_version must be None for FullTransaction::parse_from_slice".to_string(),
             ));
         }
+        let expected_txid = txid.as_ref().and_then(|txid| txid.first());
+        let (remaining_data, transaction) =
+            FullTransactionRef::parse(data, expected_txid.map(|txid| txid.as_slice()))?;
+        Ok((remaining_data, transaction.into_owned()))
+    }
+}
+
+impl SerializeToVec for FullTransaction {
+    /// Writes the transaction from its parsed fields, see [`FullTransaction::raw_bytes`] for the bytes it was parsed
+    /// from.
+    fn write_to_vec(&self, buffer: &mut Vec<u8>) {
+        self.raw_transaction.write_to_vec(buffer);
+    }
+}
+
+/// Borrowed [`FullTransaction`], referencing the transaction bytes it was parsed from.
+///
+/// Parsing into a `FullTransactionRef` does not copy any byte fields out of the given data, so it can be used to
+/// build compact transactions without allocating an owned copy of every field first.
+#[derive(Debug, Clone)]
+pub struct FullTransactionRef<'a> {
+    /// Full transaction data.
+    raw_transaction: TransactionDataRef<'a>,
+
+    /// Raw transaction bytes.
+    raw_bytes: &'a [u8],
+
+    /// Transaction Id, computed from the transaction data.
+    tx_id: [u8; 32],
+}
+
+impl<'a> FullTransactionRef<'a> {
+    /// Parses a transaction from the start of `data`, returning it along with the remaining data.
+    ///
+    /// If txid is given it is checked against the txid computed from the transaction data.
+    pub fn parse(data: &'a [u8], txid: Option<&[u8]>) -> Result<(&'a [u8], Self), ParseError> {
         let mut cursor = Cursor::new(data);
 
         let header = read_u32(&mut cursor, "Error reading FullTransaction::header")?;
         let f_overwintered = (header >> 31) == 1;
-        if !f_overwintered {
-            return Err(ParseError::InvalidData(
-                "fOverwinter flag must be set".to_string(),
-            ));
-        }
         let version = header & 0x7FFFFFFF;
-        if version < 4 {
-            return Err(ParseError::InvalidData(format!(
-                "version number {} must be greater or equal to 4",
-                version
-            )));
-        }
-        let n_version_group_id = read_u32(
-            &mut cursor,
-            "Error reading FullTransaction::n_version_group_id",
-        )?;
 
-        let (remaining_data, transaction_data) = if version <= 4 {
-            TransactionData::parse_v4(
-                &data[cursor.position() as usize..],
-                version,
-                n_version_group_id,
-            )?
+        let (remaining_data, transaction_data) = if !f_overwintered {
+            if !(1..=2).contains(&version) {
+                return Err(ParseError::InvalidData(format!(
+                    "version number {} must be 1 or 2 for transactions without the fOverwinter flag",
+                    version
+                )));
+            }
+            TransactionDataRef::parse_v1_v2(&data[cursor.position() as usize..], version)?
         } else {
-            TransactionData::parse_v5(
-                &data[cursor.position() as usize..],
-                version,
-                n_version_group_id,
-            )?
+            if version < 3 {
+                return Err(ParseError::InvalidData(format!(
+                    "version number {} must be greater or equal to 3 for transactions with the fOverwinter flag",
+                    version
+                )));
+            }
+            let n_version_group_id = read_u32(
+                &mut cursor,
+                "Error reading FullTransaction::n_version_group_id",
+            )?;
+            match version {
+                3 => TransactionDataRef::parse_v3(
+                    &data[cursor.position() as usize..],
+                    version,
+                    n_version_group_id,
+                )?,
+                4 => TransactionDataRef::parse_v4(
+                    &data[cursor.position() as usize..],
+                    version,
+                    n_version_group_id,
+                )?,
+                _ => TransactionDataRef::parse_v5(
+                    &data[cursor.position() as usize..],
+                    version,
+                    n_version_group_id,
+                )?,
+            }
         };
 
-        let full_transaction = FullTransaction {
-            raw_transaction: transaction_data,
-            raw_bytes: data[..(data.len() - remaining_data.len())].to_vec(),
-            tx_id: txid[0].clone(),
-        };
+        let raw_bytes = &data[..(data.len() - remaining_data.len())];
+        let tx_id = transaction_data.txid(raw_bytes);
+        if let Some(expected_txid) = txid {
+            if expected_txid != tx_id {
+                return Err(ParseError::InvalidData(format!(
+                    "txid mismatch: expected {}, computed {}",
+                    hex::encode(expected_txid),
+                    hex::encode(tx_id)
+                )));
+            }
+        }
+
+        Ok((
+            remaining_data,
+            FullTransactionRef {
+                raw_transaction: transaction_data,
+                raw_bytes,
+                tx_id,
+            },
+        ))
+    }
+
+    /// Returns the transaction version.
+    pub fn version(&self) -> u32 {
+        self.raw_transaction.version
+    }
+
+    /// Returns the transparent inputs of the transaction.
+    pub fn transparent_inputs(&self) -> &[TxInRef<'a>] {
+        &self.raw_transaction.transparent_inputs
+    }
+
+    /// Returns the transparent outputs of the transaction.
+    pub fn transparent_outputs(&self) -> &[TxOutRef<'a>] {
+        &self.raw_transaction.transparent_outputs
+    }
+
+    /// Returns the Sprout JoinSplit descriptions of the transaction.
+    pub fn join_splits(&self) -> &[JoinSplitRef<'a>] {
+        &self.raw_transaction.join_splits
+    }
+
+    /// Returns an iterator over the sapling nullifiers of the transaction.
+    pub fn shielded_spends(&self) -> impl Iterator<Item = &'a [u8]> + '_ {
+        self.raw_transaction
+            .shielded_spends
+            .iter()
+            .map(|spend| spend.nullifier)
+    }
+
+    /// Returns an iterator over the orchard nullifiers of the transaction.
+    pub fn orchard_nullifiers(&self) -> impl Iterator<Item = &'a [u8]> + '_ {
+        self.raw_transaction
+            .orchard_actions
+            .iter()
+            .map(|action| action.nullifier)
+    }
+
+    /// Returns the transaction as raw bytes.
+    pub fn raw_bytes(&self) -> &'a [u8] {
+        self.raw_bytes
+    }
+
+    /// Returns the TxId of the transaction (in internal byte order).
+    pub fn tx_id(&self) -> &[u8; 32] {
+        &self.tx_id
+    }
+
+    /// Returns true if the transaction contains either sapling spends or outputs.
+    pub fn has_shielded_elements(&self) -> bool {
+        !self.raw_transaction.shielded_spends.is_empty()
+            || !self.raw_transaction.shielded_outputs.is_empty()
+            || !self.raw_transaction.orchard_actions.is_empty()
+    }
+
+    /// Converts the transaction into a compact transaction, copying only the fields the compact transaction holds.
+    pub fn to_compact(&self, index: u64) -> Result<CompactTx, ParseError> {
+        Ok(compact_tx(
+            index,
+            &self.tx_id,
+            self.raw_transaction
+                .shielded_spends
+                .iter()
+                .map(|spend| spend.nullifier),
+            self.raw_transaction
+                .shielded_outputs
+                .iter()
+                .map(|output| (output.cmu, output.ephemeral_key, output.enc_ciphertext)),
+            self.raw_transaction.orchard_actions.iter().map(|action| {
+                (
+                    action.nullifier,
+                    action.cmx,
+                    action.ephemeral_key,
+                    action.enc_ciphertext,
+                )
+            }),
+        ))
+    }
 
-        Ok((remaining_data, full_transaction))
+    /// Copies the transaction into an owned [`FullTransaction`].
+    pub fn into_owned(self) -> FullTransaction {
+        FullTransaction {
+            raw_transaction: self.raw_transaction.into_owned(),
+            raw_bytes: self.raw_bytes.to_vec(),
+            tx_id: self.tx_id.to_vec(),
+        }
     }
 }
 
@@ -784,21 +2216,49 @@ impl FullTransaction {
         self.raw_transaction.consensus_branch_id
     }
 
-    /// Returns a vec of transparent input script_sigs for the transaction.
-    pub fn transparent_inputs(&self) -> Vec<Vec<u8>> {
-        self.raw_transaction
-            .transparent_inputs
-            .iter()
-            .map(|input| input.clone().into_inner())
-            .collect()
+    /// Returns the transparent inputs of the transaction.
+    pub fn transparent_inputs(&self) -> Vec<TxIn> {
+        self.raw_transaction.transparent_inputs.clone()
+    }
+
+    /// Returns the transparent outputs of the transaction.
+    pub fn transparent_outputs(&self) -> Vec<TxOut> {
+        self.raw_transaction.transparent_outputs.clone()
+    }
+
+    /// Returns the earliest block height or time the transaction can be mined at.
+    pub fn lock_time(&self) -> u32 {
+        self.raw_transaction.lock_time
+    }
+
+    /// Returns the block height after which the transaction expires, 0 if the transaction does not expire.
+    pub fn expiry_height(&self) -> u32 {
+        self.raw_transaction.expiry_height
+    }
+
+    /// Returns the net value of Sapling spends minus outputs.
+    pub fn value_balance_sapling(&self) -> i64 {
+        self.raw_transaction.value_balance_sapling
+    }
+
+    /// Returns the Sapling anchor shared by all spends (v5 transactions with spends only).
+    ///
+    /// In v4 transactions each spend holds its own anchor.
+    pub fn anchor_sapling(&self) -> Option<Vec<u8>> {
+        self.raw_transaction.anchor_sapling.clone()
     }
 
-    /// Returns a vec of transparent output values for the transaction.
-    pub fn transparent_outputs(&self) -> Vec<u64> {
+    /// Returns the anchor of each Sapling spend, taken from the spend in v4 transactions and `anchorSapling` in v5.
+    pub fn sapling_spend_anchors(&self) -> Vec<Vec<u8>> {
         self.raw_transaction
-            .transparent_outputs
+            .shielded_spends
             .iter()
-            .map(|input| input.clone().into_inner())
+            .filter_map(|spend| {
+                spend
+                    .anchor
+                    .clone()
+                    .or_else(|| self.raw_transaction.anchor_sapling.clone())
+            })
             .collect()
     }
 
@@ -820,9 +2280,9 @@ impl FullTransaction {
             .collect()
     }
 
-    /// Returns None as joinsplits are not supported in Zaino.
-    pub fn join_splits(&self) -> Option<()> {
-        None
+    /// Returns the Sprout JoinSplit descriptions of the transaction.
+    pub fn join_splits(&self) -> Vec<JoinSplit> {
+        self.raw_transaction.join_splits.clone()
     }
 
     /// Returns a vec of orchard actions (nullifier, cmx, ephemeral_key, enc_ciphertext) for the transaction.
@@ -835,63 +2295,97 @@ impl FullTransaction {
             .collect()
     }
 
+    /// Returns the Orchard flags (bit 0: enableSpends, bit 1: enableOutputs).
+    pub fn flags_orchard(&self) -> u8 {
+        self.raw_transaction.flags_orchard
+    }
+
+    /// Returns the net value of Orchard spends minus outputs.
+    pub fn value_balance_orchard(&self) -> i64 {
+        self.raw_transaction.value_balance_orchard
+    }
+
+    /// Returns the Orchard anchor (v5 transactions with actions only).
+    pub fn anchor_orchard(&self) -> Option<Vec<u8>> {
+        self.raw_transaction.anchor_orchard.clone()
+    }
+
     /// Returns the transaction as raw bytes.
     pub fn raw_bytes(&self) -> Vec<u8> {
         self.raw_bytes.clone()
     }
 
-    /// returns the TxId of the transaction.
+    /// Returns the TxId of the transaction (in internal byte order).
     pub fn tx_id(&self) -> Vec<u8> {
         self.tx_id.clone()
     }
 
-    /// Converts a zcash full transaction into a compact transaction.
-    pub fn to_compact(self, index: u64) -> Result<CompactTx, ParseError> {
-        let hash = self.tx_id;
-
-        // NOTE: LightWalletD currently does not return a fee and is not currently priority here. Please open an Issue or PR at the Zingo-Indexer github (https://github.com/zingolabs/zingo-indexer) if you require this functionality.
-        let fee = 0;
-
-        let spends = self
-            .raw_transaction
-            .shielded_spends
+    /// Returns the transaction fee, given the values of the transparent outputs spent by each transparent input.
+    ///
+    /// The fee is the transparent value spent less the transparent value created, plus the Sprout, Sapling and Orchard
+    /// value balances. Coinbase transactions do not pay a fee.
+    pub fn fee(&self, prevout_values: &[u64]) -> Result<u64, ParseError> {
+        let tx = &self.raw_transaction;
+        if tx
+            .transparent_inputs
             .iter()
-            .map(|spend| CompactSaplingSpend {
-                nf: spend.nullifier.clone(),
-            })
-            .collect();
-
-        let outputs = self
-            .raw_transaction
-            .shielded_outputs
+            .any(|input| input.is_coinbase())
+        {
+            return Ok(0);
+        }
+        if prevout_values.len() != tx.transparent_inputs.len() {
+            return Err(ParseError::InvalidData(format!(
+                "number of prevout values ({}) does not match number of transparent inputs ({})",
+                prevout_values.len(),
+                tx.transparent_inputs.len()
+            )));
+        }
+        let mut balance: i128 = prevout_values.iter().map(|value| *value as i128).sum();
+        balance -= tx
+            .transparent_outputs
             .iter()
-            .map(|output| CompactSaplingOutput {
-                cmu: output.cmu.clone(),
-                ephemeral_key: output.ephemeral_key.clone(),
-                ciphertext: output.enc_ciphertext[..52].to_vec(),
-            })
-            .collect();
-
-        let actions = self
-            .raw_transaction
-            .orchard_actions
+            .map(|output| output.value as i128)
+            .sum::<i128>();
+        balance += tx
+            .join_splits
             .iter()
-            .map(|action| CompactOrchardAction {
-                nullifier: action.nullifier.clone(),
-                cmx: action.cmx.clone(),
-                ephemeral_key: action.ephemeral_key.clone(),
-                ciphertext: action.enc_ciphertext[..52].to_vec(),
-            })
-            .collect();
+            .map(|join_split| join_split.vpub_new as i128 - join_split.vpub_old as i128)
+            .sum::<i128>();
+        balance += tx.value_balance_sapling as i128 + tx.value_balance_orchard as i128;
+        u64::try_from(balance).map_err(|_| {
+            ParseError::InvalidData(format!(
+                "transaction {} has a negative fee ({})",
+                hex::encode(&self.tx_id),
+                balance
+            ))
+        })
+    }
 
-        Ok(CompactTx {
+    /// Converts a zcash full transaction into a compact transaction.
+    pub fn to_compact(self, index: u64) -> Result<CompactTx, ParseError> {
+        Ok(compact_tx(
             index,
-            hash,
-            fee,
-            spends,
-            outputs,
-            actions,
-        })
+            &self.tx_id,
+            self.raw_transaction
+                .shielded_spends
+                .iter()
+                .map(|spend| spend.nullifier.as_slice()),
+            self.raw_transaction.shielded_outputs.iter().map(|output| {
+                (
+                    output.cmu.as_slice(),
+                    output.ephemeral_key.as_slice(),
+                    output.enc_ciphertext.as_slice(),
+                )
+            }),
+            self.raw_transaction.orchard_actions.iter().map(|action| {
+                (
+                    action.nullifier.as_slice(),
+                    action.cmx.as_slice(),
+                    action.ephemeral_key.as_slice(),
+                    action.enc_ciphertext.as_slice(),
+                )
+            }),
+        ))
     }
 
     /// Returns true if the transaction contains either sapling spends or outputs.
@@ -901,3 +2395,130 @@ impl FullTransaction {
             || !self.raw_transaction.orchard_actions.is_empty()
     }
 }
+
+/// Builds a compact transaction from a transaction's txid and shielded elements.
+///
+/// Shared by [`FullTransaction::to_compact`] and [`FullTransactionRef::to_compact`], sapling outputs are given as
+/// `(cmu, ephemeral_key, enc_ciphertext)` and orchard actions as `(nullifier, cmx, ephemeral_key, enc_ciphertext)`.
+fn compact_tx<'s>(
+    index: u64,
+    tx_id: &[u8],
+    spend_nullifiers: impl Iterator<Item = &'s [u8]>,
+    outputs: impl Iterator<Item = (&'s [u8], &'s [u8], &'s [u8])>,
+    actions: impl Iterator<Item = (&'s [u8], &'s [u8], &'s [u8], &'s [u8])>,
+) -> CompactTx {
+    // NOTE: LightWalletD does not return a fee, as computing it requires prevout lookups it is filled in by the caller
+    // when requested, see [`get_transaction_fee`].
+    let fee = 0;
+
+    let spends = spend_nullifiers
+        .map(|nullifier| CompactSaplingSpend {
+            nf: nullifier.to_vec(),
+        })
+        .collect();
+
+    let outputs = outputs
+        .map(
+            |(cmu, ephemeral_key, enc_ciphertext)| CompactSaplingOutput {
+                cmu: cmu.to_vec(),
+                ephemeral_key: ephemeral_key.to_vec(),
+                ciphertext: enc_ciphertext[..COMPACT_NOTE_SIZE].to_vec(),
+            },
+        )
+        .collect();
+
+    let actions = actions
+        .map(
+            |(nullifier, cmx, ephemeral_key, enc_ciphertext)| CompactOrchardAction {
+                nullifier: nullifier.to_vec(),
+                cmx: cmx.to_vec(),
+                ephemeral_key: ephemeral_key.to_vec(),
+                ciphertext: enc_ciphertext[..COMPACT_NOTE_SIZE].to_vec(),
+            },
+        )
+        .collect();
+
+    CompactTx {
+        index,
+        hash: tx_id.to_vec(),
+        fee,
+        spends,
+        outputs,
+        actions,
+    }
+}
+
+/// Returns the values of the transparent outputs spent by the transaction's inputs.
+///
+/// Outputs created by `related_transactions` (eg. the other transactions in the same block) are resolved locally,
+/// all other prevouts are fetched from the node. `prevout_cache` holds the transactions fetched from the node, so that
+/// it can be shared between calls.
+pub async fn get_prevout_values(
+    zebrad_client: &JsonRpcConnector,
+    transaction: &FullTransaction,
+    related_transactions: &[FullTransaction],
+    prevout_cache: &mut HashMap<Vec<u8>, FullTransaction>,
+) -> Result<Vec<u64>, BlockCacheError> {
+    let mut values = Vec::with_capacity(transaction.raw_transaction.transparent_inputs.len());
+    for input in transaction.raw_transaction.transparent_inputs.iter() {
+        if input.is_coinbase() {
+            continue;
+        }
+        let prev_tx = match related_transactions
+            .iter()
+            .find(|tx| tx.tx_id == input.prev_txid)
+        {
+            Some(prev_tx) => prev_tx,
+            None => {
+                if !prevout_cache.contains_key(&input.prev_txid) {
+                    let txid_hex =
+                        hex::encode(input.prev_txid.iter().rev().copied().collect::<Vec<u8>>());
+                    let raw = match zebrad_client.get_raw_transaction(txid_hex, Some(0)).await? {
+                        GetTransactionResponse::Raw(raw) => raw,
+                        GetTransactionResponse::Object { hex, .. } => hex,
+                    };
+                    let (_, prev_tx) = FullTransaction::parse_from_slice(
+                        raw.as_ref(),
+                        Some(vec![input.prev_txid.clone()]),
+                        None,
+                    )?;
+                    prevout_cache.insert(input.prev_txid.clone(), prev_tx);
+                }
+                &prevout_cache[&input.prev_txid]
+            }
+        };
+        let output = prev_tx
+            .raw_transaction
+            .transparent_outputs
+            .get(input.prev_index as usize)
+            .ok_or_else(|| {
+                ParseError::InvalidData(format!(
+                    "prevout {}:{} does not exist",
+                    hex::encode(&input.prev_txid),
+                    input.prev_index
+                ))
+            })?;
+        values.push(output.value);
+    }
+    Ok(values)
+}
+
+/// Returns the fee paid by the transaction, resolving its transparent prevouts through [`get_prevout_values`].
+///
+/// The fee is saturated to `u32::MAX` zatoshis, the largest fee a `CompactTx` can hold.
+pub async fn get_transaction_fee(
+    zebrad_client: &JsonRpcConnector,
+    transaction: &FullTransaction,
+    related_transactions: &[FullTransaction],
+    prevout_cache: &mut HashMap<Vec<u8>, FullTransaction>,
+) -> Result<u32, BlockCacheError> {
+    let prevout_values = get_prevout_values(
+        zebrad_client,
+        transaction,
+        related_transactions,
+        prevout_cache,
+    )
+    .await?;
+    let fee = transaction.fee(&prevout_values)?;
+    Ok(u32::try_from(fee).unwrap_or(u32::MAX))
+}
@@ -9,7 +9,8 @@ use crate::chain::error::ParseError;
 pub trait ParseFromSlice {
     /// Reads data from a bytestring, consuming data read, and returns an instance of self along with the remaining data in the bytestring given.
     ///
-    /// txid is givin as an input as this is taken from a get_block verbose=1 call.
+    /// txid may be given to cross-check the txids computed from the data, eg. against those returned by a get_block
+    /// verbose=1 call.
     ///
     /// tx_version is used for deserializing sapling spends and outputs.
     fn parse_from_slice(
@@ -21,7 +22,23 @@ pub trait ParseFromSlice {
         Self: Sized;
 }
 
+/// Used for encoding zcash blocks into a bytestring, the counterpart of [`ParseFromSlice`].
+///
+/// Parsing the serialized bytes gives back an equal value.
+pub trait SerializeToVec {
+    /// Appends the consensus encoding of self to buffer.
+    fn write_to_vec(&self, buffer: &mut Vec<u8>);
+
+    /// Returns the consensus encoding of self.
+    fn serialize_to_vec(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        self.write_to_vec(&mut buffer);
+        buffer
+    }
+}
+
 /// Skips the next n bytes in cursor, returns error message given if eof is reached.
+#[allow(dead_code)]
 pub(crate) fn skip_bytes(
     cursor: &mut Cursor<&[u8]>,
     n: usize,
@@ -47,6 +64,23 @@ pub(crate) fn read_bytes(
     Ok(buf)
 }
 
+/// Returns the next n bytes from cursor as a slice of the underlying data (without copying), returns error message
+/// given if eof is reached.
+pub(crate) fn read_slice<'a>(
+    cursor: &mut Cursor<&'a [u8]>,
+    n: usize,
+    error_msg: &str,
+) -> Result<&'a [u8], ParseError> {
+    let data: &'a [u8] = *cursor.get_ref();
+    let start = cursor.position() as usize;
+    let end = start
+        .checked_add(n)
+        .filter(|end| *end <= data.len())
+        .ok_or_else(|| ParseError::InvalidData(error_msg.to_string()))?;
+    cursor.set_position(end as u64);
+    Ok(&data[start..end])
+}
+
 /// Reads the next 8 bytes from cursor into a u64, returns error message given if eof is reached.
 pub(crate) fn read_u64(cursor: &mut Cursor<&[u8]>, error_msg: &str) -> Result<u64, ParseError> {
     cursor
@@ -71,6 +105,14 @@ pub(crate) fn read_i32(cursor: &mut Cursor<&[u8]>, error_msg: &str) -> Result<i3
         .map_err(|_| ParseError::InvalidData(error_msg.to_string()))
 }
 
+/// Reads the next 8 bytes from cursor into an i64, returns error message given if eof is reached.
+pub(crate) fn read_i64(cursor: &mut Cursor<&[u8]>, error_msg: &str) -> Result<i64, ParseError> {
+    cursor
+        .read_i64::<LittleEndian>()
+        .map_err(ParseError::from)
+        .map_err(|_| ParseError::InvalidData(error_msg.to_string()))
+}
+
 /// Reads the next byte from cursor into a bool, returns error message given if eof is reached.
 #[allow(dead_code)]
 pub(crate) fn read_bool(cursor: &mut Cursor<&[u8]>, error_msg: &str) -> Result<bool, ParseError> {
@@ -111,6 +153,17 @@ pub(crate) fn read_zcash_script_i64(cursor: &mut Cursor<&[u8]>) -> Result<i64, P
     }
 }
 
+/// Returns the capacity to reserve for `count` elements that are each encoded in at least `min_size` bytes, read from
+/// `remaining` bytes of data.
+///
+/// Counts are read from untrusted data, bounding the capacity by the data left stops a large count from causing a large
+/// allocation before parsing fails.
+pub(crate) fn bounded_capacity(count: u64, remaining: usize, min_size: usize) -> usize {
+    usize::try_from(count)
+        .unwrap_or(usize::MAX)
+        .min(remaining / min_size.max(1))
+}
+
 /// Zcash CompactSize implementation taken from LibRustZcash::zcash_encoding to simplify dependency tree.
 ///
 /// Namespace for functions for compact encoding of integers.
@@ -196,6 +249,12 @@ impl CompactSize {
     }
 }
 
+/// Appends the CompactSize encoding of size to buffer.
+pub(crate) fn write_compact_size(buffer: &mut Vec<u8>, size: usize) {
+    // Writing to a Vec can not fail.
+    let _ = CompactSize::write(buffer, size);
+}
+
 /// Takes a vec of big endian hex encoded txids and returns them as a vec of little endian raw bytes.
 pub(crate) fn display_txids_to_server(txids: Vec<String>) -> Result<Vec<Vec<u8>>, ParseError> {
     txids
@@ -1,5 +1,6 @@
 //! JsonRPC client used to send requests to Zebrad.
 
+pub mod backend;
 pub mod connector;
 pub mod error;
 pub mod response;
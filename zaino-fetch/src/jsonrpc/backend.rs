@@ -0,0 +1,497 @@
+//! Manages a set of validator backends, routing requests to the healthiest backend and failing over when it goes down.
+
+use http::Uri;
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Arc, RwLock,
+};
+
+use crate::jsonrpc::{
+    connector::JsonRpcConnector, error::JsonRpcConnectorError, response::SendTransactionResponse,
+};
+
+/// Interval between backend health checks.
+const HEALTH_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Number of blocks a backend's tip may trail its estimated network height by while still being considered synced.
+///
+/// Zebra estimates the network height from the time since its tip block, so this allows for slow blocks.
+const MAX_SYNC_LAG: u32 = 24;
+
+/// Validator implementation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendKind {
+    /// Zcash Foundation's zebrad.
+    Zebrad,
+    /// Electric Coin Company's zcashd.
+    Zcashd,
+    /// Validator that could not be identified.
+    Unknown,
+}
+
+impl std::fmt::Display for BackendKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            BackendKind::Zebrad => "zebrad",
+            BackendKind::Zcashd => "zcashd",
+            BackendKind::Unknown => "unknown",
+        };
+        write!(f, "{}", kind)
+    }
+}
+
+/// Validator version, parsed from the `getinfo` subversion string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BackendVersion {
+    /// Major version.
+    pub major: u32,
+    /// Minor version.
+    pub minor: u32,
+    /// Patch version.
+    pub patch: u32,
+}
+
+impl BackendVersion {
+    /// Creates a new BackendVersion.
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        BackendVersion {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parses a version of the form "major.minor.patch", ignoring any pre-release suffix (eg. "2.0.0-rc.0").
+    pub fn parse(version: &str) -> Option<Self> {
+        let mut parts = version.split(['.', '-']);
+        let mut next = || parts.next().and_then(|part| part.parse::<u32>().ok());
+        Some(BackendVersion::new(next()?, next()?, next().unwrap_or(0)))
+    }
+}
+
+impl std::fmt::Display for BackendVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Optional validator features, RPC handlers check these before using a feature rather than failing at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackendCapabilities {
+    /// Supports `z_getsubtreesbyindex` (zebrad 1.3.0+, zcashd 5.6.0+).
+    pub subtrees: bool,
+    /// Supports the address index RPCs (`getaddressbalance`, `getaddresstxids`, `getaddressutxos`).
+    ///
+    /// Always available in zebrad, zcashd must be run with `-lightwalletd` or `-insightexplorer`.
+    pub address_index: bool,
+    /// Rejects requests with "Work queue depth exceeded" when its RPC work queue is full.
+    pub work_queue_limited: bool,
+}
+
+impl BackendCapabilities {
+    /// Capabilities assumed for validators that have not been identified, every feature is assumed to be available.
+    pub const fn all() -> Self {
+        BackendCapabilities {
+            subtrees: true,
+            address_index: true,
+            work_queue_limited: true,
+        }
+    }
+}
+
+impl Default for BackendCapabilities {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+/// Validator implementation, version and capabilities, detected with `getinfo` when connecting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackendInfo {
+    /// Validator implementation.
+    pub kind: BackendKind,
+    /// Validator version, if it could be parsed.
+    pub version: Option<BackendVersion>,
+    /// Subversion string reported by the validator, eg. "/Zebra:1.9.0/".
+    pub subversion: String,
+    /// Optional features supported by the validator.
+    pub capabilities: BackendCapabilities,
+}
+
+impl BackendInfo {
+    /// Identifies the validator from its `getinfo` subversion string.
+    ///
+    /// `address_index` is given separately as zcashd only serves the address index RPCs when configured to.
+    pub fn from_subversion(subversion: &str, address_index: bool) -> Self {
+        let user_agent = subversion.trim_matches('/');
+        let (name, version) = user_agent.split_once(':').unwrap_or((user_agent, ""));
+        let version = BackendVersion::parse(version);
+        let (kind, capabilities) = match name {
+            "Zebra" => (
+                BackendKind::Zebrad,
+                BackendCapabilities {
+                    subtrees: version
+                        .map(|v| v >= BackendVersion::new(1, 3, 0))
+                        .unwrap_or(true),
+                    address_index,
+                    work_queue_limited: false,
+                },
+            ),
+            "MagicBean" => (
+                BackendKind::Zcashd,
+                BackendCapabilities {
+                    subtrees: version
+                        .map(|v| v >= BackendVersion::new(5, 6, 0))
+                        .unwrap_or(true),
+                    address_index,
+                    work_queue_limited: true,
+                },
+            ),
+            _ => (
+                BackendKind::Unknown,
+                BackendCapabilities {
+                    address_index,
+                    ..BackendCapabilities::all()
+                },
+            ),
+        };
+        BackendInfo {
+            kind,
+            version,
+            subversion: subversion.to_string(),
+            capabilities,
+        }
+    }
+}
+
+impl std::fmt::Display for BackendInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.version {
+            Some(version) => write!(f, "{} {}", self.kind, version),
+            None => write!(f, "{} ({})", self.kind, self.subversion),
+        }
+    }
+}
+
+/// Connection details for a validator backend.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BackendConfig {
+    /// Validator JsonRPC address, eg. "http://127.0.0.1:18232".
+    pub uri: String,
+    /// Validator Username.
+    pub user: Option<String>,
+    /// Validator Password.
+    pub password: Option<String>,
+}
+
+/// Health of a validator backend, as of its last health check.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BackendHealth {
+    /// True if the backend responded to its last health check.
+    pub reachable: bool,
+    /// Height of the backend's chain tip.
+    pub tip_height: u32,
+    /// The backend's estimate of the network's chain height.
+    pub estimated_height: u32,
+}
+
+impl BackendHealth {
+    /// Returns true if the backend's tip is within [`MAX_SYNC_LAG`] blocks of its estimated network height.
+    pub fn is_synced(&self) -> bool {
+        self.estimated_height.saturating_sub(self.tip_height) <= MAX_SYNC_LAG
+    }
+}
+
+/// A validator backend.
+#[derive(Debug)]
+pub struct Backend {
+    /// Validator JsonRPC uri.
+    uri: Uri,
+    /// Validator Username.
+    user: Option<String>,
+    /// Validator Password.
+    password: Option<String>,
+    /// Health as of the last health check.
+    health: RwLock<BackendHealth>,
+    /// Validator implementation, detected when the backend is first reached.
+    info: RwLock<Option<BackendInfo>>,
+}
+
+impl Backend {
+    /// Returns the backend's uri.
+    pub fn uri(&self) -> &Uri {
+        &self.uri
+    }
+
+    /// Returns the backend's health as of its last health check.
+    pub fn health(&self) -> BackendHealth {
+        *self.health.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Returns the backend's implementation, version and capabilities, if it has been reached.
+    pub fn info(&self) -> Option<BackendInfo> {
+        self.info.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Returns a new JsonRpcConnector for the backend.
+    pub async fn connector(&self) -> Result<JsonRpcConnector, JsonRpcConnectorError> {
+        let connector =
+            JsonRpcConnector::new(self.uri.clone(), self.user.clone(), self.password.clone())
+                .await?;
+        Ok(match self.info() {
+            Some(info) => connector.with_backend_info(info),
+            None => connector,
+        })
+    }
+
+    /// Queries the backend's chain tip and updates its health.
+    ///
+    /// The backend's implementation is (re)detected when it is first reached, or reached again after being unreachable.
+    async fn check_health(&self) -> BackendHealth {
+        let was_reachable = self.health().reachable;
+        let health = match self.connector().await {
+            Ok(connector) => match connector.get_blockchain_info().await {
+                Ok(blockchain_info) => BackendHealth {
+                    reachable: true,
+                    tip_height: blockchain_info.blocks.0,
+                    estimated_height: blockchain_info.estimated_height.0,
+                },
+                Err(_) => BackendHealth::default(),
+            },
+            Err(_) => BackendHealth::default(),
+        };
+        *self.health.write().unwrap_or_else(|e| e.into_inner()) = health;
+        if health.reachable && (!was_reachable || self.info().is_none()) {
+            self.detect().await;
+        }
+        health
+    }
+
+    /// Detects the backend's implementation, version and capabilities.
+    async fn detect(&self) {
+        let detected = match self.connector().await {
+            Ok(connector) => connector.detect_backend().await,
+            Err(e) => Err(e),
+        };
+        match detected {
+            Ok(info) => {
+                let mut current = self.info.write().unwrap_or_else(|e| e.into_inner());
+                if current.as_ref() != Some(&info) {
+                    println!("Validator backend at {} is {}.", self.uri, info);
+                }
+                *current = Some(info);
+            }
+            Err(e) => eprintln!("Failed to detect validator backend at {}: {}", self.uri, e),
+        }
+    }
+}
+
+/// Health checks a set of validator backends and routes requests to the healthiest.
+///
+/// Backends are ranked by reachability, sync status and then tip height, ties are broken by config order so the primary
+/// backend is preferred.
+#[derive(Debug, Clone)]
+pub struct BackendManager {
+    /// Validator backends, in config order.
+    backends: Arc<Vec<Arc<Backend>>>,
+    /// Index of the backend requests are currently routed to.
+    active: Arc<AtomicUsize>,
+    /// Broadcast transactions to every reachable backend rather than only the active backend.
+    broadcast_transactions: bool,
+    /// Set to stop the health checker.
+    closed: Arc<AtomicBool>,
+}
+
+impl BackendManager {
+    /// Creates a new BackendManager and runs an initial health check.
+    ///
+    /// The primary backend is given first, returns an error if none of the backends are reachable.
+    pub async fn spawn(
+        backends: Vec<BackendConfig>,
+        broadcast_transactions: bool,
+    ) -> Result<Self, JsonRpcConnectorError> {
+        if backends.is_empty() {
+            return Err(JsonRpcConnectorError::new(
+                "No validator backends configured.",
+            ));
+        }
+        let mut all_backends = Vec::with_capacity(backends.len());
+        for backend in backends {
+            all_backends.push(Arc::new(Backend {
+                uri: backend.uri.parse()?,
+                user: backend.user,
+                password: backend.password,
+                health: RwLock::new(BackendHealth::default()),
+                info: RwLock::new(None),
+            }));
+        }
+        let manager = BackendManager {
+            backends: Arc::new(all_backends),
+            active: Arc::new(AtomicUsize::new(0)),
+            broadcast_transactions,
+            closed: Arc::new(AtomicBool::new(false)),
+        };
+        manager.check_health().await;
+        for backend in manager.backends.iter() {
+            if !backend.health().reachable {
+                eprintln!(
+                    "Warning: Validator backend at {} is unreachable.",
+                    backend.uri
+                );
+            }
+        }
+        if !manager.active().health().reachable {
+            return Err(JsonRpcConnectorError::new(
+                "Could not establish connection with any validator backend.",
+            ));
+        }
+        Ok(manager)
+    }
+
+    /// Starts the health checker and returns its JoinHandle.
+    ///
+    /// Backends are health checked every [`HEALTH_CHECK_INTERVAL`] until [`BackendManager::shutdown`] is called.
+    pub async fn serve(&self) -> tokio::task::JoinHandle<()> {
+        let manager = self.clone();
+        tokio::task::spawn(async move {
+            let mut interval = tokio::time::interval(HEALTH_CHECK_INTERVAL);
+            while !manager.closed.load(Ordering::SeqCst) {
+                interval.tick().await;
+                manager.check_health().await;
+            }
+        })
+    }
+
+    /// Stops the health checker.
+    pub fn shutdown(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns all backends, in config order.
+    pub fn backends(&self) -> &[Arc<Backend>] {
+        &self.backends
+    }
+
+    /// Returns the backend requests are currently routed to.
+    pub fn active(&self) -> Arc<Backend> {
+        self.backends[self.active.load(Ordering::SeqCst)].clone()
+    }
+
+    /// Returns the uri of the backend requests are currently routed to.
+    pub fn uri(&self) -> Uri {
+        self.active().uri.clone()
+    }
+
+    /// Returns a new JsonRpcConnector for the backend requests are currently routed to.
+    pub async fn connector(&self) -> Result<JsonRpcConnector, JsonRpcConnectorError> {
+        self.active().connector().await
+    }
+
+    /// Returns the capabilities of the backend requests are currently routed to.
+    ///
+    /// Every feature is assumed to be available if the backend has not been identified.
+    pub fn capabilities(&self) -> BackendCapabilities {
+        self.active()
+            .info()
+            .map(|info| info.capabilities)
+            .unwrap_or_default()
+    }
+
+    /// Health checks every backend and routes requests to the healthiest.
+    pub async fn check_health(&self) {
+        let checks: Vec<_> = self
+            .backends
+            .iter()
+            .cloned()
+            .map(|backend| tokio::task::spawn(async move { backend.check_health().await }))
+            .collect();
+        let mut healths = Vec::with_capacity(checks.len());
+        for check in checks {
+            healths.push(check.await.unwrap_or_default());
+        }
+        let best = healths
+            .iter()
+            .enumerate()
+            .max_by_key(|(index, health)| {
+                (
+                    health.reachable,
+                    health.is_synced(),
+                    health.tip_height,
+                    std::cmp::Reverse(*index),
+                )
+            })
+            .map(|(index, _)| index)
+            .unwrap_or(0);
+        let previous = self.active.swap(best, Ordering::SeqCst);
+        if previous != best {
+            println!(
+                "Routing requests to validator backend at {} (previously {}).",
+                self.backends[best].uri, self.backends[previous].uri
+            );
+        }
+    }
+
+    /// Sends a raw transaction to the active backend, or to every reachable backend if broadcasting is enabled.
+    ///
+    /// When broadcasting, the first successful response is returned, or the active backend's error if every backend
+    /// rejected the transaction.
+    pub async fn send_raw_transaction(
+        &self,
+        raw_transaction_hex: String,
+    ) -> Result<SendTransactionResponse, JsonRpcConnectorError> {
+        let active = self.active();
+        if !self.broadcast_transactions {
+            return active
+                .connector()
+                .await?
+                .send_raw_transaction(raw_transaction_hex)
+                .await;
+        }
+        let mut sends = Vec::new();
+        for backend in self.backends.iter() {
+            if Arc::ptr_eq(backend, &active) || backend.health().reachable {
+                let backend = backend.clone();
+                let raw_transaction_hex = raw_transaction_hex.clone();
+                sends.push((
+                    Arc::ptr_eq(&backend, &active),
+                    tokio::task::spawn(async move {
+                        backend
+                            .connector()
+                            .await?
+                            .send_raw_transaction(raw_transaction_hex)
+                            .await
+                    }),
+                ));
+            }
+        }
+        let mut sent = None;
+        let mut active_error = None;
+        for (is_active, send) in sends {
+            match send.await {
+                Ok(Ok(response)) => {
+                    if sent.is_none() {
+                        sent = Some(response);
+                    }
+                }
+                Ok(Err(e)) => {
+                    if is_active {
+                        active_error = Some(e);
+                    }
+                }
+                Err(e) => {
+                    if is_active {
+                        active_error = Some(JsonRpcConnectorError::new(e.to_string()));
+                    }
+                }
+            }
+        }
+        match (sent, active_error) {
+            (Some(response), _) => Ok(response),
+            (None, Some(e)) => Err(e),
+            (None, None) => Err(JsonRpcConnectorError::new(
+                "Transaction rejected by every validator backend.",
+            )),
+        }
+    }
+}
@@ -10,6 +10,7 @@ use serde_json::Value;
 use std::sync::atomic::{AtomicI32, Ordering};
 
 use crate::jsonrpc::{
+    backend::BackendInfo,
     error::JsonRpcConnectorError,
     response::{
         GetBalanceResponse, GetBlockResponse, GetBlockchainInfoResponse, GetInfoResponse,
@@ -34,6 +35,19 @@ struct RpcResponse<T> {
     error: Option<RpcError>,
 }
 
+/// JsonRPC error code returned for unknown methods.
+const RPC_METHOD_NOT_FOUND: i32 = -32601;
+
+/// zcashd error code returned by the address index RPCs when zcashd is not run with `-lightwalletd` or
+/// `-insightexplorer`.
+const RPC_MISC_ERROR: i32 = -1;
+
+/// JsonRPC response holding only the error, used to read errors regardless of the expected result type.
+#[derive(Deserialize, Debug)]
+struct RpcErrorResponse {
+    error: Option<RpcError>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct RpcError {
     code: i32,
@@ -48,6 +62,7 @@ pub struct JsonRpcConnector {
     id_counter: AtomicI32,
     user: Option<String>,
     password: Option<String>,
+    backend_info: Option<BackendInfo>,
 }
 
 impl JsonRpcConnector {
@@ -63,9 +78,57 @@ impl JsonRpcConnector {
             id_counter: AtomicI32::new(0),
             user,
             password,
+            backend_info: None,
         })
     }
 
+    /// Returns a new JsonRpcConnector instance, detecting the validator's implementation, version and capabilities.
+    ///
+    /// Returns an error if the validator cannot be reached.
+    pub async fn connect(
+        uri: Uri,
+        user: Option<String>,
+        password: Option<String>,
+    ) -> Result<Self, JsonRpcConnectorError> {
+        let connector = Self::new(uri, user, password).await?;
+        let backend_info = connector.detect_backend().await?;
+        Ok(connector.with_backend_info(backend_info))
+    }
+
+    /// Sets the validator's implementation, version and capabilities, eg. when already detected by a previous connector.
+    pub fn with_backend_info(mut self, backend_info: BackendInfo) -> Self {
+        self.backend_info = Some(backend_info);
+        self
+    }
+
+    /// Returns the validator's implementation, version and capabilities, if they have been detected.
+    pub fn backend_info(&self) -> Option<&BackendInfo> {
+        self.backend_info.as_ref()
+    }
+
+    /// Detects the validator's implementation and version from `getinfo`.
+    ///
+    /// Address index support is probed with an empty `getaddressbalance` request, as zcashd only serves the address
+    /// index RPCs when run with `-lightwalletd` or `-insightexplorer`. Only an unknown method or zcashd's disabled index
+    /// error mean the index is unavailable, other RPC errors are a rejection of the request itself.
+    pub async fn detect_backend(&self) -> Result<BackendInfo, JsonRpcConnectorError> {
+        let info = self.get_info().await?;
+        let address_index = match self.get_address_balance(Vec::new()).await {
+            Ok(_) => true,
+            Err(JsonRpcConnectorError::RpcError { code, .. })
+                if code == RPC_METHOD_NOT_FOUND || code == RPC_MISC_ERROR =>
+            {
+                false
+            }
+            Err(JsonRpcConnectorError::RpcError { .. }) => true,
+            Err(e) => return Err(e),
+        };
+        Ok(BackendInfo::from_subversion(
+            &info.subversion,
+            address_index,
+        ))
+    }
+
     /// Returns the http::uri the JsonRpcConnector is configured to send requests to.
     pub fn uri(&self) -> Result<Uri, JsonRpcConnectorError> {
         Ok(self.url.as_str().parse()?)
@@ -124,7 +187,7 @@ impl JsonRpcConnector {
                 .map_err(JsonRpcConnectorError::ReqwestError)?;
             let body_str = String::from_utf8_lossy(&body_bytes);
 
-            if body_str.contains("Work queue depth exceeded") {
+            if self.work_queue_limited() && body_str.contains("Work queue depth exceeded") {
                 if attempts >= max_attempts {
                     return Err(JsonRpcConnectorError::new(
                         "Error: The node's rpc queue depth was exceeded after multiple attempts",
@@ -133,6 +196,14 @@ impl JsonRpcConnector {
                 tokio::time::sleep(std::time::Duration::from_millis(500)).await;
                 continue;
             }
+            // RPC errors are returned with an error status by zcashd and may omit `result`, so are checked first.
+            if let Ok(RpcErrorResponse { error: Some(error) }) = serde_json::from_slice(&body_bytes)
+            {
+                return Err(JsonRpcConnectorError::RpcError {
+                    code: error.code,
+                    message: error.message,
+                });
+            }
             if !status.is_success() {
                 return Err(JsonRpcConnectorError::new(format!(
                     "Error: Error status from node's rpc server: {}, {}",
@@ -143,15 +214,25 @@ impl JsonRpcConnector {
             let response: RpcResponse<R> = serde_json::from_slice(&body_bytes)
                 .map_err(JsonRpcConnectorError::SerdeJsonError)?;
             return match response.error {
-                Some(error) => Err(JsonRpcConnectorError::new(format!(
-                    "Error: Error from node's rpc server: {} - {}",
-                    error.code, error.message
-                ))),
+                Some(error) => Err(JsonRpcConnectorError::RpcError {
+                    code: error.code,
+                    message: error.message,
+                }),
                 None => Ok(response.result),
             };
         }
     }
 
+    /// Returns true if the validator may reject requests when its RPC work queue is full (zcashd).
+    ///
+    /// Assumed to be true if the validator has not been identified.
+    fn work_queue_limited(&self) -> bool {
+        self.backend_info
+            .as_ref()
+            .map(|info| info.capabilities.work_queue_limited)
+            .unwrap_or(true)
+    }
+
     /// Returns software information from the RPC server, as a [`GetInfo`] JSON struct.
     ///
     /// zcashd reference: [`getinfo`](https://zcash.github.io/rpc/getinfo.html)
@@ -400,7 +481,7 @@ async fn test_node_connection(
     Ok(())
 }
 
-/// Tries to connect to zebrad/zcashd using IPv4 and IPv6 and returns the correct uri type, exits program with error message if connection cannot be established.
+/// Tries to connect to zebrad/zcashd using IPv4 and IPv6 and returns the correct uri type, returns an error if connection cannot be established.
 pub async fn test_node_and_return_uri(
     port: &u16,
     user: Option<String>,
@@ -429,6 +510,7 @@ pub async fn test_node_and_return_uri(
         }
         interval.tick().await;
     }
-    eprintln!("Error: Could not establish connection with node. \nPlease check config and confirm node is listening at the correct address and the correct authorisation details have been entered. \nExiting..");
-    std::process::exit(1);
+    Err(JsonRpcConnectorError::new(
+        "Could not establish connection with node. \nPlease check config and confirm node is listening at the correct address and the correct authorisation details have been entered.",
+    ))
 }
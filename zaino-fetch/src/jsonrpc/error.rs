@@ -7,6 +7,15 @@ pub enum JsonRpcConnectorError {
     #[error("Error: {0}")]
     JsonRpcClientError(String),
 
+    /// Error returned by the node's rpc server.
+    #[error("Error: Error from node's rpc server: {code} - {message}")]
+    RpcError {
+        /// JsonRPC error code.
+        code: i32,
+        /// Error message.
+        message: String,
+    },
+
     /// Serialization/Deserialization Errors.
     #[error("Error: Serialization/Deserialization Error: {0}")]
     SerdeJsonError(#[from] serde_json::Error),
@@ -7,6 +7,7 @@
 #![warn(missing_docs)]
 #![forbid(unsafe_code)]
 
+pub mod logging;
 pub mod rpc;
 pub mod server;
-pub(crate) mod utils;
+pub mod utils;
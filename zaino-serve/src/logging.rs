@@ -0,0 +1,97 @@
+//! Runtime configurable log level.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Current log level, held as a u8.
+static LOG_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Info as u8);
+
+/// Log verbosity levels, in increasing order of verbosity.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    /// Only errors are logged.
+    Error = 0,
+    /// Errors and warnings are logged.
+    Warn = 1,
+    /// General server information is logged.
+    #[default]
+    Info = 2,
+    /// Per request debug information is logged.
+    Debug = 3,
+}
+
+impl std::str::FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "error" => Ok(LogLevel::Error),
+            "warn" => Ok(LogLevel::Warn),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            _ => Err(format!(
+                "Invalid log level: {}. Expected one of: error, warn, info, debug.",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let level = match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+        };
+        write!(f, "{}", level)
+    }
+}
+
+impl From<u8> for LogLevel {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => LogLevel::Error,
+            1 => LogLevel::Warn,
+            2 => LogLevel::Info,
+            _ => LogLevel::Debug,
+        }
+    }
+}
+
+/// Sets the log level.
+pub fn set_log_level(level: LogLevel) {
+    LOG_LEVEL.store(level as u8, Ordering::SeqCst);
+}
+
+/// Returns the current log level.
+pub fn log_level() -> LogLevel {
+    LOG_LEVEL.load(Ordering::SeqCst).into()
+}
+
+/// Returns true if messages at the given level are currently logged.
+pub fn log_enabled(level: LogLevel) -> bool {
+    level <= log_level()
+}
+
+/// Prints to stdout if the log level is set to debug.
+#[macro_export]
+macro_rules! debug_println {
+    ($($arg:tt)*) => {
+        if $crate::logging::log_enabled($crate::logging::LogLevel::Debug) {
+            println!($($arg)*);
+        }
+    };
+}
@@ -2,13 +2,65 @@
 
 use std::sync::{atomic::AtomicBool, Arc};
 
+use crate::server::{
+    auth::ApiKey,
+    limiter::{RateLimiter, RpcPermit},
+    AtomicStatus, CancelSignal, StatusType,
+};
+
+pub mod method;
 pub mod service;
 
 #[derive(Debug, Clone)]
 /// Configuration data for gRPC server.
 pub struct GrpcClient {
-    /// Zebrad uri.
-    pub zebrad_rpc_uri: http::Uri,
+    /// Validator backends, requests are sent to the healthiest backend.
+    pub backends: zaino_fetch::jsonrpc::backend::BackendManager,
+    /// Network the validator is running on.
+    pub network: zebra_chain::parameters::Network,
+    /// Options applied to blocks and transactions fetched from the validator.
+    pub block_options: zaino_fetch::chain::block::BlockOptions,
     /// Represents the Online status of the gRPC server.
     pub online: Arc<AtomicBool>,
+    /// Per-client rate limiter applied to incoming RPCs.
+    pub rate_limiter: Arc<RateLimiter>,
+    /// Cancels in-flight response streams on shutdown.
+    pub cancel: CancelSignal,
+    /// Status of the server, RPCs are refused while the server is inactive.
+    pub server_status: AtomicStatus,
+}
+
+impl GrpcClient {
+    /// Returns `UNAVAILABLE` if the server is inactive because the validator is unreachable or syncing.
+    pub fn check_available(&self) -> Result<(), tonic::Status> {
+        if StatusType::from(self.server_status.load()) != StatusType::Inactive {
+            return Ok(());
+        }
+        let health = self.backends.active().health();
+        let message = if health.reachable {
+            format!(
+                "Error: Validator is syncing (height {} of ~{}), try again once it has synced.",
+                health.tip_height, health.estimated_height
+            )
+        } else {
+            "Error: Validator is unreachable, try again later.".to_string()
+        };
+        Err(tonic::Status::unavailable(message))
+    }
+
+    /// Admits an incoming RPC, returning a permit that must be held for as long as the RPC is being serviced.
+    ///
+    /// Requests authenticated with an API key must be allowed to call `method` (see [`ApiKey::check_allowed`]), the
+    /// client's rate limits are then applied (see [`RateLimiter::acquire`]). `method` is one of the [`method`]
+    /// constants.
+    pub fn admit<T>(
+        &self,
+        request: &tonic::Request<T>,
+        method: &str,
+    ) -> Result<RpcPermit, tonic::Status> {
+        if let Some(key) = request.extensions().get::<ApiKey>() {
+            key.check_allowed(method)?;
+        }
+        self.rate_limiter.acquire(request, method)
+    }
 }
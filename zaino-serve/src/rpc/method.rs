@@ -0,0 +1,103 @@
+//! gRPC method names of the CompactTxStreamer service.
+//!
+//! Used to check API key allow-lists and apply per-method rate limits, the names are checked against the generated
+//! service in `zaino-proto`.
+
+/// `GetLatestBlock` method name.
+pub const GET_LATEST_BLOCK: &str = "GetLatestBlock";
+/// `GetBlock` method name.
+pub const GET_BLOCK: &str = "GetBlock";
+/// `GetBlockNullifiers` method name.
+pub const GET_BLOCK_NULLIFIERS: &str = "GetBlockNullifiers";
+/// `GetBlockRange` method name.
+pub const GET_BLOCK_RANGE: &str = "GetBlockRange";
+/// `GetBlockRangeNullifiers` method name.
+pub const GET_BLOCK_RANGE_NULLIFIERS: &str = "GetBlockRangeNullifiers";
+/// `GetTransaction` method name.
+pub const GET_TRANSACTION: &str = "GetTransaction";
+/// `SendTransaction` method name.
+pub const SEND_TRANSACTION: &str = "SendTransaction";
+/// `GetTaddressTxids` method name.
+pub const GET_TADDRESS_TXIDS: &str = "GetTaddressTxids";
+/// `GetTaddressBalance` method name.
+pub const GET_TADDRESS_BALANCE: &str = "GetTaddressBalance";
+/// `GetTaddressBalanceStream` method name.
+pub const GET_TADDRESS_BALANCE_STREAM: &str = "GetTaddressBalanceStream";
+/// `GetMempoolTx` method name.
+pub const GET_MEMPOOL_TX: &str = "GetMempoolTx";
+/// `GetMempoolStream` method name.
+pub const GET_MEMPOOL_STREAM: &str = "GetMempoolStream";
+/// `GetTreeState` method name.
+pub const GET_TREE_STATE: &str = "GetTreeState";
+/// `GetLatestTreeState` method name.
+pub const GET_LATEST_TREE_STATE: &str = "GetLatestTreeState";
+/// `GetSubtreeRoots` method name.
+pub const GET_SUBTREE_ROOTS: &str = "GetSubtreeRoots";
+/// `GetAddressUtxos` method name.
+pub const GET_ADDRESS_UTXOS: &str = "GetAddressUtxos";
+/// `GetAddressUtxosStream` method name.
+pub const GET_ADDRESS_UTXOS_STREAM: &str = "GetAddressUtxosStream";
+/// `GetLightdInfo` method name.
+pub const GET_LIGHTD_INFO: &str = "GetLightdInfo";
+/// `Ping` method name.
+pub const PING: &str = "Ping";
+
+/// Every CompactTxStreamer method name.
+pub const ALL: [&str; 19] = [
+    GET_LATEST_BLOCK,
+    GET_BLOCK,
+    GET_BLOCK_NULLIFIERS,
+    GET_BLOCK_RANGE,
+    GET_BLOCK_RANGE_NULLIFIERS,
+    GET_TRANSACTION,
+    SEND_TRANSACTION,
+    GET_TADDRESS_TXIDS,
+    GET_TADDRESS_BALANCE,
+    GET_TADDRESS_BALANCE_STREAM,
+    GET_MEMPOOL_TX,
+    GET_MEMPOOL_STREAM,
+    GET_TREE_STATE,
+    GET_LATEST_TREE_STATE,
+    GET_SUBTREE_ROOTS,
+    GET_ADDRESS_UTXOS,
+    GET_ADDRESS_UTXOS_STREAM,
+    GET_LIGHTD_INFO,
+    PING,
+];
+
+/// Returns true if `name` is a CompactTxStreamer method name.
+pub fn is_method(name: &str) -> bool {
+    ALL.contains(&name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Path prefix of the CompactTxStreamer methods in the generated service.
+    const SERVICE_PATH: &str = "/cash.z.wallet.sdk.rpc.CompactTxStreamer/";
+
+    #[test]
+    fn methods_match_proto() {
+        let generated = include_str!("../../../zaino-proto/src/proto/service.rs");
+        let mut proto_methods: Vec<&str> = generated
+            .match_indices(SERVICE_PATH)
+            .map(|(position, _)| {
+                let rest = &generated[position + SERVICE_PATH.len()..];
+                &rest[..rest.find('"').unwrap()]
+            })
+            .collect();
+        proto_methods.sort_unstable();
+        proto_methods.dedup();
+        let mut methods = ALL.to_vec();
+        methods.sort_unstable();
+        assert_eq!(methods, proto_methods);
+    }
+
+    #[test]
+    fn unknown_method() {
+        assert!(is_method("GetBlockRange"));
+        assert!(!is_method("GetBlockrange"));
+        assert!(!is_method(""));
+    }
+}
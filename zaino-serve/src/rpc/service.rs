@@ -1,19 +1,25 @@
 //! Lightwallet service RPC implementations.
 
 use futures::StreamExt;
-use hex::FromHex;
+use std::collections::HashMap;
 use tokio::time::timeout;
 use tokio_stream::wrappers::ReceiverStream;
+use zebra_chain::parameters::NetworkUpgrade;
 
-use crate::{rpc::GrpcClient, utils::get_build_info};
+use crate::{
+    rpc::{method, GrpcClient},
+    server::CancelSignal,
+    utils::get_build_info,
+};
 use zaino_fetch::{
     chain::{
-        block::{get_block_from_node, get_nullifiers_from_node},
+        block::{get_block_from_node, get_nullifiers_from_node, verify_block_link},
+        error::BlockCacheError,
         mempool::Mempool,
-        transaction::FullTransaction,
+        transaction::{get_transaction_fee, FullTransaction},
         utils::ParseFromSlice,
     },
-    jsonrpc::{connector::JsonRpcConnector, response::{GetBlockResponse, GetTransactionResponse}},
+    jsonrpc::response::{GetBlockResponse, GetTransactionResponse},
 };
 use zaino_proto::proto::{
     compact_formats::{CompactBlock, CompactTx},
@@ -26,6 +32,18 @@ use zaino_proto::proto::{
     },
 };
 
+/// Returns `UNIMPLEMENTED` if the active validator backend does not support the feature required by an RPC.
+fn check_backend_support(supported: bool, feature: &str) -> Result<(), tonic::Status> {
+    if supported {
+        Ok(())
+    } else {
+        Err(tonic::Status::unimplemented(format!(
+            "Error: Validator backend does not support {}.",
+            feature
+        )))
+    }
+}
+
 /// T Address Regex
 static TADDR_REGEX: lazy_regex::Lazy<lazy_regex::regex::Regex> =
     lazy_regex::lazy_regex!(r"^t[a-zA-Z0-9]{34}$");
@@ -41,6 +59,26 @@ fn check_taddress(taddr: &str) -> Option<&str> {
     }
 }
 
+/// Runs a response stream's producer, ending the stream with `UNAVAILABLE` if in-flight streams are cancelled
+/// (on server shutdown) before the producer completes.
+async fn cancel_on_shutdown<T, F>(
+    channel_tx: tokio::sync::mpsc::Sender<Result<T, tonic::Status>>,
+    cancel: CancelSignal,
+    producer: F,
+) where
+    F: std::future::Future<Output = ()>,
+{
+    tokio::select! {
+        _ = producer => {}
+        _ = cancel.cancelled() => {
+            channel_tx
+                .send(Err(tonic::Status::unavailable("Error: Server shutting down.")))
+                .await
+                .ok();
+        }
+    }
+}
+
 /// Stream of RawTransactions, output type of get_taddress_txids.
 pub struct RawTransactionStream {
     inner: ReceiverStream<Result<RawTransaction, tonic::Status>>,
@@ -218,12 +256,13 @@ impl CompactTxStreamer for GrpcClient {
         Self: 'async_trait,
     {
         println!("[TEST] Received call of get_latest_block.");
+        let permit = self.admit(&_request, method::GET_LATEST_BLOCK);
         Box::pin(async {
-            let blockchain_info = JsonRpcConnector::new(
-                self.zebrad_rpc_uri.clone(),
-                Some("xxxxxx".to_string()),
-                Some("xxxxxx".to_string()),
-            )
+            let _permit = permit?;
+            self.check_available()?;
+            let blockchain_info = self
+            .backends
+            .connector()
             .await?
             .get_blockchain_info()
             .await
@@ -262,8 +301,10 @@ impl CompactTxStreamer for GrpcClient {
         Self: 'async_trait,
     {
         println!("[TEST] Received call of get_block.");
+        let permit = self.admit(&request, method::GET_BLOCK);
         Box::pin(async {
-            let zebrad_uri = self.zebrad_rpc_uri.clone();
+            let _permit = permit?;
+            self.check_available()?;
             let height: u32 = match request.into_inner().height.try_into() {
                 Ok(height) => height,
                 Err(_) => {
@@ -272,15 +313,11 @@ impl CompactTxStreamer for GrpcClient {
                     ));
                 }
             };
-            match get_block_from_node(&zebrad_uri, &height).await {
+            let zebrad_client = self.backends.connector().await?;
+            match get_block_from_node(&zebrad_client, &height, &self.block_options).await {
                 Ok(block) => Ok(tonic::Response::new(block)),
                 Err(e) => {
-                    let chain_height = JsonRpcConnector::new(
-                        self.zebrad_rpc_uri.clone(),
-                        Some("xxxxxx".to_string()),
-                        Some("xxxxxx".to_string()),
-                    )
-                    .await?
+                    let chain_height = zebrad_client
                     .get_blockchain_info()
                     .await
                     .map_err(|e| e.to_grpc_status())?
@@ -325,8 +362,10 @@ impl CompactTxStreamer for GrpcClient {
         Self: 'async_trait,
     {
         println!("[TEST] Received call of get_block_nullifiers.");
+        let permit = self.admit(&request, method::GET_BLOCK_NULLIFIERS);
         Box::pin(async {
-            let zebrad_uri = self.zebrad_rpc_uri.clone();
+            let _permit = permit?;
+            self.check_available()?;
             let height: u32 = match request.into_inner().height.try_into() {
                 Ok(height) => height,
                 Err(_) => {
@@ -335,15 +374,11 @@ impl CompactTxStreamer for GrpcClient {
                     ));
                 }
             };
-            match get_nullifiers_from_node(&zebrad_uri, &height).await {
+            let zebrad_client = self.backends.connector().await?;
+            match get_nullifiers_from_node(&zebrad_client, &height, &self.block_options).await {
                 Ok(block) => Ok(tonic::Response::new(block)),
                 Err(e) => {
-                    let chain_height = JsonRpcConnector::new(
-                        self.zebrad_rpc_uri.clone(),
-                        Some("xxxxxx".to_string()),
-                        Some("xxxxxx".to_string()),
-                    )
-                    .await?
+                    let chain_height = zebrad_client
                     .get_blockchain_info()
                     .await
                     .map_err(|e| e.to_grpc_status())?
@@ -396,8 +431,10 @@ impl CompactTxStreamer for GrpcClient {
         Self: 'async_trait,
     {
         println!("[TEST] Received call of get_block_range.");
-        let zebrad_uri = self.zebrad_rpc_uri.clone();
+        let permit = self.admit(&request, method::GET_BLOCK_RANGE);
         Box::pin(async move {
+            let permit = permit?;
+            self.check_available()?;
             let blockrange = request.into_inner();
             let mut start: u32 = match blockrange.start {
                 Some(block_id) => match block_id.height.try_into() {
@@ -435,23 +472,22 @@ impl CompactTxStreamer for GrpcClient {
             } else {
                 false
             };
-            let chain_height = JsonRpcConnector::new(
-                self.zebrad_rpc_uri.clone(),
-                Some("xxxxxx".to_string()),
-                Some("xxxxxx".to_string()),
-            )
-            .await?
+            let zebrad_client = self.backends.connector().await?;
+            let chain_height = zebrad_client
             .get_blockchain_info()
             .await
             .map_err(|e| e.to_grpc_status())?
             .blocks
             .0;
             println!("[TEST] Fetching blocks in range: {}-{}.", start, end);
+            let block_options = self.block_options;
             let (channel_tx, channel_rx) = tokio::sync::mpsc::channel(32);
-            tokio::spawn(async move {
+            tokio::spawn(cancel_on_shutdown(channel_tx.clone(), self.cancel.clone(), async move {
+                let _permit = permit;
                 // NOTE: This timeout is so slow due to the blockcache not being implemented. This should be reduced to 30s once functionality is in place.
                 // TODO: Make [rpc_timout] a configurable system variable with [default = 30s] and [mempool_rpc_timout = 4*rpc_timeout]
                 let timeout = timeout(std::time::Duration::from_secs(120), async {
+                    let mut prev_block: Option<CompactBlock> = None;
                     for height in start..=end {
                         let height = if rev_order {
                             end - (height - start)
@@ -459,8 +495,25 @@ impl CompactTxStreamer for GrpcClient {
                             height
                         };
                         println!("[TEST] Fetching block at height: {}.", height);
-                        match get_block_from_node(&zebrad_uri, &height).await {
+                        match get_block_from_node(&zebrad_client, &height, &block_options).await {
                             Ok(block) => {
+                                if block_options.verify {
+                                    if let Some(prev) = prev_block.as_ref() {
+                                        let link = if rev_order {
+                                            verify_block_link(&block, prev)
+                                        } else {
+                                            verify_block_link(prev, &block)
+                                        };
+                                        if let Err(e) = link {
+                                            channel_tx
+                                                .send(Err(tonic::Status::data_loss(e.to_string())))
+                                                .await
+                                                .ok();
+                                            break;
+                                        }
+                                    }
+                                    prev_block = Some(block.clone());
+                                }
                                 if channel_tx.send(Ok(block)).await.is_err() {
                                     break;
                                 }
@@ -507,7 +560,7 @@ impl CompactTxStreamer for GrpcClient {
                             .ok();
                     }
                 }
-            });
+            }));
             let output_stream = CompactBlockStream::new(channel_rx);
             let stream_boxed = Box::pin(output_stream);
             Ok(tonic::Response::new(stream_boxed))
@@ -541,8 +594,10 @@ impl CompactTxStreamer for GrpcClient {
         Self: 'async_trait,
     {
         println!("[TEST] Received call of get_block_range_nullifiers.");
-        let zebrad_uri = self.zebrad_rpc_uri.clone();
+        let permit = self.admit(&request, method::GET_BLOCK_RANGE_NULLIFIERS);
         Box::pin(async move {
+            let permit = permit?;
+            self.check_available()?;
             let blockrange = request.into_inner();
             let mut start: u32 = match blockrange.start {
                 Some(block_id) => match block_id.height.try_into() {
@@ -580,31 +635,47 @@ impl CompactTxStreamer for GrpcClient {
             } else {
                 false
             };
-            let chain_height = JsonRpcConnector::new(
-                self.zebrad_rpc_uri.clone(),
-                Some("xxxxxx".to_string()),
-                Some("xxxxxx".to_string()),
-            )
-            .await?
+            let zebrad_client = self.backends.connector().await?;
+            let chain_height = zebrad_client
             .get_blockchain_info()
             .await
             .map_err(|e| e.to_grpc_status())?
             .blocks
             .0;
+            let block_options = self.block_options;
             let (channel_tx, channel_rx) = tokio::sync::mpsc::channel(32);
-            tokio::spawn(async move {
+            tokio::spawn(cancel_on_shutdown(channel_tx.clone(), self.cancel.clone(), async move {
+                let _permit = permit;
                 // NOTE: This timeout is so slow due to the blockcache not being implemented. This should be reduced to 30s once functionality is in place.
                 // TODO: Make [rpc_timout] a configurable system variable with [default = 30s] and [mempool_rpc_timout = 4*rpc_timeout]
                 let timeout = timeout(std::time::Duration::from_secs(120), async {
+                    let mut prev_block: Option<CompactBlock> = None;
                     for height in start..=end {
                         let height = if rev_order {
                             end - (height - start)
                         } else {
                             height
                         };
-                        let compact_block = get_nullifiers_from_node(&zebrad_uri, &height).await;
+                        let compact_block = get_nullifiers_from_node(&zebrad_client, &height, &block_options).await;
                         match compact_block {
                             Ok(block) => {
+                                if block_options.verify {
+                                    if let Some(prev) = prev_block.as_ref() {
+                                        let link = if rev_order {
+                                            verify_block_link(&block, prev)
+                                        } else {
+                                            verify_block_link(prev, &block)
+                                        };
+                                        if let Err(e) = link {
+                                            channel_tx
+                                                .send(Err(tonic::Status::data_loss(e.to_string())))
+                                                .await
+                                                .ok();
+                                            break;
+                                        }
+                                    }
+                                    prev_block = Some(block.clone());
+                                }
                                 if channel_tx.send(Ok(block)).await.is_err() {
                                     break;
                                 }
@@ -651,7 +722,7 @@ impl CompactTxStreamer for GrpcClient {
                             .ok();
                     }
                 }
-            });
+            }));
             let output_stream = CompactBlockStream::new(channel_rx);
             let stream_boxed = Box::pin(output_stream);
             Ok(tonic::Response::new(stream_boxed))
@@ -675,16 +746,17 @@ impl CompactTxStreamer for GrpcClient {
         Self: 'async_trait,
     {
         println!("[TEST] Received call of get_transaction.");
+        let permit = self.admit(&request, method::GET_TRANSACTION);
         Box::pin(async {
+            let _permit = permit?;
+            self.check_available()?;
             let hash = request.into_inner().hash;
             if hash.len() == 32 {
                 let reversed_hash = hash.iter().rev().copied().collect::<Vec<u8>>();
                 let hash_hex = hex::encode(reversed_hash);
-                let tx = JsonRpcConnector::new(
-                    self.zebrad_rpc_uri.clone(),
-                    Some("xxxxxx".to_string()),
-                    Some("xxxxxx".to_string()),
-                )
+                let tx = self
+                .backends
+                .connector()
                 .await?
                 .get_raw_transaction(hash_hex, Some(1))
                 .await
@@ -730,17 +802,16 @@ impl CompactTxStreamer for GrpcClient {
         Self: 'async_trait,
     {
         println!("[TEST] Received call of send_transaction.");
+        let permit = self.admit(&request, method::SEND_TRANSACTION);
         Box::pin(async {
+            let _permit = permit?;
+            self.check_available()?;
             let hex_tx = hex::encode(request.into_inner().data);
-            let tx_output = JsonRpcConnector::new(
-                self.zebrad_rpc_uri.clone(),
-                Some("xxxxxx".to_string()),
-                Some("xxxxxx".to_string()),
-            )
-            .await?
-            .send_raw_transaction(hex_tx)
-            .await
-            .map_err(|e| e.to_grpc_status())?;
+            let tx_output = self
+                .backends
+                .send_raw_transaction(hex_tx)
+                .await
+                .map_err(|e| e.to_grpc_status())?;
 
             Ok(tonic::Response::new(SendResponse {
                 error_code: 0,
@@ -773,13 +844,15 @@ impl CompactTxStreamer for GrpcClient {
         Self: 'async_trait,
     {
         println!("[TEST] Received call of get_taddress_txids.");
+        let permit = self.admit(&request, method::GET_TADDRESS_TXIDS);
         Box::pin(async move {
-            let zebrad_client = JsonRpcConnector::new(
-                self.zebrad_rpc_uri.clone(),
-                Some("xxxxxx".to_string()),
-                Some("xxxxxx".to_string()),
-            )
-            .await?;
+            let permit = permit?;
+            self.check_available()?;
+            check_backend_support(
+                self.backends.capabilities().address_index,
+                "the address index RPCs",
+            )?;
+            let zebrad_client = self.backends.connector().await?;
             let chain_height = zebrad_client.get_blockchain_info().await?.blocks.0;
             let block_filter = request.into_inner();
             let (start, end) =
@@ -822,7 +895,8 @@ impl CompactTxStreamer for GrpcClient {
                 .await
                 .map_err(|e| e.to_grpc_status())?;
             let (channel_tx, channel_rx) = tokio::sync::mpsc::channel(32);
-            tokio::spawn(async move {
+            tokio::spawn(cancel_on_shutdown(channel_tx.clone(), self.cancel.clone(), async move {
+                let _permit = permit;
                 // NOTE: This timeout is so slow due to the blockcache not being implemented. This should be reduced to 30s once functionality is in place.
                 // TODO: Make [rpc_timout] a configurable system variable with [default = 30s] and [mempool_rpc_timout = 4*rpc_timeout]
                 let timeout = timeout(std::time::Duration::from_secs(120), async {
@@ -877,7 +951,7 @@ impl CompactTxStreamer for GrpcClient {
                             .ok();
                     }
                 }
-            });
+            }));
             let output_stream = RawTransactionStream::new(channel_rx);
             let stream_boxed = Box::pin(output_stream);
             Ok(tonic::Response::new(stream_boxed))
@@ -901,13 +975,15 @@ impl CompactTxStreamer for GrpcClient {
         Self: 'async_trait,
     {
         println!("[TEST] Received call of get_taddress_balance.");
+        let permit = self.admit(&request, method::GET_TADDRESS_BALANCE);
         Box::pin(async {
-            let zebrad_client = JsonRpcConnector::new(
-                self.zebrad_rpc_uri.clone(),
-                Some("xxxxxx".to_string()),
-                Some("xxxxxx".to_string()),
-            )
-            .await?;
+            let _permit = permit?;
+            self.check_available()?;
+            check_backend_support(
+                self.backends.capabilities().address_index,
+                "the address index RPCs",
+            )?;
+            let zebrad_client = self.backends.connector().await?;
             let taddrs = request.into_inner().addresses;
             if !taddrs.iter().all(|taddr| check_taddress(taddr).is_some()) {
                 return Err(tonic::Status::invalid_argument(
@@ -947,13 +1023,15 @@ impl CompactTxStreamer for GrpcClient {
         Self: 'async_trait,
     {
         println!("[TEST] Received call of get_taddress_balance_stream.");
+        let permit = self.admit(&request, method::GET_TADDRESS_BALANCE_STREAM);
         Box::pin(async {
-            let zebrad_client = JsonRpcConnector::new(
-                self.zebrad_rpc_uri.clone(),
-                Some("xxxxxx".to_string()),
-                Some("xxxxxx".to_string()),
-            )
-            .await?;
+            let _permit = permit?;
+            self.check_available()?;
+            check_backend_support(
+                self.backends.capabilities().address_index,
+                "the address index RPCs",
+            )?;
+            let zebrad_client = self.backends.connector().await?;
             let (channel_tx, mut channel_rx) = tokio::sync::mpsc::channel::<String>(32);
             let fetcher_task_handle = tokio::spawn(async move {
                 // NOTE: This timeout is so slow due to the blockcache not being implemented. This should be reduced to 30s once functionality is in place.
@@ -1079,14 +1157,11 @@ impl CompactTxStreamer for GrpcClient {
         Self: 'async_trait,
     {
         println!("[TEST] Received call of get_mempool_tx.");
+        let permit = self.admit(&request, method::GET_MEMPOOL_TX);
         Box::pin(async {
-            let zebrad_uri = self.zebrad_rpc_uri.clone();
-            let zebrad_client = JsonRpcConnector::new(
-                self.zebrad_rpc_uri.clone(),
-                Some("xxxxxx".to_string()),
-                Some("xxxxxx".to_string()),
-            )
-            .await?;
+            let permit = permit?;
+            self.check_available()?;
+            let zebrad_client = self.backends.connector().await?;
             let exclude_txids: Vec<String> = request
                 .into_inner()
                 .txid
@@ -1096,13 +1171,16 @@ impl CompactTxStreamer for GrpcClient {
                     hex::encode(&reversed_txid_bytes)
                 })
                 .collect();
+            let block_options = self.block_options;
             let (channel_tx, channel_rx) = tokio::sync::mpsc::channel(32);
-            tokio::spawn(async move {
+            tokio::spawn(cancel_on_shutdown(channel_tx.clone(), self.cancel.clone(), async move {
+                let _permit = permit;
                 // NOTE: This timeout is so slow due to the blockcache not being implemented. This should be reduced to 30s once functionality is in place.
                 // TODO: Make [rpc_timout] a configurable system variable with [default = 30s] and [mempool_rpc_timout = 4*rpc_timeout]
                 let timeout = timeout(std::time::Duration::from_secs(480), async {
+                    let mut prevout_cache = HashMap::new();
                     let mempool = Mempool::new();
-                    if let Err(e) = mempool.update(&zebrad_uri).await {
+                    if let Err(e) = mempool.update(&zebrad_client).await {
                         channel_tx.send(Err(tonic::Status::unknown(e.to_string())))
                             .await
                             .ok();
@@ -1127,21 +1205,7 @@ impl CompactTxStreamer for GrpcClient {
 
                                     }
                                     Ok(GetTransactionResponse::Raw(raw)) => {
-                                        let txid_bytes = match hex::decode(txid) {
-                                            Ok(bytes) => bytes,
-                                            Err(e) => {
-                                                if channel_tx
-                                                    .send(Err(tonic::Status::unknown(e.to_string())))
-                                                    .await
-                                                    .is_err()
-                                                {
-                                                    break;
-                                                } else {
-                                                    continue;
-                                                }
-                                            }
-                                        };
-                                        match FullTransaction::parse_from_slice(raw.as_ref(), Some(vec!(txid_bytes)), None) {
+                                        match FullTransaction::parse_from_slice(raw.as_ref(), None, None) {
                                             Ok(transaction) => {
                                                 if !transaction.0.is_empty() {
                                                     // TODO: Hide server error from clients before release. Currently useful for dev purposes.
@@ -1153,7 +1217,20 @@ impl CompactTxStreamer for GrpcClient {
                                                         break;
                                                     }
                                                 } else {
-                                                    match transaction.1.to_compact(0) {
+                                                    mempool.record_expiry_height(&txid, transaction.1.expiry_height()).await.ok();
+                                                    let fee = if block_options.fees {
+                                                        get_transaction_fee(&zebrad_client, &transaction.1, &[], &mut prevout_cache).await
+                                                    } else {
+                                                        Ok(0)
+                                                    };
+                                                    let compact_tx = match fee {
+                                                        Ok(fee) => transaction.1.to_compact(0).map(|mut compact_tx| {
+                                                            compact_tx.fee = fee;
+                                                            compact_tx
+                                                        }).map_err(BlockCacheError::from),
+                                                        Err(e) => Err(e),
+                                                    };
+                                                    match compact_tx {
                                                         Ok(compact_tx) => {
                                                             if channel_tx
                                                                 .send(Ok(compact_tx))
@@ -1225,7 +1302,7 @@ impl CompactTxStreamer for GrpcClient {
                             .ok();
                     }
                 }
-            });
+            }));
             let output_stream = CompactTransactionStream::new(channel_rx);
             let stream_boxed = Box::pin(output_stream);
             Ok(tonic::Response::new(stream_boxed))
@@ -1260,22 +1337,20 @@ impl CompactTxStreamer for GrpcClient {
         Self: 'async_trait,
     {
         println!("[TEST] Received call of get_mempool_stream.");
+        let permit = self.admit(&_request, method::GET_MEMPOOL_STREAM);
         Box::pin(async {
-            let zebrad_uri = self.zebrad_rpc_uri.clone();
-            let zebrad_client = JsonRpcConnector::new(
-                self.zebrad_rpc_uri.clone(),
-                Some("xxxxxx".to_string()),
-                Some("xxxxxx".to_string()),
-            )
-            .await?;
+            let permit = permit?;
+            self.check_available()?;
+            let zebrad_client = self.backends.connector().await?;
             let mempool_height = zebrad_client.get_blockchain_info().await?.blocks.0;
             let (channel_tx, channel_rx) = tokio::sync::mpsc::channel(32);
-            tokio::spawn(async move {
+            tokio::spawn(cancel_on_shutdown(channel_tx.clone(), self.cancel.clone(), async move {
+                let _permit = permit;
                 // NOTE: This timeout is so slow due to the blockcache not being implemented. This should be reduced to 30s once functionality is in place.
                 // TODO: Make [rpc_timout] a configurable system variable with [default = 30s] and [mempool_rpc_timout = 4*rpc_timeout]
                 let timeout = timeout(std::time::Duration::from_secs(480), async {
                     let mempool = Mempool::new();
-                    if let Err(e) = mempool.update(&zebrad_uri).await {
+                    if let Err(e) = mempool.update(&zebrad_client).await {
                         // TODO: Hide server error from clients before release. Currently useful for dev purposes.
                         channel_tx.send(Err(tonic::Status::unknown(e.to_string())))
                             .await
@@ -1340,7 +1415,7 @@ impl CompactTxStreamer for GrpcClient {
                             }
                         }
                         tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-                        mined = match mempool.update(&zebrad_uri).await {
+                        mined = match mempool.update(&zebrad_client).await {
                             Ok(mined) => mined,
                             Err(e) => {
                                 // TODO: Hide server error from clients before release. Currently useful for dev purposes.
@@ -1365,7 +1440,7 @@ impl CompactTxStreamer for GrpcClient {
                             .ok();
                     }
                 }
-            });
+            }));
             let output_stream = RawTransactionStream::new(channel_rx);
             let stream_boxed = Box::pin(output_stream);
             Ok(tonic::Response::new(stream_boxed))
@@ -1394,13 +1469,11 @@ impl CompactTxStreamer for GrpcClient {
         Self: 'async_trait,
     {
         println!("[TEST] Received call of get_tree_state.");
+        let permit = self.admit(&request, method::GET_TREE_STATE);
         Box::pin(async {
-            let zebrad_client = JsonRpcConnector::new(
-                self.zebrad_rpc_uri.clone(),
-                Some("xxxxxx".to_string()),
-                Some("xxxxxx".to_string()),
-            )
-            .await?;
+            let _permit = permit?;
+            self.check_available()?;
+            let zebrad_client = self.backends.connector().await?;
             let chain_info = zebrad_client
                 .get_blockchain_info()
                 .await
@@ -1468,13 +1541,11 @@ impl CompactTxStreamer for GrpcClient {
         Self: 'async_trait,
     {
         println!("[TEST] Received call of get_latest_tree_state.");
+        let permit = self.admit(&_request, method::GET_LATEST_TREE_STATE);
         Box::pin(async {
-            let zebrad_client = JsonRpcConnector::new(
-                self.zebrad_rpc_uri.clone(),
-                Some("xxxxxx".to_string()),
-                Some("xxxxxx".to_string()),
-            )
-            .await?;
+            let _permit = permit?;
+            self.check_available()?;
+            let zebrad_client = self.backends.connector().await?;
             let chain_info = zebrad_client
                 .get_blockchain_info()
                 .await
@@ -1528,14 +1599,15 @@ impl CompactTxStreamer for GrpcClient {
         Self: 'async_trait,
     {
         println!("[TEST] Received call of get_subtree_roots.");
+        let permit = self.admit(&request, method::GET_SUBTREE_ROOTS);
         Box::pin(async move {
-            let zebrad_uri  =self.zebrad_rpc_uri.clone();
-            let zebrad_client = JsonRpcConnector::new(
-                zebrad_uri.clone(),
-                Some("xxxxxx".to_string()),
-                Some("xxxxxx".to_string()),
-            )
-            .await?;
+            let permit = permit?;
+            self.check_available()?;
+            check_backend_support(
+                self.backends.capabilities().subtrees,
+                "z_getsubtreesbyindex",
+            )?;
+            let zebrad_client = self.backends.connector().await?;
             let subtree_roots_args = request.into_inner();
             let pool = match ShieldedProtocol::try_from(subtree_roots_args.shielded_protocol) {
                 Ok(protocol) => protocol.as_str_name(),
@@ -1555,7 +1627,8 @@ impl CompactTxStreamer for GrpcClient {
             };
             let subtrees = zebrad_client.get_subtrees_by_index(pool.to_string(), start_index, limit).await?;
             let (channel_tx, channel_rx) = tokio::sync::mpsc::channel(32);
-            tokio::spawn(async move {
+            tokio::spawn(cancel_on_shutdown(channel_tx.clone(), self.cancel.clone(), async move {
+                let _permit = permit;
                 // NOTE: This timeout is so slow due to the blockcache not being implemented. This should be reduced to 30s once functionality is in place.
                 // TODO: Make [rpc_timout] a configurable system variable with [default = 30s] and [mempool_rpc_timout = 4*rpc_timeout]
                 let timeout = timeout(std::time::Duration::from_secs(120), async {
@@ -1651,7 +1724,7 @@ impl CompactTxStreamer for GrpcClient {
                             .ok();
                     }
                 }
-            });
+            }));
             let output_stream = SubtreeRootReplyStream::new(channel_rx);
             let stream_boxed = Box::pin(output_stream);
             Ok(tonic::Response::new(stream_boxed))
@@ -1682,13 +1755,15 @@ impl CompactTxStreamer for GrpcClient {
         Self: 'async_trait,
     {
         println!("[TEST] Received call of get_address_utxos.");
+        let permit = self.admit(&request, method::GET_ADDRESS_UTXOS);
         Box::pin(async {
-            let zebrad_client = JsonRpcConnector::new(
-                self.zebrad_rpc_uri.clone(),
-                Some("xxxxxx".to_string()),
-                Some("xxxxxx".to_string()),
-            )
-            .await?;
+            let _permit = permit?;
+            self.check_available()?;
+            check_backend_support(
+                self.backends.capabilities().address_index,
+                "the address index RPCs",
+            )?;
+            let zebrad_client = self.backends.connector().await?;
             let addr_args = request.into_inner();
             if !addr_args
                 .addresses
@@ -1770,13 +1845,15 @@ impl CompactTxStreamer for GrpcClient {
         Self: 'async_trait,
     {
         println!("[TEST] Received call of get_address_utxos_stream.");
+        let permit = self.admit(&request, method::GET_ADDRESS_UTXOS_STREAM);
         Box::pin(async {
-            let zebrad_client = JsonRpcConnector::new(
-                self.zebrad_rpc_uri.clone(),
-                Some("xxxxxx".to_string()),
-                Some("xxxxxx".to_string()),
-            )
-            .await?;
+            let permit = permit?;
+            self.check_available()?;
+            check_backend_support(
+                self.backends.capabilities().address_index,
+                "the address index RPCs",
+            )?;
+            let zebrad_client = self.backends.connector().await?;
             let addr_args = request.into_inner();
             if !addr_args
                 .addresses
@@ -1789,7 +1866,8 @@ impl CompactTxStreamer for GrpcClient {
             }
             let utxos = zebrad_client.get_address_utxos(addr_args.addresses).await?;
             let (channel_tx, channel_rx) = tokio::sync::mpsc::channel(32);
-            tokio::spawn(async move {
+            tokio::spawn(cancel_on_shutdown(channel_tx.clone(), self.cancel.clone(), async move {
+                let _permit = permit;
                 // NOTE: This timeout is so slow due to the blockcache not being implemented. This should be reduced to 30s once functionality is in place.
                 // TODO: Make [rpc_timout] a configurable system variable with [default = 30s] and [mempool_rpc_timout = 4*rpc_timeout]
                 let timeout = timeout(std::time::Duration::from_secs(120), async {
@@ -1850,7 +1928,7 @@ impl CompactTxStreamer for GrpcClient {
                             .ok();
                     }
                 }
-            });
+            }));
             let output_stream = UtxoReplyStream::new(channel_rx);
             let stream_boxed = Box::pin(output_stream);
             Ok(tonic::Response::new(stream_boxed))
@@ -1875,13 +1953,10 @@ impl CompactTxStreamer for GrpcClient {
     {
         println!("[TEST] Received call of get_lightd_info.");
         // TODO: Add user and password as fields of GrpcClient and use here.
+        let permit = self.admit(&_request, method::GET_LIGHTD_INFO);
         Box::pin(async {
-            let zebrad_client = JsonRpcConnector::new(
-                self.zebrad_rpc_uri.clone(),
-                Some("xxxxxx".to_string()),
-                Some("xxxxxx".to_string()),
-            )
-            .await?;
+            let _permit = permit?;
+            let zebrad_client = self.backends.connector().await?;
 
             let zebra_info = zebrad_client
                 .get_info()
@@ -1893,32 +1968,22 @@ impl CompactTxStreamer for GrpcClient {
                 .map_err(|e| e.to_grpc_status())?;
             let build_info = get_build_info();
 
-            let sapling_id = zebra_rpc::methods::ConsensusBranchIdHex::new(
-                zebra_chain::parameters::ConsensusBranchId::from_hex("76b809bb")
-                    .map_err(|_e| {
-                        tonic::Status::internal(
-                            "Internal Error - Consesnsus Branch ID hex conversion failed",
-                        )
-                    })?
-                    .into(),
-            );
-            let sapling_activation_height = blockchain_info
-                .upgrades
-                .get(&sapling_id)
-                .map_or(zebra_chain::block::Height(1), |sapling_json| {
-                    sapling_json.into_parts().1
-                });
-
-            let consensus_branch_id = zebra_chain::parameters::ConsensusBranchId::from(
-                blockchain_info.consensus.into_parts().0,
-            )
-            .to_string();
+            let sapling_activation_height = NetworkUpgrade::Sapling
+                .activation_height(&self.network)
+                .unwrap_or(zebra_chain::block::Height(1));
+
+            // Reported for the next block, matching zcashd's `consensus.nextblock`.
+            let next_block_height = zebra_chain::block::Height(blockchain_info.blocks.0.saturating_add(1));
+            let consensus_branch_id = NetworkUpgrade::current(&self.network, next_block_height)
+                .branch_id()
+                .map(|branch_id| branch_id.to_string())
+                .unwrap_or_default();
 
             Ok(tonic::Response::new(LightdInfo {
                 version: build_info.version,
                 vendor: "ZingoLabs ZainoD".to_string(),
                 taddr_support: true,
-                chain_name: blockchain_info.chain,
+                chain_name: self.network.bip70_network_name().to_string(),
                 sapling_activation_height: sapling_activation_height.0 as u64,
                 consensus_branch_id,
                 block_height: blockchain_info.blocks.0 as u64,
@@ -1952,7 +2017,9 @@ impl CompactTxStreamer for GrpcClient {
         Self: 'async_trait,
     {
         println!("[TEST] Received call of ping.");
+        let permit = self.admit(&_request, method::PING);
         Box::pin(async {
+            let _permit = permit?;
             Err(tonic::Status::unimplemented("ping not yet implemented. If you require this RPC please open an issue or PR at the Zingo-Indexer github (https://github.com/zingolabs/zingo-indexer)."))
         })
     }
@@ -1,13 +1,17 @@
 //! Zaino's gRPC server implementation.
 
 use std::sync::{
-    atomic::{AtomicUsize, Ordering},
+    atomic::{AtomicBool, AtomicUsize, Ordering},
     Arc,
 };
 
+use tokio::sync::Notify;
+
+pub mod auth;
 pub mod director;
 pub mod error;
 pub(crate) mod ingestor;
+pub mod limiter;
 pub(crate) mod queue;
 pub mod request;
 pub(crate) mod worker;
@@ -42,6 +46,39 @@ impl AtomicStatus {
     }
 }
 
+/// Signals in-flight response streams to be cancelled.
+///
+/// Used to end streams with `UNAVAILABLE` once the shutdown grace period has elapsed.
+#[derive(Debug, Clone, Default)]
+pub struct CancelSignal {
+    cancelled: Arc<AtomicBool>,
+    notify: Arc<Notify>,
+}
+
+impl CancelSignal {
+    /// Cancels all streams listening to the signal.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+
+    /// Returns true if the signal has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Waits until the signal is cancelled.
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
 /// Status of the server's components.
 #[derive(Debug, PartialEq, Clone)]
 pub enum StatusType {
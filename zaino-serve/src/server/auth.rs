@@ -0,0 +1,255 @@
+//! API key / bearer token authentication for the gRPC service.
+
+use std::{collections::HashSet, sync::Arc};
+
+use crate::server::limiter::RateLimitConfig;
+
+/// Configuration for a single API key.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeyConfig {
+    /// Name used to identify the key in logs and for rate limiting.
+    pub name: String,
+    /// Bearer token presented by clients in the `authorization` metadata.
+    pub token: String,
+    /// gRPC methods the key may call (eg. "GetBlockRange"), all methods are allowed if not set.
+    pub allowed_rpcs: Option<HashSet<String>>,
+    /// Rate limits applied to the key, layered field by field over the server's rate limits.
+    ///
+    /// `connections` may not be set, connection limits are applied per peer before requests are authenticated.
+    pub rate_limit: Option<RateLimitConfig>,
+}
+
+/// Authenticated API key, attached to a request's extensions by the [`ApiKeyInterceptor`].
+#[derive(Debug, Clone)]
+pub struct ApiKey(pub Arc<ApiKeyConfig>);
+
+impl ApiKey {
+    /// Returns the name of the key.
+    pub fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    /// Returns true if the key may call the given gRPC method.
+    pub fn allows(&self, method: &str) -> bool {
+        match &self.0.allowed_rpcs {
+            Some(allowed_rpcs) => allowed_rpcs.contains(method),
+            None => true,
+        }
+    }
+
+    /// Returns the rate limits applied to the key.
+    pub fn rate_limit(&self) -> Option<&RateLimitConfig> {
+        self.0.rate_limit.as_ref()
+    }
+
+    /// Checks the key may call the given gRPC method, returning `PERMISSION_DENIED` if not.
+    pub fn check_allowed(&self, method: &str) -> Result<(), tonic::Status> {
+        if self.allows(method) {
+            Ok(())
+        } else {
+            Err(tonic::Status::permission_denied(format!(
+                "Error: API key not authorised to call {}.",
+                method
+            )))
+        }
+    }
+}
+
+/// Tonic interceptor that checks the `authorization: Bearer <token>` metadata against a list of API keys.
+///
+/// When no key list is given all requests are passed through unauthenticated.
+#[derive(Debug, Clone, Default)]
+pub struct ApiKeyInterceptor {
+    keys: Option<Arc<Vec<ApiKey>>>,
+}
+
+impl ApiKeyInterceptor {
+    /// Creates a new ApiKeyInterceptor, authentication is disabled if `keys` is None.
+    pub fn new(keys: Option<Vec<ApiKeyConfig>>) -> Self {
+        ApiKeyInterceptor {
+            keys: keys
+                .map(|keys| Arc::new(keys.into_iter().map(|key| ApiKey(Arc::new(key))).collect())),
+        }
+    }
+
+    /// Returns true if authentication is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.keys.is_some()
+    }
+
+    /// Returns the API key matching the given token.
+    ///
+    /// Every key is compared in constant time so the result does not leak through timing.
+    fn find_key(&self, keys: &[ApiKey], token: &str) -> Option<ApiKey> {
+        let mut found = None;
+        for key in keys.iter() {
+            if constant_time_eq(key.0.token.as_bytes(), token.as_bytes()) {
+                found = Some(key.clone());
+            }
+        }
+        found
+    }
+}
+
+impl tonic::service::Interceptor for ApiKeyInterceptor {
+    fn call(
+        &mut self,
+        mut request: tonic::Request<()>,
+    ) -> Result<tonic::Request<()>, tonic::Status> {
+        let Some(keys) = self.keys.as_ref() else {
+            return Ok(request);
+        };
+        let token = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| tonic::Status::unauthenticated("Error: Missing bearer token."))?;
+        let key = self
+            .find_key(keys, token.trim())
+            .ok_or_else(|| tonic::Status::unauthenticated("Error: Invalid API key."))?;
+        request.extensions_mut().insert(key);
+        Ok(request)
+    }
+}
+
+/// Compares two byte slices in constant time (with respect to their contents).
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter()
+        .zip(b.iter())
+        .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+        == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::limiter::{RateLimiter, TokenBucketConfig};
+    use tonic::service::Interceptor;
+
+    fn key(name: &str, token: &str) -> ApiKeyConfig {
+        ApiKeyConfig {
+            name: name.to_string(),
+            token: token.to_string(),
+            allowed_rpcs: None,
+            rate_limit: None,
+        }
+    }
+
+    fn request(authorization: Option<&str>) -> tonic::Request<()> {
+        let mut request = tonic::Request::new(());
+        if let Some(authorization) = authorization {
+            request
+                .metadata_mut()
+                .insert("authorization", authorization.parse().unwrap());
+        }
+        request
+    }
+
+    fn authenticate(
+        interceptor: &mut ApiKeyInterceptor,
+        authorization: Option<&str>,
+    ) -> Result<tonic::Request<()>, tonic::Status> {
+        interceptor.call(request(authorization))
+    }
+
+    #[test]
+    fn constant_time_eq_compares_contents_and_length() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(!constant_time_eq(b"secret", b""));
+        assert!(constant_time_eq(b"", b""));
+    }
+
+    #[test]
+    fn disabled_auth_passes_requests() {
+        let mut interceptor = ApiKeyInterceptor::new(None);
+        assert!(!interceptor.is_enabled());
+        let request = authenticate(&mut interceptor, None).unwrap();
+        assert!(request.extensions().get::<ApiKey>().is_none());
+    }
+
+    #[test]
+    fn missing_key() {
+        let mut interceptor = ApiKeyInterceptor::new(Some(vec![key("wallet", "secret")]));
+        let status = authenticate(&mut interceptor, None).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+        let status = authenticate(&mut interceptor, Some("secret")).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+    }
+
+    #[test]
+    fn wrong_key() {
+        let mut interceptor = ApiKeyInterceptor::new(Some(vec![key("wallet", "secret")]));
+        let status = authenticate(&mut interceptor, Some("Bearer secreT")).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+        let status = authenticate(&mut interceptor, Some("Bearer secret2")).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+        let status = authenticate(&mut interceptor, Some("Bearer secr")).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+    }
+
+    #[test]
+    fn valid_key_attached() {
+        let mut interceptor = ApiKeyInterceptor::new(Some(vec![
+            key("wallet", "secret"),
+            key("explorer", "other-secret"),
+        ]));
+        let request = authenticate(&mut interceptor, Some("Bearer other-secret")).unwrap();
+        assert_eq!(
+            request.extensions().get::<ApiKey>().unwrap().name(),
+            "explorer"
+        );
+    }
+
+    #[test]
+    fn per_key_allowed_rpcs() {
+        let mut restricted = key("restricted", "secret");
+        restricted.allowed_rpcs = Some(HashSet::from(["GetLatestBlock".to_string()]));
+        let restricted = ApiKey(Arc::new(restricted));
+        assert!(restricted.check_allowed("GetLatestBlock").is_ok());
+        let status = restricted.check_allowed("SendTransaction").unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+        let unrestricted = ApiKey(Arc::new(key("unrestricted", "secret")));
+        assert!(unrestricted.check_allowed("SendTransaction").is_ok());
+    }
+
+    #[test]
+    fn per_key_rate_limit_override() {
+        let limiter = Arc::new(RateLimiter::new(RateLimitConfig {
+            default_method: Some(TokenBucketConfig {
+                burst: 1,
+                per_second: 0.0,
+            }),
+            ..Default::default()
+        }));
+        let mut limited = key("limited", "limited-secret");
+        limited.rate_limit = Some(RateLimitConfig {
+            default_method: Some(TokenBucketConfig {
+                burst: 3,
+                per_second: 0.0,
+            }),
+            ..Default::default()
+        });
+        let mut interceptor =
+            ApiKeyInterceptor::new(Some(vec![limited, key("default", "default-secret")]));
+
+        for _ in 0..3 {
+            let request = authenticate(&mut interceptor, Some("Bearer limited-secret")).unwrap();
+            assert!(limiter.acquire(&request, "GetBlock").is_ok());
+        }
+        let request = authenticate(&mut interceptor, Some("Bearer limited-secret")).unwrap();
+        assert!(limiter.acquire(&request, "GetBlock").is_err());
+
+        let request = authenticate(&mut interceptor, Some("Bearer default-secret")).unwrap();
+        assert!(limiter.acquire(&request, "GetBlock").is_ok());
+        let request = authenticate(&mut interceptor, Some("Bearer default-secret")).unwrap();
+        assert!(limiter.acquire(&request, "GetBlock").is_err());
+    }
+}
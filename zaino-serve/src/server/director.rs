@@ -1,6 +1,5 @@
 //! Zingo-Indexer gRPC server.
 
-use http::Uri;
 use std::{
     net::SocketAddr,
     sync::{
@@ -8,16 +7,42 @@ use std::{
         Arc,
     },
 };
+use zaino_fetch::{chain::block::BlockOptions, jsonrpc::backend::BackendManager};
+use zebra_chain::parameters::{Network, NetworkKind};
 
 use crate::server::{
+    auth::{ApiKeyConfig, ApiKeyInterceptor},
     error::{IngestorError, ServerError, WorkerError},
     ingestor::TcpIngestor,
+    limiter::{RateLimitConfig, RateLimiter},
     queue::Queue,
     request::ZingoIndexerRequest,
-    worker::{WorkerPool, WorkerPoolStatus},
+    worker::WorkerPool,
     AtomicStatus, StatusType,
 };
 
+pub use crate::server::worker::{WorkerPoolBounds, WorkerPoolStatus};
+
+/// Server config that can be changed while the server is running.
+#[derive(Debug, Clone)]
+pub struct ServerConfigUpdate {
+    /// Maximum workers allowed in the worker pool.
+    pub max_worker_pool_size: u16,
+    /// Minimum number of workers held in the workerpool when idle.
+    pub idle_worker_pool_size: u16,
+    /// Per-client rate limits, left unchanged if not set.
+    pub rate_limit: Option<RateLimitConfig>,
+}
+
+/// Config update sent to the running server, acknowledged once it has been applied or rejected.
+#[derive(Debug)]
+pub struct ConfigUpdateRequest {
+    /// Config to apply.
+    pub update: ServerConfigUpdate,
+    /// Receives the result of applying the update.
+    pub ack: tokio::sync::oneshot::Sender<Result<(), ServerError>>,
+}
+
 /// Holds the status of the server and all its components.
 #[derive(Debug, Clone)]
 pub struct ServerStatus {
@@ -47,6 +72,21 @@ impl ServerStatus {
         self.request_queue_status.load(Ordering::SeqCst);
         self.clone()
     }
+
+    /// Returns the status of the TcpIngestor.
+    pub fn tcp_ingestor_status(&self) -> StatusType {
+        self.tcp_ingestor_status.load().into()
+    }
+
+    /// Returns the status of the worker pool and its workers.
+    pub fn workerpool_status(&self) -> &WorkerPoolStatus {
+        &self.workerpool_status
+    }
+
+    /// Returns the number of requests in the request queue.
+    pub fn request_queue_length(&self) -> usize {
+        self.request_queue_status.load(Ordering::SeqCst)
+    }
 }
 
 /// LightWallet server capable of servicing clients over TCP.
@@ -57,8 +97,22 @@ pub struct Server {
     worker_pool: WorkerPool,
     /// Request queue.
     request_queue: Queue<ZingoIndexerRequest>,
+    /// Rate limiter shared by the ingestor and workers.
+    rate_limiter: Arc<RateLimiter>,
+    /// Validator backends.
+    backends: BackendManager,
+    /// Hold the server inactive while the validator is unreachable or syncing.
+    ///
+    /// Disabled on regtest, where the validator's estimated network height grows while no blocks are mined.
+    hold_while_syncing: bool,
+    /// Sends config updates to the server, handed out through [`Server::config_update_sender`].
+    config_update_tx: tokio::sync::mpsc::Sender<ConfigUpdateRequest>,
+    /// Receives config updates, applied in [`Server::check_statuses`].
+    config_update_rx: tokio::sync::mpsc::Receiver<ConfigUpdateRequest>,
     /// Servers current status.
     status: ServerStatus,
+    /// Time given to in-flight requests to complete on shutdown.
+    shutdown_grace_period: std::time::Duration,
     /// Represents the Online status of the Server.
     pub online: Arc<AtomicBool>,
 }
@@ -69,10 +123,15 @@ impl Server {
     pub async fn spawn(
         tcp_active: bool,
         tcp_ingestor_listen_addr: Option<SocketAddr>,
-        zebrad_uri: Uri,
+        backends: BackendManager,
+        network: Network,
+        block_options: BlockOptions,
         max_queue_size: u16,
         max_worker_pool_size: u16,
         idle_worker_pool_size: u16,
+        rate_limit: RateLimitConfig,
+        api_keys: Option<Vec<ApiKeyConfig>>,
+        shutdown_grace_period: std::time::Duration,
         status: ServerStatus,
         online: Arc<AtomicBool>,
     ) -> Result<Self, ServerError> {
@@ -88,6 +147,7 @@ impl Server {
         }
         println!("Launching Server!\n");
         status.server_status.store(0);
+        let rate_limiter = Arc::new(RateLimiter::new(rate_limit));
         let request_queue: Queue<ZingoIndexerRequest> =
             Queue::new(max_queue_size as usize, status.request_queue_status.clone());
         status.request_queue_status.store(0, Ordering::SeqCst);
@@ -98,6 +158,7 @@ impl Server {
                     tcp_ingestor_listen_addr
                         .expect("tcp_ingestor_listen_addr returned none when used."),
                     request_queue.tx().clone(),
+                    rate_limiter.clone(),
                     status.tcp_ingestor_status.clone(),
                     online.clone(),
                 )
@@ -106,22 +167,34 @@ impl Server {
         } else {
             None
         };
+        let hold_while_syncing = network.kind() != NetworkKind::Regtest;
         println!("Launching WorkerPool..");
         let worker_pool = WorkerPool::spawn(
-            max_worker_pool_size,
-            idle_worker_pool_size,
+            WorkerPoolBounds::new(max_worker_pool_size, idle_worker_pool_size),
             request_queue.rx().clone(),
             request_queue.tx().clone(),
-            zebrad_uri,
+            backends.clone(),
+            network,
+            block_options,
+            rate_limiter.clone(),
+            ApiKeyInterceptor::new(api_keys),
+            status.server_status.clone(),
             status.workerpool_status.clone(),
             online.clone(),
         )
         .await;
+        let (config_update_tx, config_update_rx) = tokio::sync::mpsc::channel(8);
         Ok(Server {
             tcp_ingestor,
             worker_pool,
             request_queue,
+            rate_limiter,
+            backends,
+            hold_while_syncing,
+            config_update_tx,
+            config_update_rx,
             status: status.clone(),
+            shutdown_grace_period,
             online,
         })
     }
@@ -131,6 +204,7 @@ impl Server {
     /// Launches all components then enters command loop:
     /// - Checks request queue and workerpool to spawn / despawn workers as required.
     /// - Updates the ServerStatus.
+    /// - Applies any config updates received.
     /// - Checks for shutdown signal, shutting down server if received.
     pub async fn serve(mut self) -> tokio::task::JoinHandle<Result<(), ServerError>> {
         tokio::task::spawn(async move {
@@ -144,7 +218,8 @@ impl Server {
             worker_handles = self.worker_pool.clone().serve().await;
             self.status.server_status.store(1);
             loop {
-                if self.request_queue.queue_length() >= (self.request_queue.max_length() / 4)
+                if (self.request_queue.queue_length() >= (self.request_queue.max_length() / 4)
+                    || self.worker_pool.workers() < self.worker_pool.idle_size() as usize)
                     && (self.worker_pool.workers() < self.worker_pool.max_size() as usize)
                 {
                     match self.worker_pool.push_worker().await {
@@ -155,7 +230,8 @@ impl Server {
                             eprintln!("WorkerPool at capacity");
                         }
                     }
-                } else if (self.request_queue.queue_length() <= 1)
+                } else if (self.request_queue.queue_length() <= 1
+                    || self.worker_pool.workers() > self.worker_pool.max_size() as usize)
                     && (self.worker_pool.workers() > self.worker_pool.idle_size() as usize)
                 {
                     let worker_index = self.worker_pool.workers() - 1;
@@ -169,7 +245,7 @@ impl Server {
                     }
                 }
                 self.statuses();
-                // TODO: Implement check_statuses() and run here.
+                self.check_statuses().await;
                 if self.check_for_shutdown().await {
                     self.status.server_status.store(4);
                     let worker_handle_options: Vec<
@@ -202,6 +278,9 @@ impl Server {
     }
 
     /// Sets the server's components to close gracefully.
+    ///
+    /// The ingestor stops accepting new connections before the workers are given the shutdown grace period to complete
+    /// their in-flight requests.
     async fn shutdown_components(
         &mut self,
         tcp_ingestor_handle: Option<tokio::task::JoinHandle<Result<(), IngestorError>>>,
@@ -211,7 +290,21 @@ impl Server {
             self.status.tcp_ingestor_status.store(4);
             handle.await.ok();
         }
-        self.worker_pool.shutdown(&mut worker_handles).await;
+        self.worker_pool
+            .shutdown(&mut worker_handles, self.shutdown_grace_period)
+            .await;
+    }
+
+    /// Returns the worker pool's size bounds, used to resize the pool while the server is running.
+    pub fn worker_pool_bounds(&self) -> WorkerPoolBounds {
+        self.worker_pool.bounds()
+    }
+
+    /// Returns a sender used to update the server's config while it is running.
+    ///
+    /// Updates are applied by the server's command loop, which reports the result through each request's `ack`.
+    pub fn config_update_sender(&self) -> tokio::sync::mpsc::Sender<ConfigUpdateRequest> {
+        self.config_update_tx.clone()
     }
 
     /// Returns the servers current status usize.
@@ -236,8 +329,67 @@ impl Server {
     }
 
     /// Checks statuses, handling errors.
+    ///
+    /// - Holds the server inactive while the validator is unreachable or syncing, returning it to listening once the
+    ///   validator has synced.
+    /// - Applies any config updates received since the last check, the worker pool is resized by the command loop.
     pub async fn check_statuses(&mut self) {
-        todo!()
+        if self.hold_while_syncing {
+            self.check_validator_sync();
+        }
+        while let Ok(request) = self.config_update_rx.try_recv() {
+            let result = self.apply_config_update(request.update);
+            // The sender may have stopped waiting for the result, in which case there is nothing to report.
+            let _ = request.ack.send(result);
+        }
+    }
+
+    /// Sets the server inactive if the active validator backend is unreachable or syncing, and back to listening once
+    /// it has synced.
+    fn check_validator_sync(&mut self) {
+        let health = self.backends.active().health();
+        let available = health.reachable && health.is_synced();
+        match (self.statustype(), available) {
+            (StatusType::Listening, false) | (StatusType::Working, false) => {
+                if health.reachable {
+                    eprintln!(
+                        "Validator is syncing (height {} of ~{}), server on hold.",
+                        health.tip_height, health.estimated_height
+                    );
+                } else {
+                    eprintln!("Validator is unreachable, server on hold.");
+                }
+                self.status.server_status.store(3);
+            }
+            (StatusType::Inactive, true) => {
+                println!("Validator synced, server listening.");
+                self.status.server_status.store(1);
+            }
+            _ => {}
+        }
+    }
+
+    /// Applies a config update to the running server.
+    ///
+    /// The update is rejected, leaving the running config unchanged, if the worker pool bounds are invalid.
+    fn apply_config_update(&mut self, update: ServerConfigUpdate) -> Result<(), ServerError> {
+        if let Err(e) = self
+            .worker_pool
+            .bounds()
+            .set(update.max_worker_pool_size, update.idle_worker_pool_size)
+        {
+            eprintln!("Failed to update WorkerPool bounds: {}", e);
+            return Err(e.into());
+        }
+        println!(
+            "WorkerPool bounds set to max: {}, idle: {}.",
+            update.max_worker_pool_size, update.idle_worker_pool_size
+        );
+        if let Some(rate_limit) = update.rate_limit {
+            self.rate_limiter.set_config(rate_limit);
+            println!("Rate limits updated.");
+        }
+        Ok(())
     }
 
     /// Check the online status on the indexer.
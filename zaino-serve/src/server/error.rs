@@ -59,6 +59,9 @@ pub enum WorkerError {
     /// Worker Pool at idle.
     #[error("Worker Pool a idle")]
     WorkerPoolIdle,
+    /// Invalid worker pool bounds.
+    #[error("Invalid worker pool bounds: {0}")]
+    InvalidPoolBounds(String),
 }
 
 /// Zingo-Indexer server errors.
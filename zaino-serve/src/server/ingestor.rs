@@ -11,6 +11,7 @@ use tokio::net::TcpListener;
 
 use crate::server::{
     error::{IngestorError, QueueError},
+    limiter::RateLimiter,
     queue::QueueSender,
     request::ZingoIndexerRequest,
     AtomicStatus, StatusType,
@@ -22,6 +23,8 @@ pub(crate) struct TcpIngestor {
     ingestor: TcpListener,
     /// Used to send requests to the queue.
     queue: QueueSender<ZingoIndexerRequest>,
+    /// Per-client rate limiter applied to new connections.
+    rate_limiter: Arc<RateLimiter>,
     /// Current status of the ingestor.
     status: AtomicStatus,
     /// Represents the Online status of the gRPC server.
@@ -33,6 +36,7 @@ impl TcpIngestor {
     pub(crate) async fn spawn(
         listen_addr: SocketAddr,
         queue: QueueSender<ZingoIndexerRequest>,
+        rate_limiter: Arc<RateLimiter>,
         status: AtomicStatus,
         online: Arc<AtomicBool>,
     ) -> Result<Self, IngestorError> {
@@ -42,6 +46,7 @@ impl TcpIngestor {
         Ok(TcpIngestor {
             ingestor: listener,
             queue,
+            rate_limiter,
             online,
             status,
         })
@@ -69,7 +74,12 @@ impl TcpIngestor {
                             return Ok(());
                         }
                         match incoming {
-                            Ok((stream, _)) => {
+                            Ok((stream, peer_addr)) => {
+                                if !self.rate_limiter.check_connection(peer_addr.ip()) {
+                                    // NOTE: The connection is dropped before the HTTP/2 handshake so no tonic status can be returned.
+                                    eprintln!("Connection rate limit exceeded for {}.", peer_addr.ip());
+                                    continue;
+                                }
                                 match self.queue.try_send(ZingoIndexerRequest::new_from_grpc(stream)) {
                                     Ok(_) => {
                                         println!("[TEST] Requests in Queue: {}", self.queue.queue_length());
@@ -0,0 +1,519 @@
+//! Per-client rate limiting and concurrency caps.
+//!
+//! Clients are identified by their peer IP address, or by their API key where one has been attached to the request.
+
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, Mutex, RwLock},
+    time::Instant,
+};
+
+use crate::server::auth::ApiKey;
+
+/// Number of tracked clients after which idle token buckets are pruned.
+const PRUNE_THRESHOLD: usize = 4096;
+
+/// Token bucket parameters.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenBucketConfig {
+    /// Maximum number of tokens held by the bucket (burst size).
+    pub burst: u32,
+    /// Number of tokens added to the bucket each second.
+    pub per_second: f64,
+}
+
+/// Rate limiting configuration.
+///
+/// All limits are applied per client, limits that are not set are not enforced.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// Maximum number of RPCs a single client may have in flight at once.
+    pub max_in_flight: Option<u32>,
+    /// Limit on new connections accepted from a single client.
+    pub connections: Option<TokenBucketConfig>,
+    /// Limit applied to RPC methods that are not listed in `methods`.
+    pub default_method: Option<TokenBucketConfig>,
+    /// Per-method limits, keyed by gRPC method name (eg. "GetBlockRange").
+    #[serde(default)]
+    pub methods: HashMap<String, TokenBucketConfig>,
+}
+
+impl RateLimitConfig {
+    /// Returns the limit applied to the given RPC method.
+    fn method_limit(&self, method: &str) -> Option<&TokenBucketConfig> {
+        self.methods.get(method).or(self.default_method.as_ref())
+    }
+
+    /// Returns the config with the limits set in `overrides` layered over it, field by field.
+    ///
+    /// Per-method limits are merged by method name.
+    pub fn layered(&self, overrides: &RateLimitConfig) -> RateLimitConfig {
+        let mut methods = self.methods.clone();
+        methods.extend(
+            overrides
+                .methods
+                .iter()
+                .map(|(method, limit)| (method.clone(), *limit)),
+        );
+        RateLimitConfig {
+            max_in_flight: overrides.max_in_flight.or(self.max_in_flight),
+            connections: overrides.connections.or(self.connections),
+            default_method: overrides.default_method.or(self.default_method),
+            methods,
+        }
+    }
+}
+
+/// Identifies a client for rate limiting purposes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ClientId {
+    /// Client identified by peer IP address.
+    Ip(IpAddr),
+    /// Client identified by API key.
+    ApiKey(String),
+    /// Client that could not be identified.
+    Unknown,
+}
+
+impl ClientId {
+    /// Returns the ClientId of the sender of a gRPC request.
+    pub fn from_request<T>(request: &tonic::Request<T>) -> Self {
+        if let Some(key) = request.extensions().get::<ApiKey>() {
+            ClientId::ApiKey(key.name().to_string())
+        } else if let Some(addr) = request.remote_addr() {
+            ClientId::Ip(addr.ip())
+        } else {
+            ClientId::Unknown
+        }
+    }
+}
+
+/// Token bucket state.
+#[derive(Debug)]
+struct TokenBucket {
+    /// Bucket parameters.
+    limit: TokenBucketConfig,
+    /// Tokens currently held.
+    tokens: f64,
+    /// Time the bucket was last refilled.
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Returns a new full token bucket.
+    fn new(limit: TokenBucketConfig) -> Self {
+        TokenBucket {
+            limit,
+            tokens: limit.burst as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Refills the bucket and tries to take a token, returns false if the bucket is empty.
+    ///
+    /// If the bucket's parameters have changed the bucket is updated to use the new parameters.
+    fn try_take(&mut self, limit: &TokenBucketConfig) -> bool {
+        self.refill();
+        if self.limit != *limit {
+            self.limit = *limit;
+            self.tokens = self.tokens.min(limit.burst as f64);
+        }
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Adds the tokens accrued since the last refill.
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.limit.per_second).min(self.limit.burst as f64);
+        self.last_refill = now;
+    }
+
+    /// Returns true if the bucket has refilled completely.
+    fn is_full(&mut self) -> bool {
+        self.refill();
+        self.tokens >= self.limit.burst as f64
+    }
+}
+
+/// Applies token bucket limits to new connections and RPCs, and caps the number of RPCs in flight per client.
+#[derive(Debug)]
+pub struct RateLimiter {
+    /// Rate limiting configuration, may be replaced while the server is running.
+    config: RwLock<Arc<RateLimitConfig>>,
+    /// Connection token buckets, per client.
+    connection_buckets: Mutex<HashMap<IpAddr, TokenBucket>>,
+    /// RPC token buckets, per client and method.
+    method_buckets: Mutex<HashMap<(ClientId, String), TokenBucket>>,
+    /// Number of RPCs currently in flight, per client.
+    in_flight: Mutex<HashMap<ClientId, u32>>,
+}
+
+impl RateLimiter {
+    /// Creates a new RateLimiter.
+    pub fn new(config: RateLimitConfig) -> Self {
+        RateLimiter {
+            config: RwLock::new(Arc::new(config)),
+            connection_buckets: Mutex::new(HashMap::new()),
+            method_buckets: Mutex::new(HashMap::new()),
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the rate limiting configuration.
+    pub fn config(&self) -> Arc<RateLimitConfig> {
+        self.config
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Replaces the rate limiting configuration.
+    ///
+    /// Existing token buckets take the new limits on their next use, requests already in flight are unaffected.
+    pub fn set_config(&self, config: RateLimitConfig) {
+        *self.config.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(config);
+    }
+
+    /// Returns true if a new connection from the given peer is allowed.
+    pub(crate) fn check_connection(&self, ip: IpAddr) -> bool {
+        let config = self.config();
+        let Some(limit) = config.connections.as_ref() else {
+            return true;
+        };
+        let mut buckets = self
+            .connection_buckets
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if buckets.len() >= PRUNE_THRESHOLD {
+            buckets.retain(|_, bucket| !bucket.is_full());
+        }
+        buckets
+            .entry(ip)
+            .or_insert_with(|| TokenBucket::new(*limit))
+            .try_take(limit)
+    }
+
+    /// Checks the rate limits for an incoming RPC.
+    ///
+    /// Requests authenticated with an API key are checked against the key's rate limits layered over the server's,
+    /// see [`RateLimitConfig::layered`]. API key allow-lists are checked by [`crate::rpc::GrpcClient::admit`].
+    ///
+    /// Returns a permit that must be held for as long as the RPC is being serviced (including any response stream), or
+    /// `RESOURCE_EXHAUSTED` if the client is over its limits.
+    pub fn acquire<T>(
+        self: &Arc<Self>,
+        request: &tonic::Request<T>,
+        method: &str,
+    ) -> Result<RpcPermit, tonic::Status> {
+        let api_key = request.extensions().get::<ApiKey>();
+        let server_config = self.config();
+        let key_config;
+        let config = match api_key.and_then(|key| key.rate_limit()) {
+            Some(key_limits) => {
+                key_config = server_config.layered(key_limits);
+                &key_config
+            }
+            None => &server_config,
+        };
+        let client = ClientId::from_request(request);
+        // Checked before taking a method token so that requests rejected for being over the in flight cap do not
+        // drain the client's bucket, the permit releases its slot if the method limit is then exceeded.
+        let permit = self.check_in_flight(config, client)?;
+        self.check_method(config, permit.client(), method)?;
+        Ok(permit)
+    }
+
+    /// Takes a token from the client's bucket for the given method.
+    fn check_method(
+        &self,
+        config: &RateLimitConfig,
+        client: &ClientId,
+        method: &str,
+    ) -> Result<(), tonic::Status> {
+        let Some(limit) = config.method_limit(method) else {
+            return Ok(());
+        };
+        let mut buckets = self
+            .method_buckets
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if buckets.len() >= PRUNE_THRESHOLD {
+            buckets.retain(|_, bucket| !bucket.is_full());
+        }
+        let allowed = buckets
+            .entry((client.clone(), method.to_string()))
+            .or_insert_with(|| TokenBucket::new(*limit))
+            .try_take(limit);
+        if allowed {
+            Ok(())
+        } else {
+            Err(tonic::Status::resource_exhausted(format!(
+                "Error: Rate limit exceeded for {}.",
+                method
+            )))
+        }
+    }
+
+    /// Registers a new RPC in flight for the client.
+    fn check_in_flight(
+        self: &Arc<Self>,
+        config: &RateLimitConfig,
+        client: ClientId,
+    ) -> Result<RpcPermit, tonic::Status> {
+        let Some(max_in_flight) = config.max_in_flight else {
+            return Ok(RpcPermit {
+                limiter: None,
+                client,
+            });
+        };
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        let count = in_flight.entry(client.clone()).or_insert(0);
+        if *count >= max_in_flight {
+            return Err(tonic::Status::resource_exhausted(format!(
+                "Error: Too many requests in flight. Maximum allowed: {}.",
+                max_in_flight
+            )));
+        }
+        *count += 1;
+        Ok(RpcPermit {
+            limiter: Some(self.clone()),
+            client,
+        })
+    }
+
+    /// Releases an RPC in flight for the client.
+    fn release(&self, client: &ClientId) {
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(count) = in_flight.get_mut(client) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                in_flight.remove(client);
+            }
+        }
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(RateLimitConfig::default())
+    }
+}
+
+/// Held for the duration of an RPC, releases the client's in flight slot when dropped.
+#[derive(Debug)]
+pub struct RpcPermit {
+    limiter: Option<Arc<RateLimiter>>,
+    client: ClientId,
+}
+
+impl RpcPermit {
+    /// Returns the client the permit was issued to.
+    pub fn client(&self) -> &ClientId {
+        &self.client
+    }
+}
+
+impl Drop for RpcPermit {
+    fn drop(&mut self) {
+        if let Some(limiter) = self.limiter.take() {
+            limiter.release(&self.client);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn bucket(burst: u32, per_second: f64) -> TokenBucketConfig {
+        TokenBucketConfig { burst, per_second }
+    }
+
+    fn acquire(limiter: &Arc<RateLimiter>, method: &str) -> Result<RpcPermit, tonic::Status> {
+        limiter.acquire(&tonic::Request::new(()), method)
+    }
+
+    #[test]
+    fn token_bucket_refill() {
+        let limit = bucket(2, 1.0);
+        let mut bucket = TokenBucket::new(limit);
+        assert!(bucket.try_take(&limit));
+        assert!(bucket.try_take(&limit));
+        assert!(!bucket.try_take(&limit));
+
+        bucket.last_refill -= Duration::from_secs(1);
+        assert!(bucket.try_take(&limit));
+        assert!(!bucket.try_take(&limit));
+
+        bucket.last_refill -= Duration::from_secs(10);
+        assert!(bucket.is_full());
+        assert_eq!(bucket.tokens, 2.0);
+    }
+
+    #[test]
+    fn token_bucket_takes_new_limit() {
+        let mut bucket = TokenBucket::new(bucket(4, 1.0));
+        let limit = TokenBucketConfig {
+            burst: 1,
+            per_second: 1.0,
+        };
+        assert!(bucket.try_take(&limit));
+        assert!(!bucket.try_take(&limit));
+    }
+
+    #[test]
+    fn per_method_buckets() {
+        let limiter = Arc::new(RateLimiter::new(RateLimitConfig {
+            default_method: Some(bucket(2, 0.0)),
+            methods: HashMap::from([("GetBlock".to_string(), bucket(1, 0.0))]),
+            ..Default::default()
+        }));
+        assert!(acquire(&limiter, "GetBlock").is_ok());
+        let status = acquire(&limiter, "GetBlock").unwrap_err();
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+
+        assert!(acquire(&limiter, "GetTransaction").is_ok());
+        assert!(acquire(&limiter, "GetTransaction").is_ok());
+        assert!(acquire(&limiter, "GetTransaction").is_err());
+        assert!(acquire(&limiter, "GetTreeState").is_ok());
+    }
+
+    #[test]
+    fn per_client_buckets() {
+        let limiter = RateLimiter::new(RateLimitConfig::default());
+        let config = RateLimitConfig {
+            default_method: Some(bucket(1, 0.0)),
+            ..Default::default()
+        };
+        let alice = ClientId::ApiKey("alice".to_string());
+        let bob = ClientId::ApiKey("bob".to_string());
+        assert!(limiter.check_method(&config, &alice, "GetBlock").is_ok());
+        assert!(limiter.check_method(&config, &alice, "GetBlock").is_err());
+        assert!(limiter.check_method(&config, &bob, "GetBlock").is_ok());
+    }
+
+    #[test]
+    fn in_flight_cap_and_release() {
+        let limiter = Arc::new(RateLimiter::new(RateLimitConfig {
+            max_in_flight: Some(2),
+            ..Default::default()
+        }));
+        let first = acquire(&limiter, "GetBlock").unwrap();
+        let _second = acquire(&limiter, "GetBlock").unwrap();
+        let status = acquire(&limiter, "GetBlock").unwrap_err();
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+
+        drop(first);
+        assert!(acquire(&limiter, "GetBlock").is_ok());
+    }
+
+    #[test]
+    fn permits_released_on_drop() {
+        let limiter = Arc::new(RateLimiter::new(RateLimitConfig {
+            max_in_flight: Some(1),
+            ..Default::default()
+        }));
+        let permit = acquire(&limiter, "GetBlock").unwrap();
+        assert_eq!(permit.client(), &ClientId::Unknown);
+        assert_eq!(
+            limiter.in_flight.lock().unwrap().get(&ClientId::Unknown),
+            Some(&1)
+        );
+        drop(permit);
+        assert!(limiter.in_flight.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn key_limits_layered_over_server_limits() {
+        let limiter = Arc::new(RateLimiter::new(RateLimitConfig {
+            max_in_flight: Some(1),
+            default_method: Some(bucket(1, 0.0)),
+            ..Default::default()
+        }));
+        let key = ApiKey(Arc::new(crate::server::auth::ApiKeyConfig {
+            name: "wallet".to_string(),
+            token: "secret".to_string(),
+            allowed_rpcs: None,
+            rate_limit: Some(RateLimitConfig {
+                methods: HashMap::from([("GetBlock".to_string(), bucket(2, 0.0))]),
+                ..Default::default()
+            }),
+        }));
+        let keyed_request = || {
+            let mut request = tonic::Request::new(());
+            request.extensions_mut().insert(key.clone());
+            request
+        };
+
+        let permit = limiter.acquire(&keyed_request(), "GetBlock").unwrap();
+        // The server's in flight cap still applies to the key.
+        assert!(limiter.acquire(&keyed_request(), "GetBlock").is_err());
+        drop(permit);
+        drop(limiter.acquire(&keyed_request(), "GetBlock").unwrap());
+        assert!(limiter.acquire(&keyed_request(), "GetBlock").is_err());
+
+        // Methods the key does not limit take the server's default limit.
+        drop(limiter.acquire(&keyed_request(), "GetTransaction").unwrap());
+        assert!(limiter.acquire(&keyed_request(), "GetTransaction").is_err());
+    }
+
+    #[test]
+    fn layered_config() {
+        let server = RateLimitConfig {
+            max_in_flight: Some(8),
+            connections: Some(bucket(4, 1.0)),
+            default_method: Some(bucket(10, 1.0)),
+            methods: HashMap::from([
+                ("GetBlock".to_string(), bucket(5, 1.0)),
+                ("GetBlockRange".to_string(), bucket(1, 1.0)),
+            ]),
+        };
+        let key = RateLimitConfig {
+            max_in_flight: Some(2),
+            methods: HashMap::from([("GetBlock".to_string(), bucket(50, 10.0))]),
+            ..Default::default()
+        };
+        let layered = server.layered(&key);
+        assert_eq!(layered.max_in_flight, Some(2));
+        assert_eq!(layered.connections, server.connections);
+        assert_eq!(layered.default_method, server.default_method);
+        assert_eq!(layered.methods["GetBlock"], bucket(50, 10.0));
+        assert_eq!(layered.methods["GetBlockRange"], bucket(1, 1.0));
+        assert_eq!(server.layered(&RateLimitConfig::default()), server);
+    }
+
+    #[test]
+    fn in_flight_rejection_keeps_method_token() {
+        let limiter = Arc::new(RateLimiter::new(RateLimitConfig {
+            max_in_flight: Some(1),
+            default_method: Some(bucket(2, 0.0)),
+            ..Default::default()
+        }));
+        let permit = acquire(&limiter, "GetBlock").unwrap();
+        assert!(acquire(&limiter, "GetBlock").is_err());
+        drop(permit);
+        assert!(acquire(&limiter, "GetBlock").is_ok());
+    }
+
+    #[test]
+    fn method_rejection_releases_in_flight_slot() {
+        let limiter = Arc::new(RateLimiter::new(RateLimitConfig {
+            max_in_flight: Some(1),
+            default_method: Some(bucket(1, 0.0)),
+            ..Default::default()
+        }));
+        drop(acquire(&limiter, "GetBlock").unwrap());
+        assert!(acquire(&limiter, "GetBlock").is_err());
+        assert!(limiter.in_flight.lock().unwrap().is_empty());
+    }
+}
@@ -1,25 +1,31 @@
 //! Holds the server worker implementation.
 
 use std::sync::{
-    atomic::{AtomicBool, AtomicUsize, Ordering},
-    Arc,
+    atomic::{AtomicBool, AtomicU16, AtomicUsize, Ordering},
+    Arc, RwLock,
 };
 
-use http::Uri;
 use tonic::transport::Server;
+use zaino_fetch::{chain::block::BlockOptions, jsonrpc::backend::BackendManager};
+use zebra_chain::parameters::Network;
 
 use crate::{
     rpc::GrpcClient,
     server::{
+        auth::ApiKeyInterceptor,
         error::WorkerError,
+        limiter::RateLimiter,
         queue::{QueueReceiver, QueueSender},
         request::ZingoIndexerRequest,
-        AtomicStatus,
+        AtomicStatus, CancelSignal, StatusType,
     },
 };
 
 use zaino_proto::proto::service::compact_tx_streamer_server::CompactTxStreamerServer;
 
+/// Time given to workers to return after their in-flight streams have been cancelled, before they are aborted.
+const STREAM_CANCEL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
 /// A queue working is the entity that takes requests from the queue and processes them.
 ///
 /// TODO: - Add JsonRpcConnector to worker and pass to underlying RPC services.
@@ -34,6 +40,8 @@ pub(crate) struct Worker {
     requeue: QueueSender<ZingoIndexerRequest>,
     /// gRPC client used for processing requests received over http.
     grpc_client: GrpcClient,
+    /// Authenticates requests before they are passed to the gRPC client.
+    interceptor: ApiKeyInterceptor,
     /// Thread safe worker status.
     atomic_status: AtomicStatus,
     /// Represents the Online status of the Worker.
@@ -47,19 +55,31 @@ impl Worker {
         _worker_id: usize,
         queue: QueueReceiver<ZingoIndexerRequest>,
         requeue: QueueSender<ZingoIndexerRequest>,
-        zebrad_uri: Uri,
+        backends: BackendManager,
+        network: Network,
+        block_options: BlockOptions,
+        rate_limiter: Arc<RateLimiter>,
+        interceptor: ApiKeyInterceptor,
+        cancel: CancelSignal,
+        server_status: AtomicStatus,
         atomic_status: AtomicStatus,
         online: Arc<AtomicBool>,
     ) -> Self {
         let grpc_client = GrpcClient {
-            zebrad_rpc_uri: zebrad_uri,
+            backends,
+            network,
+            block_options,
             online: online.clone(),
+            rate_limiter,
+            cancel,
+            server_status,
         };
         Worker {
             _worker_id,
             queue,
             requeue,
             grpc_client,
+            interceptor,
             atomic_status,
             online,
         }
@@ -72,7 +92,10 @@ impl Worker {
         tokio::task::spawn(async move {
             // NOTE: This interval may need to be reduced or removed / moved once scale testing begins.
             let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(100));
-            let svc = CompactTxStreamerServer::new(self.grpc_client.clone());
+            let svc = CompactTxStreamerServer::with_interceptor(
+                self.grpc_client.clone(),
+                self.interceptor.clone(),
+            );
             // TODO: create tonic server here for use within loop.
             self.atomic_status.store(1);
             loop {
@@ -88,12 +111,14 @@ impl Worker {
                                 self.atomic_status.store(2);
                                     match request {
                                         ZingoIndexerRequest::TcpServerRequest(request) => {
+                                            // On shutdown the connection stops accepting new requests and in-flight requests are allowed to complete.
                                             Server::builder().add_service(svc.clone())
-                                                .serve_with_incoming( async_stream::stream! {
+                                                .serve_with_incoming_shutdown( async_stream::stream! {
                                                     yield Ok::<_, std::io::Error>(
                                                         request.get_request().get_stream()
                                                     );
-                                                }
+                                                },
+                                                self.wait_for_shutdown()
                                             )
                                             .await?;
                                         }
@@ -133,6 +158,14 @@ impl Worker {
         false
     }
 
+    /// Waits until the worker receives a closure signal.
+    async fn wait_for_shutdown(&self) {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(50));
+        while !self.check_for_shutdown().await {
+            interval.tick().await;
+        }
+    }
+
     /// Sets the worker to close gracefully.
     pub(crate) async fn shutdown(&mut self) {
         self.atomic_status.store(4)
@@ -158,7 +191,7 @@ impl Worker {
 #[derive(Debug, Clone)]
 pub struct WorkerPoolStatus {
     workers: Arc<AtomicUsize>,
-    statuses: Vec<AtomicStatus>,
+    statuses: Arc<RwLock<Vec<AtomicStatus>>>,
 }
 
 impl WorkerPoolStatus {
@@ -166,31 +199,106 @@ impl WorkerPoolStatus {
     pub(crate) fn new(max_workers: u16) -> Self {
         WorkerPoolStatus {
             workers: Arc::new(AtomicUsize::new(0)),
-            statuses: vec![AtomicStatus::new(5); max_workers as usize],
+            statuses: Arc::new(RwLock::new(vec![
+                AtomicStatus::new(5);
+                max_workers as usize
+            ])),
         }
     }
 
     /// Returns the WorkerPoolStatus.
     pub(crate) fn load(&self) -> WorkerPoolStatus {
         self.workers.load(Ordering::SeqCst);
-        for i in 0..self.statuses.len() {
-            self.statuses[i].load();
+        for status in self
+            .statuses
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+        {
+            status.load();
         }
         self.clone()
     }
+
+    /// Returns the status of the worker at the given index, adding new statuses if the pool has grown.
+    pub(crate) fn worker_status(&self, index: usize) -> AtomicStatus {
+        let mut statuses = self.statuses.write().unwrap_or_else(|e| e.into_inner());
+        while statuses.len() <= index {
+            statuses.push(AtomicStatus::new(5));
+        }
+        statuses[index].clone()
+    }
+
+    /// Returns the number of workers currently in the pool.
+    pub fn workers(&self) -> usize {
+        self.workers.load(Ordering::SeqCst)
+    }
+
+    /// Returns the statuses of the workers currently in the pool.
+    pub fn statuses(&self) -> Vec<StatusType> {
+        self.statuses
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .take(self.workers())
+            .map(|status| status.load().into())
+            .collect()
+    }
+}
+
+/// Thread safe size bounds of the worker pool, may be changed while the server is running.
+#[derive(Debug, Clone)]
+pub struct WorkerPoolBounds {
+    max_size: Arc<AtomicU16>,
+    idle_size: Arc<AtomicU16>,
+}
+
+impl WorkerPoolBounds {
+    /// Creates a new WorkerPoolBounds.
+    pub fn new(max_size: u16, idle_size: u16) -> Self {
+        WorkerPoolBounds {
+            max_size: Arc::new(AtomicU16::new(max_size)),
+            idle_size: Arc::new(AtomicU16::new(idle_size)),
+        }
+    }
+
+    /// Returns the maximum number of workers allowed in the pool.
+    pub fn max_size(&self) -> u16 {
+        self.max_size.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of workers kept running on standby.
+    pub fn idle_size(&self) -> u16 {
+        self.idle_size.load(Ordering::SeqCst)
+    }
+
+    /// Sets new bounds for the worker pool.
+    ///
+    /// The server adds or removes workers to bring the pool within the new bounds.
+    pub fn set(&self, max_size: u16, idle_size: u16) -> Result<(), WorkerError> {
+        if idle_size == 0 || idle_size > max_size {
+            return Err(WorkerError::InvalidPoolBounds(format!(
+                "idle size ({}) must be at least 1 and no greater than max size ({}).",
+                idle_size, max_size
+            )));
+        }
+        self.max_size.store(max_size, Ordering::SeqCst);
+        self.idle_size.store(idle_size, Ordering::SeqCst);
+        Ok(())
+    }
 }
 
 /// Dynamically sized pool of workers.
 #[derive(Debug, Clone)]
 pub(crate) struct WorkerPool {
-    /// Maximun number of concurrent workers allowed.
-    max_size: u16,
-    /// Minimum number of workers kept running on stanby.
-    idle_size: u16,
+    /// Maximun number of concurrent workers allowed and minimum number of workers kept running on stanby.
+    bounds: WorkerPoolBounds,
     /// Workers currently in the pool
     workers: Vec<Worker>,
     /// Status of the workerpool and its workers.
     status: WorkerPoolStatus,
+    /// Cancels the workers' in-flight response streams.
+    cancel: CancelSignal,
     /// Represents the Online status of the WorkerPool.
     pub online: Arc<AtomicBool>,
 }
@@ -199,23 +307,35 @@ impl WorkerPool {
     /// Creates a new worker pool containing [idle_workers] workers.
     #[allow(clippy::too_many_arguments)]
     pub(crate) async fn spawn(
-        max_size: u16,
-        idle_size: u16,
+        bounds: WorkerPoolBounds,
         queue: QueueReceiver<ZingoIndexerRequest>,
         _requeue: QueueSender<ZingoIndexerRequest>,
-        zebrad_uri: Uri,
+        backends: BackendManager,
+        network: Network,
+        block_options: BlockOptions,
+        rate_limiter: Arc<RateLimiter>,
+        interceptor: ApiKeyInterceptor,
+        server_status: AtomicStatus,
         status: WorkerPoolStatus,
         online: Arc<AtomicBool>,
     ) -> Self {
-        let mut workers: Vec<Worker> = Vec::with_capacity(max_size as usize);
+        let cancel = CancelSignal::default();
+        let idle_size = bounds.idle_size();
+        let mut workers: Vec<Worker> = Vec::with_capacity(bounds.max_size() as usize);
         for _ in 0..idle_size {
             workers.push(
                 Worker::spawn(
                     workers.len(),
                     queue.clone(),
                     _requeue.clone(),
-                    zebrad_uri.clone(),
-                    status.statuses[workers.len()].clone(),
+                    backends.clone(),
+                    network.clone(),
+                    block_options,
+                    rate_limiter.clone(),
+                    interceptor.clone(),
+                    cancel.clone(),
+                    server_status.clone(),
+                    status.worker_status(workers.len()),
                     online.clone(),
                 )
                 .await,
@@ -223,10 +343,10 @@ impl WorkerPool {
         }
         status.workers.store(idle_size as usize, Ordering::SeqCst);
         WorkerPool {
-            max_size,
-            idle_size,
+            bounds,
             workers,
             status,
+            cancel,
             online,
         }
     }
@@ -244,7 +364,7 @@ impl WorkerPool {
     pub(crate) async fn push_worker(
         &mut self,
     ) -> Result<tokio::task::JoinHandle<Result<(), WorkerError>>, WorkerError> {
-        if self.workers.len() >= self.max_size as usize {
+        if self.workers.len() >= self.max_size() as usize {
             Err(WorkerError::WorkerPoolFull)
         } else {
             let worker_index = self.workers();
@@ -253,8 +373,14 @@ impl WorkerPool {
                     worker_index,
                     self.workers[0].queue.clone(),
                     self.workers[0].requeue.clone(),
-                    self.workers[0].grpc_client.zebrad_rpc_uri.clone(),
-                    self.status.statuses[worker_index].clone(),
+                    self.workers[0].grpc_client.backends.clone(),
+                    self.workers[0].grpc_client.network.clone(),
+                    self.workers[0].grpc_client.block_options,
+                    self.workers[0].grpc_client.rate_limiter.clone(),
+                    self.workers[0].interceptor.clone(),
+                    self.cancel.clone(),
+                    self.workers[0].grpc_client.server_status.clone(),
+                    self.status.worker_status(worker_index),
                     self.online.clone(),
                 )
                 .await,
@@ -269,7 +395,7 @@ impl WorkerPool {
         &mut self,
         worker_handle: tokio::task::JoinHandle<Result<(), WorkerError>>,
     ) -> Result<(), WorkerError> {
-        if self.workers.len() <= self.idle_size as usize {
+        if self.workers.len() <= self.idle_size() as usize {
             Err(WorkerError::WorkerPoolIdle)
         } else {
             let worker_index = self.workers.len() - 1;
@@ -277,13 +403,13 @@ impl WorkerPool {
             match worker_handle.await {
                 Ok(worker) => match worker {
                     Ok(()) => {
-                        self.status.statuses[worker_index].store(5);
+                        self.status.worker_status(worker_index).store(5);
                         self.workers.pop();
                         self.status.workers.fetch_sub(1, Ordering::SeqCst);
                         Ok(())
                     }
                     Err(e) => {
-                        self.status.statuses[worker_index].store(6);
+                        self.status.worker_status(worker_index).store(6);
                         eprintln!("Worker returned error on shutdown: {}", e);
                         // TODO: Handle the inner WorkerError. Return error.
                         self.status.workers.fetch_sub(1, Ordering::SeqCst);
@@ -291,7 +417,7 @@ impl WorkerPool {
                     }
                 },
                 Err(e) => {
-                    self.status.statuses[worker_index].store(6);
+                    self.status.worker_status(worker_index).store(6);
                     eprintln!("Worker returned error on shutdown: {}", e);
                     // TODO: Handle the JoinError. Return error.
                     self.status.workers.fetch_sub(1, Ordering::SeqCst);
@@ -303,12 +429,17 @@ impl WorkerPool {
 
     /// Returns the max size of the pool
     pub(crate) fn max_size(&self) -> u16 {
-        self.max_size
+        self.bounds.max_size()
     }
 
     /// Returns the idle size of the pool
     pub(crate) fn idle_size(&self) -> u16 {
-        self.idle_size
+        self.bounds.idle_size()
+    }
+
+    /// Returns the pool's size bounds.
+    pub(crate) fn bounds(&self) -> WorkerPoolBounds {
+        self.bounds.clone()
     }
 
     /// Returns the current number of workers in the pool.
@@ -318,37 +449,61 @@ impl WorkerPool {
 
     /// Fetches and returns the status of the workerpool and its workers.
     pub(crate) fn status(&self) -> WorkerPoolStatus {
-        self.status.workers.load(Ordering::SeqCst);
-        for i in 0..self.workers() {
-            self.status.statuses[i].load();
-        }
-        self.status.clone()
+        self.status.load()
     }
 
     /// Shuts down all the workers in the pool.
+    ///
+    /// Workers stop accepting new requests and are given [grace_period] to complete their in-flight requests,
+    /// after which in-flight response streams are cancelled with `UNAVAILABLE`. Workers that have still not returned
+    /// after a further [STREAM_CANCEL_TIMEOUT] are aborted.
     pub(crate) async fn shutdown(
         &mut self,
         worker_handles: &mut [Option<tokio::task::JoinHandle<Result<(), WorkerError>>>],
+        grace_period: std::time::Duration,
     ) {
+        for worker in self.workers.iter_mut() {
+            worker.shutdown().await;
+        }
+        let deadline = tokio::time::Instant::now() + grace_period;
         for i in (0..self.workers.len()).rev() {
-            self.workers[i].shutdown().await;
-            if let Some(worker_handle) = worker_handles[i].take() {
-                match worker_handle.await {
+            if let Some(mut worker_handle) = worker_handles[i].take() {
+                let result = match tokio::time::timeout_at(deadline, &mut worker_handle).await {
+                    Ok(result) => result,
+                    Err(_) => {
+                        if !self.cancel.is_cancelled() {
+                            eprintln!(
+                                "Shutdown grace period elapsed, cancelling in-flight streams."
+                            );
+                            self.cancel.cancel();
+                        }
+                        match tokio::time::timeout(STREAM_CANCEL_TIMEOUT, &mut worker_handle).await
+                        {
+                            Ok(result) => result,
+                            Err(_) => {
+                                eprintln!("Worker failed to shut down, aborting.");
+                                worker_handle.abort();
+                                worker_handle.await
+                            }
+                        }
+                    }
+                };
+                match result {
                     Ok(worker) => match worker {
                         Ok(()) => {
-                            self.status.statuses[i].store(5);
+                            self.status.worker_status(i).store(5);
                             self.workers.pop();
                             self.status.workers.fetch_sub(1, Ordering::SeqCst);
                         }
                         Err(e) => {
-                            self.status.statuses[i].store(6);
+                            self.status.worker_status(i).store(6);
                             eprintln!("Worker returned error on shutdown: {}", e);
                             // TODO: Handle the inner WorkerError
                             self.status.workers.fetch_sub(1, Ordering::SeqCst);
                         }
                     },
                     Err(e) => {
-                        self.status.statuses[i].store(6);
+                        self.status.worker_status(i).store(6);
                         eprintln!("Worker returned error on shutdown: {}", e);
                         // TODO: Handle the JoinError
                         self.status.workers.fetch_sub(1, Ordering::SeqCst);
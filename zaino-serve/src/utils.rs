@@ -1,7 +1,8 @@
 //! Utility functions for Zingo-RPC.
 
 /// Zingo-Indexer build info.
-pub(crate) struct BuildInfo {
+#[derive(Debug, Clone)]
+pub struct BuildInfo {
     /// Git commit hash.
     pub commit_hash: String,
     /// Git Branch.
@@ -15,7 +16,7 @@ pub(crate) struct BuildInfo {
 }
 
 /// Returns build info for Zingo-Indexer.
-pub(crate) fn get_build_info() -> BuildInfo {
+pub fn get_build_info() -> BuildInfo {
     BuildInfo {
         commit_hash: env!("GIT_COMMIT").to_string(),
         branch: env!("BRANCH").to_string(),
@@ -24,3 +25,13 @@ pub(crate) fn get_build_info() -> BuildInfo {
         version: env!("VERSION").to_string(),
     }
 }
+
+impl std::fmt::Display for BuildInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Version: {}", self.version)?;
+        writeln!(f, "Commit: {}", self.commit_hash)?;
+        writeln!(f, "Branch: {}", self.branch)?;
+        writeln!(f, "Build date: {}", self.build_date)?;
+        write!(f, "Build user: {}", self.build_user)
+    }
+}
@@ -339,11 +339,21 @@ impl TestManager {
                 tcp_active: true,
                 listen_port: Some(zaino_grpc_listen_port),
                 zebrad_port: zebrad_rpc_listen_port,
+                network: zainodlib::config::NetworkConfig::Regtest,
                 node_user: Some("xxxxxx".to_string()),
                 node_password: Some("xxxxxx".to_string()),
+                broadcast_transactions: false,
                 max_queue_size: 512,
                 max_worker_pool_size: 64,
                 idle_worker_pool_size: 4,
+                shutdown_grace_period: 5,
+                log_level: Default::default(),
+                regtest: Default::default(),
+                rate_limit: Default::default(),
+                auth: Default::default(),
+                admin: Default::default(),
+                backends: Vec::new(),
+                blocks: Default::default(),
             };
             let handle = zainodlib::indexer::Indexer::new(indexer_config, online.clone())
                 .await
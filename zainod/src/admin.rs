@@ -0,0 +1,610 @@
+//! Authenticated admin API for runtime control of the indexer.
+//!
+//! Serves a minimal HTTP/1.1 JSON API on localhost, every request must carry an `authorization: Bearer <token>` header.
+//!
+//! Endpoints:
+//! - GET  /status: Returns the status of the indexer and its components.
+//! - POST /shutdown: Shuts the indexer down gracefully.
+//! - GET  /worker-pool: Returns the worker pool bounds.
+//! - POST /worker-pool: Sets the worker pool bounds, takes `{"max_size": u16, "idle_size": u16}`. Applied by the server
+//!   in the same way as a config reload.
+//! - GET  /log-level: Returns the log level.
+//! - POST /log-level: Sets the log level, takes `{"level": "error" | "warn" | "info" | "debug"}`.
+//!
+//! NOTE: Block cache endpoints (flush / rebuild) will be added once the block cache has been added to the indexer.
+
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+use zaino_serve::{
+    logging::{log_level, set_log_level, LogLevel},
+    server::{
+        auth::constant_time_eq,
+        director::{ConfigUpdateRequest, ServerConfigUpdate, WorkerPoolBounds},
+        StatusType,
+    },
+};
+
+use crate::{error::IndexerError, indexer::IndexerStatus};
+
+/// Maximum size of an admin request header.
+const MAX_HEADER_SIZE: usize = 8192;
+
+/// Maximum size of an admin request body.
+const MAX_BODY_SIZE: usize = 8192;
+
+/// Time allowed for a client to send a complete request.
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Handles used by the admin API to inspect and control the indexer.
+#[derive(Debug, Clone)]
+pub struct AdminHandle {
+    /// Status of the indexer and its components.
+    status: IndexerStatus,
+    /// Worker pool size bounds.
+    worker_pool_bounds: WorkerPoolBounds,
+    /// Sends config updates to the server, worker pool resizes are applied by the server as on config reload.
+    config_update_tx: tokio::sync::mpsc::Sender<ConfigUpdateRequest>,
+}
+
+impl AdminHandle {
+    /// Creates a new AdminHandle.
+    pub fn new(
+        status: IndexerStatus,
+        worker_pool_bounds: WorkerPoolBounds,
+        config_update_tx: tokio::sync::mpsc::Sender<ConfigUpdateRequest>,
+    ) -> Self {
+        AdminHandle {
+            status,
+            worker_pool_bounds,
+            config_update_tx,
+        }
+    }
+
+    /// Sends new worker pool bounds to the server and waits for the server to apply them.
+    async fn resize_worker_pool(&self, max_size: u16, idle_size: u16) -> Result<(), AdminResponse> {
+        let (ack_tx, ack_rx) = tokio::sync::oneshot::channel();
+        let request = ConfigUpdateRequest {
+            update: ServerConfigUpdate {
+                max_worker_pool_size: max_size,
+                idle_worker_pool_size: idle_size,
+                rate_limit: None,
+            },
+            ack: ack_tx,
+        };
+        if self.config_update_tx.send(request).await.is_err() {
+            return Err(AdminResponse::error(500, "Server is not running."));
+        }
+        match ack_rx.await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(e)) => Err(AdminResponse::error(400, e)),
+            Err(_) => Err(AdminResponse::error(500, "Server is not running.")),
+        }
+    }
+}
+
+/// Admin API server.
+pub struct AdminServer {
+    /// Tcp Listener.
+    listener: TcpListener,
+    /// Bearer token required to access the API.
+    token: Arc<String>,
+    /// Handles used to inspect and control the indexer.
+    handle: AdminHandle,
+    /// Online status of the indexer.
+    online: Arc<AtomicBool>,
+}
+
+impl AdminServer {
+    /// Binds the admin API to the given address.
+    pub async fn spawn(
+        listen_addr: SocketAddr,
+        token: String,
+        handle: AdminHandle,
+        online: Arc<AtomicBool>,
+    ) -> Result<Self, IndexerError> {
+        let listener = TcpListener::bind(listen_addr).await.map_err(|e| {
+            IndexerError::AdminError(format!("Failed to bind to {}: {}", listen_addr, e))
+        })?;
+        println!("Admin API listening at: {}.", listen_addr);
+        Ok(AdminServer {
+            listener,
+            token: Arc::new(token),
+            handle,
+            online,
+        })
+    }
+
+    /// Starts the admin API service and returns its JoinHandle.
+    pub async fn serve(self) -> tokio::task::JoinHandle<Result<(), IndexerError>> {
+        tokio::task::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(50));
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {
+                        if self.check_for_shutdown() {
+                            return Ok(());
+                        }
+                    }
+                    incoming = self.listener.accept() => {
+                        match incoming {
+                            Ok((stream, _)) => {
+                                let token = self.token.clone();
+                                let handle = self.handle.clone();
+                                tokio::task::spawn(async move {
+                                    if let Err(e) = handle_connection(stream, &token, &handle).await {
+                                        eprintln!("Admin API connection error: {}", e);
+                                    }
+                                });
+                            }
+                            Err(e) => {
+                                eprintln!("Failed to accept admin API connection: {}", e);
+                            }
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Checks the indexers online status and status for closure signal.
+    fn check_for_shutdown(&self) -> bool {
+        self.handle.status.indexer_status() as usize >= 4 || !self.online.load(Ordering::SeqCst)
+    }
+}
+
+/// Parsed admin API request.
+struct AdminRequest {
+    method: String,
+    path: String,
+    authorization: Option<String>,
+    body: Vec<u8>,
+}
+
+/// Admin API response.
+struct AdminResponse {
+    code: u16,
+    body: serde_json::Value,
+}
+
+impl AdminResponse {
+    fn ok(body: serde_json::Value) -> Self {
+        AdminResponse { code: 200, body }
+    }
+
+    fn error(code: u16, message: impl std::fmt::Display) -> Self {
+        AdminResponse {
+            code,
+            body: serde_json::json!({ "error": message.to_string() }),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.code {
+            200 => "OK",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            404 => "Not Found",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// Reads a single request from the stream, routes it and writes the response.
+async fn handle_connection(
+    mut stream: TcpStream,
+    token: &str,
+    handle: &AdminHandle,
+) -> std::io::Result<()> {
+    let response = match tokio::time::timeout(REQUEST_TIMEOUT, read_request(&mut stream)).await {
+        Ok(Ok(request)) => respond(request, token, handle).await,
+        Ok(Err(e)) => AdminResponse::error(400, e),
+        Err(_) => AdminResponse::error(400, "Request timed out."),
+    };
+    let body = response.body.to_string();
+    let message = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.code,
+        response.reason(),
+        body.len(),
+        body
+    );
+    stream.write_all(message.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Reads and parses a HTTP/1.1 request.
+async fn read_request<R: AsyncRead + Unpin>(stream: &mut R) -> Result<AdminRequest, String> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 1024];
+    let header_end = loop {
+        if let Some(position) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break position + 4;
+        }
+        if buffer.len() > MAX_HEADER_SIZE {
+            return Err("Request header too large.".to_string());
+        }
+        let read = stream.read(&mut chunk).await.map_err(|e| e.to_string())?;
+        if read == 0 {
+            return Err("Connection closed before request was received.".to_string());
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+    let header = std::str::from_utf8(&buffer[..header_end])
+        .map_err(|_| "Request header is not valid UTF-8.".to_string())?;
+    let mut lines = header.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let (Some(method), Some(path)) = (request_line.next(), request_line.next()) else {
+        return Err("Malformed request line.".to_string());
+    };
+    let mut content_length = 0;
+    let mut authorization = None;
+    for line in lines {
+        if let Some((name, value)) = line.split_once(':') {
+            match name.trim().to_ascii_lowercase().as_str() {
+                "content-length" => {
+                    content_length = value
+                        .trim()
+                        .parse::<usize>()
+                        .map_err(|_| "Invalid Content-Length.".to_string())?;
+                }
+                "authorization" => authorization = Some(value.trim().to_string()),
+                _ => {}
+            }
+        }
+    }
+    if content_length > MAX_BODY_SIZE {
+        return Err("Request body too large.".to_string());
+    }
+    let mut body = buffer[header_end..].to_vec();
+    while body.len() < content_length {
+        let read = stream.read(&mut chunk).await.map_err(|e| e.to_string())?;
+        if read == 0 {
+            return Err("Connection closed before request body was received.".to_string());
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+    body.truncate(content_length);
+    Ok(AdminRequest {
+        method: method.to_string(),
+        path: path.to_string(),
+        authorization,
+        body,
+    })
+}
+
+/// Routes an authorised request, rejecting requests that do not carry the admin bearer token.
+async fn respond(request: AdminRequest, token: &str, handle: &AdminHandle) -> AdminResponse {
+    if authorised(&request, token) {
+        route(request, handle).await
+    } else {
+        AdminResponse::error(401, "Missing or invalid bearer token.")
+    }
+}
+
+/// Returns true if the request carries the admin bearer token.
+fn authorised(request: &AdminRequest, token: &str) -> bool {
+    match request
+        .authorization
+        .as_deref()
+        .and_then(|value| value.strip_prefix("Bearer "))
+    {
+        Some(presented) => constant_time_eq(presented.trim().as_bytes(), token.as_bytes()),
+        None => false,
+    }
+}
+
+/// Worker pool resize request body.
+#[derive(serde::Deserialize)]
+struct WorkerPoolRequest {
+    max_size: u16,
+    idle_size: u16,
+}
+
+/// Log level request body.
+#[derive(serde::Deserialize)]
+struct LogLevelRequest {
+    level: String,
+}
+
+/// Routes a request to its handler.
+async fn route(request: AdminRequest, handle: &AdminHandle) -> AdminResponse {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/status") => AdminResponse::ok(status_json(&handle.status)),
+        ("POST", "/shutdown") => {
+            println!("Shutdown requested through admin API.");
+            handle.status.shutdown();
+            AdminResponse::ok(serde_json::json!({ "result": "Shutting down." }))
+        }
+        ("GET", "/worker-pool") => AdminResponse::ok(worker_pool_json(&handle.worker_pool_bounds)),
+        ("POST", "/worker-pool") => {
+            match serde_json::from_slice::<WorkerPoolRequest>(&request.body) {
+                Ok(resize) => match handle
+                    .resize_worker_pool(resize.max_size, resize.idle_size)
+                    .await
+                {
+                    Ok(()) => AdminResponse::ok(worker_pool_json(&handle.worker_pool_bounds)),
+                    Err(response) => response,
+                },
+                Err(e) => AdminResponse::error(400, format!("Invalid request body: {}", e)),
+            }
+        }
+        ("GET", "/log-level") => {
+            AdminResponse::ok(serde_json::json!({ "level": log_level().to_string() }))
+        }
+        ("POST", "/log-level") => match serde_json::from_slice::<LogLevelRequest>(&request.body) {
+            Ok(body) => match body.level.parse::<LogLevel>() {
+                Ok(level) => {
+                    set_log_level(level);
+                    AdminResponse::ok(serde_json::json!({ "level": level.to_string() }))
+                }
+                Err(e) => AdminResponse::error(400, e),
+            },
+            Err(e) => AdminResponse::error(400, format!("Invalid request body: {}", e)),
+        },
+        _ => AdminResponse::error(404, "Unknown endpoint."),
+    }
+}
+
+/// Returns the status of the indexer and its components as json.
+fn status_json(status: &IndexerStatus) -> serde_json::Value {
+    let status = status.load();
+    let server_status = status.server_status();
+    serde_json::json!({
+        "indexer_status": format!("{:?}", status.indexer_status()),
+        "server_status": format!("{:?}", StatusType::from(server_status.server_status.load())),
+        "tcp_ingestor_status": format!("{:?}", server_status.tcp_ingestor_status()),
+        "request_queue_length": server_status.request_queue_length(),
+        "workers": server_status.workerpool_status().workers(),
+        "worker_statuses": server_status
+            .workerpool_status()
+            .statuses()
+            .iter()
+            .map(|status| format!("{:?}", status))
+            .collect::<Vec<String>>(),
+    })
+}
+
+/// Returns the worker pool bounds as json.
+fn worker_pool_json(bounds: &WorkerPoolBounds) -> serde_json::Value {
+    serde_json::json!({
+        "max_size": bounds.max_size(),
+        "idle_size": bounds.idle_size(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zaino_serve::server::error::ServerError;
+
+    fn handle() -> (
+        AdminHandle,
+        tokio::sync::mpsc::Receiver<ConfigUpdateRequest>,
+    ) {
+        let (config_update_tx, config_update_rx) = tokio::sync::mpsc::channel(1);
+        let handle = AdminHandle::new(
+            IndexerStatus::new(4),
+            WorkerPoolBounds::new(4, 2),
+            config_update_tx,
+        );
+        (handle, config_update_rx)
+    }
+
+    fn request(method: &str, path: &str, authorization: Option<&str>, body: &str) -> AdminRequest {
+        AdminRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+            authorization: authorization.map(str::to_string),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    async fn send(handle: &AdminHandle, method: &str, path: &str, body: &str) -> AdminResponse {
+        respond(
+            request(method, path, Some("Bearer secret"), body),
+            "secret",
+            handle,
+        )
+        .await
+    }
+
+    async fn parse(raw: &[u8]) -> Result<AdminRequest, String> {
+        let mut stream = raw;
+        read_request(&mut stream).await
+    }
+
+    #[tokio::test]
+    async fn parses_request() {
+        let request = parse(
+            b"POST /log-level HTTP/1.1\r\nAuthorization: Bearer secret\r\nContent-Length: 17\r\n\r\n{\"level\":\"warn\"}",
+        )
+        .await
+        .unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/log-level");
+        assert_eq!(request.authorization.as_deref(), Some("Bearer secret"));
+        assert_eq!(request.body, b"{\"level\":\"warn\"}");
+        assert!(authorised(&request, "secret"));
+        assert!(!authorised(&request, "secre"));
+    }
+
+    #[tokio::test]
+    async fn malformed_request_line() {
+        assert_eq!(
+            parse(b"GET\r\n\r\n").await.err().as_deref(),
+            Some("Malformed request line.")
+        );
+        assert_eq!(
+            parse(b"\r\n\r\n").await.err().as_deref(),
+            Some("Malformed request line.")
+        );
+    }
+
+    #[tokio::test]
+    async fn oversized_header() {
+        let mut raw = b"GET /status HTTP/1.1\r\nX-Padding: ".to_vec();
+        raw.extend(std::iter::repeat(b'a').take(MAX_HEADER_SIZE + 1));
+        assert_eq!(
+            parse(&raw).await.err().as_deref(),
+            Some("Request header too large.")
+        );
+    }
+
+    #[tokio::test]
+    async fn bad_content_length() {
+        assert_eq!(
+            parse(b"POST /worker-pool HTTP/1.1\r\nContent-Length: ten\r\n\r\n")
+                .await
+                .err()
+                .as_deref(),
+            Some("Invalid Content-Length.")
+        );
+        assert_eq!(
+            parse(b"POST /worker-pool HTTP/1.1\r\nContent-Length: -1\r\n\r\n")
+                .await
+                .err()
+                .as_deref(),
+            Some("Invalid Content-Length.")
+        );
+        let oversized = format!(
+            "POST /worker-pool HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY_SIZE + 1
+        );
+        assert_eq!(
+            parse(oversized.as_bytes()).await.err().as_deref(),
+            Some("Request body too large.")
+        );
+    }
+
+    #[tokio::test]
+    async fn truncated_body() {
+        assert_eq!(
+            parse(b"POST /worker-pool HTTP/1.1\r\nContent-Length: 10\r\n\r\n{}")
+                .await
+                .err()
+                .as_deref(),
+            Some("Connection closed before request body was received.")
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_unauthorised() {
+        let (handle, _config_update_rx) = handle();
+        for authorization in [
+            None,
+            Some("Bearer wrong"),
+            Some("Bearer secret2"),
+            Some("secret"),
+            Some("Basic secret"),
+        ] {
+            let response = respond(
+                request("GET", "/status", authorization, ""),
+                "secret",
+                &handle,
+            )
+            .await;
+            assert_eq!(response.code, 401);
+        }
+    }
+
+    #[tokio::test]
+    async fn unknown_endpoint() {
+        let (handle, _config_update_rx) = handle();
+        assert_eq!(send(&handle, "GET", "/unknown", "").await.code, 404);
+        assert_eq!(send(&handle, "GET", "/shutdown", "").await.code, 404);
+        assert_eq!(
+            send(&handle, "POST", "/block-cache/flush", "").await.code,
+            404
+        );
+    }
+
+    #[tokio::test]
+    async fn bad_request_body() {
+        let (handle, _config_update_rx) = handle();
+        assert_eq!(send(&handle, "POST", "/worker-pool", "{").await.code, 400);
+        assert_eq!(
+            send(&handle, "POST", "/worker-pool", r#"{"max_size": 8}"#)
+                .await
+                .code,
+            400
+        );
+        assert_eq!(
+            send(&handle, "POST", "/log-level", r#"{"level": "loud"}"#)
+                .await
+                .code,
+            400
+        );
+    }
+
+    #[tokio::test]
+    async fn worker_pool_resize() {
+        let (handle, mut config_update_rx) = handle();
+        let bounds = handle.worker_pool_bounds.clone();
+        // Stands in for the server's command loop.
+        tokio::spawn(async move {
+            while let Some(request) = config_update_rx.recv().await {
+                assert!(request.update.rate_limit.is_none());
+                let result = bounds
+                    .set(
+                        request.update.max_worker_pool_size,
+                        request.update.idle_worker_pool_size,
+                    )
+                    .map_err(ServerError::from);
+                request.ack.send(result).ok();
+            }
+        });
+
+        let response = send(
+            &handle,
+            "POST",
+            "/worker-pool",
+            r#"{"max_size": 8, "idle_size": 3}"#,
+        )
+        .await;
+        assert_eq!(response.code, 200);
+        assert_eq!(
+            response.body,
+            serde_json::json!({ "max_size": 8, "idle_size": 3 })
+        );
+
+        let response = send(
+            &handle,
+            "POST",
+            "/worker-pool",
+            r#"{"max_size": 2, "idle_size": 3}"#,
+        )
+        .await;
+        assert_eq!(response.code, 400);
+        let response = send(&handle, "GET", "/worker-pool", "").await;
+        assert_eq!(
+            response.body,
+            serde_json::json!({ "max_size": 8, "idle_size": 3 })
+        );
+    }
+
+    #[tokio::test]
+    async fn worker_pool_resize_without_server() {
+        let (handle, config_update_rx) = handle();
+        drop(config_update_rx);
+        let response = send(
+            &handle,
+            "POST",
+            "/worker-pool",
+            r#"{"max_size": 8, "idle_size": 3}"#,
+        )
+        .await;
+        assert_eq!(response.code, 500);
+        assert_eq!(handle.worker_pool_bounds.max_size(), 4);
+    }
+}
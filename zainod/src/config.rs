@@ -1,9 +1,25 @@
 //! Zaino config.
 
+use std::path::PathBuf;
+
+use zaino_fetch::{chain::block::BlockOptions, jsonrpc::backend::BackendConfig};
+use zaino_serve::{
+    logging::LogLevel,
+    rpc::method,
+    server::{auth::ApiKeyConfig, director::ServerConfigUpdate, limiter::RateLimitConfig},
+};
+use zebra_chain::parameters::{Network, NetworkUpgrade};
+
 use crate::error::IndexerError;
 
+/// Placeholder printed in place of secrets.
+const REDACTED: &str = "<redacted>";
+
 /// Config information required for Zaino.
-#[derive(Debug, Clone, serde::Deserialize)]
+///
+/// Fields missing from a config file take their value from [`IndexerConfig::default`], unknown fields are rejected.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct IndexerConfig {
     /// Sets the TcpIngestor's status.
     pub tcp_active: bool,
@@ -11,24 +27,65 @@ pub struct IndexerConfig {
     pub listen_port: Option<u16>,
     /// Full node / validator listen port.
     pub zebrad_port: u16,
+    /// Network the validator is running on, checked against the validator at startup.
+    pub network: NetworkConfig,
     /// Full node Username.
     pub node_user: Option<String>,
     /// full node Password.
     pub node_password: Option<String>,
+    /// Send transactions to every reachable validator backend rather than only the active backend.
+    pub broadcast_transactions: bool,
     /// Maximum requests allowed in the request queue.
     pub max_queue_size: u16,
     /// Maximum workers allowed in the worker pool
     pub max_worker_pool_size: u16,
     /// Minimum number of workers held in the workerpool when idle.
     pub idle_worker_pool_size: u16,
+    /// Time in seconds given to in-flight requests to complete on shutdown, before response streams are cancelled.
+    pub shutdown_grace_period: u64,
+    /// Log verbosity: "error", "warn", "info" or "debug".
+    pub log_level: LogLevel,
+    /// Regtest network parameters, only used when `network` is regtest.
+    pub regtest: RegtestConfig,
+    /// Per-client rate limits applied to incoming connections and RPCs.
+    pub rate_limit: RateLimitConfig,
+    /// API key authentication for the gRPC service.
+    pub auth: AuthConfig,
+    /// Admin API used for runtime control of the indexer.
+    pub admin: AdminConfig,
+    /// Additional validator backends, failed over to if the validator at `zebrad_port` becomes unhealthy.
+    pub backends: Vec<BackendConfig>,
+    /// Options applied to blocks and transactions fetched from the validator, eg. verification.
+    pub blocks: BlockOptions,
 }
 
 impl IndexerConfig {
+    /// Returns a copy of the config with secrets (passwords and tokens) redacted.
+    pub fn redacted(&self) -> IndexerConfig {
+        let redact = |secret: &Option<String>| secret.as_ref().map(|_| REDACTED.to_string());
+        let mut config = self.clone();
+        config.node_password = redact(&self.node_password);
+        config.admin.token = redact(&self.admin.token);
+        for key in config.auth.keys.iter_mut() {
+            key.token = REDACTED.to_string();
+        }
+        for backend in config.backends.iter_mut() {
+            backend.password = redact(&backend.password);
+        }
+        config
+    }
+
+    /// Returns the config as toml, with secrets redacted.
+    pub fn to_redacted_toml(&self) -> Result<String, IndexerError> {
+        toml::to_string(&self.redacted())
+            .map_err(|e| IndexerError::ConfigError(format!("Failed to serialize config: {}", e)))
+    }
+
     /// Performs checks on config data.
     ///
     /// - Checks that at least 1 ingestor is active.
     /// - Checks listen port is given is tcp is active.
-    pub(crate) fn check_config(&self) -> Result<(), IndexerError> {
+    pub fn check_config(&self) -> Result<(), IndexerError> {
         if !self.tcp_active {
             return Err(IndexerError::ConfigError(
                 "Cannot start server with no ingestors selected.".to_string(),
@@ -39,8 +96,263 @@ impl IndexerConfig {
                 "TCP is active but no address provided.".to_string(),
             ));
         }
+        check_method_names("rate_limit.methods", self.rate_limit.methods.keys())?;
+        if self.auth.enabled && self.auth.keys.is_empty() && self.auth.keys_file.is_none() {
+            return Err(IndexerError::ConfigError(
+                "Authentication is enabled but no API keys provided.".to_string(),
+            ));
+        }
+        if self.admin.enabled && self.admin.token.as_deref().unwrap_or_default().is_empty() {
+            return Err(IndexerError::ConfigError(
+                "Admin API is enabled but no admin token provided.".to_string(),
+            ));
+        }
+        if self.network != NetworkConfig::Regtest && self.regtest != RegtestConfig::default() {
+            return Err(IndexerError::ConfigError(
+                "Regtest activation heights given but network is not regtest.".to_string(),
+            ));
+        }
         Ok(())
     }
+
+    /// Returns the config fields that may be updated while the server is running.
+    pub fn server_config_update(&self) -> ServerConfigUpdate {
+        ServerConfigUpdate {
+            max_worker_pool_size: self.max_worker_pool_size,
+            idle_worker_pool_size: self.idle_worker_pool_size,
+            rate_limit: Some(self.rate_limit.clone()),
+        }
+    }
+
+    /// Returns the names of fields that differ from `new` and can only be applied by restarting the indexer.
+    ///
+    /// The worker pool sizes, log level and rate limits are applied live on reload.
+    pub fn restart_required_changes(&self, new: &IndexerConfig) -> Vec<&'static str> {
+        let mut changes = Vec::new();
+        let mut check = |name: &'static str, changed: bool| {
+            if changed {
+                changes.push(name);
+            }
+        };
+        check("tcp_active", self.tcp_active != new.tcp_active);
+        check("listen_port", self.listen_port != new.listen_port);
+        check("zebrad_port", self.zebrad_port != new.zebrad_port);
+        check("network", self.network != new.network);
+        check("node_user", self.node_user != new.node_user);
+        check("node_password", self.node_password != new.node_password);
+        check(
+            "broadcast_transactions",
+            self.broadcast_transactions != new.broadcast_transactions,
+        );
+        check("max_queue_size", self.max_queue_size != new.max_queue_size);
+        check(
+            "shutdown_grace_period",
+            self.shutdown_grace_period != new.shutdown_grace_period,
+        );
+        check("regtest", self.regtest != new.regtest);
+        check("auth", self.auth != new.auth);
+        check("admin", self.admin != new.admin);
+        check("backends", self.backends != new.backends);
+        check("blocks", self.blocks != new.blocks);
+        changes
+    }
+
+    /// Returns a copy of the config with the fields that are applied live on reload taken from `new`.
+    pub fn with_live_changes(&self, new: &IndexerConfig) -> IndexerConfig {
+        IndexerConfig {
+            max_worker_pool_size: new.max_worker_pool_size,
+            idle_worker_pool_size: new.idle_worker_pool_size,
+            log_level: new.log_level,
+            rate_limit: new.rate_limit.clone(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Network the validator is running on.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkConfig {
+    /// Zcash mainnet.
+    Mainnet,
+    /// Zcash public testnet.
+    Testnet,
+    /// Local regtest network.
+    Regtest,
+}
+
+impl NetworkConfig {
+    /// Returns the BIP70 chain names reported by validators on this network, as returned by `getblockchaininfo`.
+    ///
+    /// Some validators report regtest as "test".
+    pub fn chain_names(&self) -> &'static [&'static str] {
+        match self {
+            NetworkConfig::Mainnet => &["main"],
+            NetworkConfig::Testnet => &["test"],
+            NetworkConfig::Regtest => &["regtest", "test"],
+        }
+    }
+
+    /// Returns the zebra network parameters.
+    ///
+    /// Regtest activation heights not set in `regtest` take the value given in `validator_heights`.
+    pub fn to_network(
+        &self,
+        regtest: &RegtestConfig,
+        validator_heights: impl Fn(NetworkUpgrade) -> Option<u32>,
+    ) -> Network {
+        match self {
+            NetworkConfig::Mainnet => Network::Mainnet,
+            NetworkConfig::Testnet => Network::new_default_testnet(),
+            NetworkConfig::Regtest => Network::new_regtest(
+                regtest
+                    .nu5_activation_height
+                    .or_else(|| validator_heights(NetworkUpgrade::Nu5)),
+                regtest
+                    .nu6_activation_height
+                    .or_else(|| validator_heights(NetworkUpgrade::Nu6)),
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for NetworkConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let network = match self {
+            NetworkConfig::Mainnet => "mainnet",
+            NetworkConfig::Testnet => "testnet",
+            NetworkConfig::Regtest => "regtest",
+        };
+        write!(f, "{}", network)
+    }
+}
+
+/// Regtest network config.
+///
+/// Upgrades before NU5 activate at height 1 on regtest, activation heights that are not set are taken from the
+/// validator.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RegtestConfig {
+    /// NU5 activation height.
+    pub nu5_activation_height: Option<u32>,
+    /// NU6 activation height.
+    pub nu6_activation_height: Option<u32>,
+}
+
+/// Admin API config.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AdminConfig {
+    /// Enables the admin API.
+    pub enabled: bool,
+    /// Admin API listen port, the admin API only listens on localhost.
+    pub listen_port: u16,
+    /// Bearer token required to access the admin API.
+    pub token: Option<String>,
+}
+
+impl Default for AdminConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen_port: 8138,
+            token: None,
+        }
+    }
+}
+
+/// API key authentication config.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    /// Requires clients to present a valid API key as an `authorization: Bearer <token>` header.
+    pub enabled: bool,
+    /// Optional path to a toml file holding additional API keys as `[[keys]]` tables.
+    pub keys_file: Option<PathBuf>,
+    /// API keys accepted by the server.
+    pub keys: Vec<ApiKeyConfig>,
+}
+
+/// Layout of an API key file.
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ApiKeyFile {
+    keys: Vec<ApiKeyConfig>,
+}
+
+impl AuthConfig {
+    /// Returns the API keys accepted by the server, reading `keys_file` if given.
+    ///
+    /// Returns None if authentication is disabled.
+    pub fn load_keys(&self) -> Result<Option<Vec<ApiKeyConfig>>, IndexerError> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        let mut keys = self.keys.clone();
+        if let Some(path) = &self.keys_file {
+            let contents = std::fs::read_to_string(path).map_err(|e| {
+                IndexerError::ConfigError(format!(
+                    "Failed to read API key file {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+            let key_file = toml::from_str::<ApiKeyFile>(&contents)
+                .map_err(|e| IndexerError::ConfigError(toml_error_message(path, &contents, &e)))?;
+            keys.extend(key_file.keys);
+        }
+        for (i, key) in keys.iter().enumerate() {
+            if key.token.is_empty() {
+                return Err(IndexerError::ConfigError(format!(
+                    "API key {} has an empty token.",
+                    key.name
+                )));
+            }
+            if let Some(allowed_rpcs) = &key.allowed_rpcs {
+                check_method_names(&format!("API key {} allowed_rpcs", key.name), allowed_rpcs)?;
+            }
+            if let Some(rate_limit) = &key.rate_limit {
+                check_method_names(
+                    &format!("API key {} rate_limit.methods", key.name),
+                    rate_limit.methods.keys(),
+                )?;
+            }
+            if key
+                .rate_limit
+                .as_ref()
+                .is_some_and(|rate_limit| rate_limit.connections.is_some())
+            {
+                return Err(IndexerError::ConfigError(format!(
+                    "API key {} sets rate_limit.connections, connection limits are applied per peer before authentication and may only be set in [rate_limit].",
+                    key.name
+                )));
+            }
+            if keys[..i].iter().any(|other| other.name == key.name) {
+                return Err(IndexerError::ConfigError(format!(
+                    "Duplicate API key name: {}.",
+                    key.name
+                )));
+            }
+        }
+        Ok(Some(keys))
+    }
+}
+
+/// Returns an error naming the first of `names` that is not a gRPC method of the lightwallet service.
+fn check_method_names<'a>(
+    field: &str,
+    names: impl IntoIterator<Item = &'a String>,
+) -> Result<(), IndexerError> {
+    match names.into_iter().find(|name| !method::is_method(name)) {
+        Some(name) => Err(IndexerError::ConfigError(format!(
+            "{} contains unknown gRPC method \"{}\".",
+            field, name
+        ))),
+        None => Ok(()),
+    }
 }
 
 impl Default for IndexerConfig {
@@ -49,33 +361,726 @@ impl Default for IndexerConfig {
             tcp_active: true,
             listen_port: Some(8080),
             zebrad_port: 18232,
-            node_user: Some("xxxxxx".to_string()),
-            node_password: Some("xxxxxx".to_string()),
+            network: NetworkConfig::Testnet,
+            node_user: None,
+            node_password: None,
+            broadcast_transactions: false,
             max_queue_size: 1024,
             max_worker_pool_size: 32,
             idle_worker_pool_size: 4,
+            shutdown_grace_period: 30,
+            log_level: LogLevel::default(),
+            regtest: RegtestConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            auth: AuthConfig::default(),
+            admin: AdminConfig::default(),
+            backends: Vec::new(),
+            blocks: BlockOptions::default(),
         }
     }
 }
 
-/// Attempts to load config data from a toml file at the specified path.
-pub fn load_config(file_path: &std::path::PathBuf) -> IndexerConfig {
-    let mut config = IndexerConfig::default();
+/// Returns a commented zindexer.toml holding the default config.
+pub fn default_config_toml() -> String {
+    let config = IndexerConfig::default();
+    format!(
+        r#"# Configuration for Zaino
+# Fields left out of this file take their default values, unknown fields are rejected.
+
+# Sets the TcpIngestor's status (true or false)
+tcp_active = {tcp_active}
+
+# Optional TcpIngestors listen port (use None or specify a port number)
+listen_port = {listen_port}
+
+# Full node / validator listen port
+zebrad_port = {zebrad_port}
+
+# Network the validator is running on: "mainnet", "testnet" or "regtest"
+network = "{network}"
+
+# Optional full node Username, no credentials are sent if not set
+# node_user = "xxxxxx"
+
+# Optional full node Password
+# node_password = "xxxxxx"
+
+# Send transactions to every reachable validator backend rather than only the active backend
+broadcast_transactions = {broadcast_transactions}
+
+# Maximum requests allowed in the request queue
+max_queue_size = {max_queue_size}
+
+# Maximum workers allowed in the worker pool
+max_worker_pool_size = {max_worker_pool_size}
+
+# Minimum number of workers held in the worker pool when idle
+idle_worker_pool_size = {idle_worker_pool_size}
+
+# Time in seconds given to in-flight requests to complete on shutdown, before response streams are cancelled
+shutdown_grace_period = {shutdown_grace_period}
+
+# Log verbosity: "error", "warn", "info" or "debug"
+log_level = "{log_level}"
+
+# Optional regtest activation heights, heights that are not set are taken from the validator.
+# [regtest]
+# nu5_activation_height = 1
+# nu6_activation_height = 1
+
+# Optional per-client rate limits, clients are keyed by peer IP (or API key where present).
+# Limits that are not set are not enforced.
+# [rate_limit]
+# Maximum RPCs a single client may have in flight at once
+# max_in_flight = 16
+# Token bucket applied to new connections
+# connections = {{ burst = 32, per_second = 8.0 }}
+# Token bucket applied to RPC methods without a specific limit
+# default_method = {{ burst = 100, per_second = 50.0 }}
+# Per-method token buckets, keyed by gRPC method name
+# methods.GetBlockRange = {{ burst = 4, per_second = 0.5 }}
+# methods.GetLatestBlock = {{ burst = 200, per_second = 100.0 }}
+
+# Optional API key authentication, clients must send an `authorization: Bearer <token>` header.
+# [auth]
+# enabled = true
+# Optional path to a toml file holding additional API keys as [[keys]] tables
+# keys_file = "./zainod/api_keys.toml"
+# [[auth.keys]]
+# name = "read-only"
+# token = "<secret>"
+# Optional list of gRPC methods the key may call, all methods are allowed if not set
+# allowed_rpcs = ["GetLatestBlock", "GetBlock", "GetBlockRange", "GetTransaction"]
+# Optional rate limits for the key, layered over [rate_limit] for its requests (connections may not be set)
+# rate_limit = {{ max_in_flight = 4, default_method = {{ burst = 20, per_second = 10.0 }} }}
+
+# Optional admin API used for runtime control of zainod, only listens on localhost.
+# Requests must send an `authorization: Bearer <token>` header.
+# [admin]
+# enabled = true
+# listen_port = {admin_listen_port}
+# token = "<secret>"
+
+# Optional additional validator backends. Requests are routed to the healthiest backend (the validator at zebrad_port
+# is preferred), failing over automatically if it becomes unreachable or falls behind.
+# [[backends]]
+# uri = "http://10.0.0.2:18232"
+# user = "xxxxxx"
+# password = "xxxxxx"
+
+# Optional verification of blocks fetched from the validator. Checks each block's transaction merkle root and hash,
+# and that consecutive blocks in range requests link to their parent.
+# [blocks]
+# verify = true
+# Checks each block header's Equihash solution and that its hash meets the nBits target, not supported on regtest
+# verify_pow = true
+# Returns the serialized block header in CompactBlock.header, for wallets that verify the header chain
+# compact_header = true
+# Returns the fee of each CompactTx (including mempool transactions), costs a getrawtransaction call per spent output
+# fees = true
+"#,
+        tcp_active = config.tcp_active,
+        listen_port = config.listen_port.unwrap_or_default(),
+        zebrad_port = config.zebrad_port,
+        network = config.network,
+        broadcast_transactions = config.broadcast_transactions,
+        max_queue_size = config.max_queue_size,
+        max_worker_pool_size = config.max_worker_pool_size,
+        idle_worker_pool_size = config.idle_worker_pool_size,
+        shutdown_grace_period = config.shutdown_grace_period,
+        log_level = config.log_level,
+        admin_listen_port = config.admin.listen_port,
+    )
+}
+
+/// Loads config data from a toml file at the specified path.
+///
+/// Returns an error if the file cannot be read or fails to parse, fields missing from the file take their default
+/// values.
+pub fn load_config(file_path: &std::path::PathBuf) -> Result<IndexerConfig, IndexerError> {
+    let contents = std::fs::read_to_string(file_path).map_err(|e| {
+        IndexerError::ConfigError(format!(
+            "Failed to read config file {}: {}",
+            file_path.display(),
+            e
+        ))
+    })?;
+    toml::from_str::<IndexerConfig>(&contents)
+        .map_err(|e| IndexerError::ConfigError(toml_error_message(file_path, &contents, &e)))
+}
+
+/// Location of the config file and the overrides applied on top of it, used to reload the config.
+#[derive(Debug, Clone)]
+pub struct ConfigSource {
+    /// Path to the config file.
+    pub path: PathBuf,
+    /// Environment / command line overrides.
+    pub overrides: ConfigOverrides,
+}
+
+impl ConfigSource {
+    /// Loads the config file and applies the overrides.
+    pub fn load(&self) -> Result<IndexerConfig, IndexerError> {
+        let mut config = load_config(&self.path)?;
+        self.overrides.apply(&mut config);
+        Ok(config)
+    }
+}
+
+/// Returns a diagnostic message for a toml parse error, quoting the offending line where known.
+///
+/// The line and column of the error are included in the error's message.
+pub(crate) fn toml_error_message(
+    file_path: &std::path::Path,
+    contents: &str,
+    error: &toml::de::Error,
+) -> String {
+    match error.line_col() {
+        Some((line, _)) => format!(
+            "Failed to parse {}: {}\n{:>5} | {}",
+            file_path.display(),
+            error,
+            line + 1,
+            contents.lines().nth(line).unwrap_or_default()
+        ),
+        None => format!("Failed to parse {}: {}", file_path.display(), error),
+    }
+}
 
-    if let Ok(contents) = std::fs::read_to_string(file_path) {
-        if let Ok(parsed_config) = toml::from_str::<IndexerConfig>(&contents) {
-            config = IndexerConfig {
-                tcp_active: parsed_config.tcp_active,
-                listen_port: parsed_config.listen_port.or(config.listen_port),
-                zebrad_port: parsed_config.zebrad_port,
-                node_user: parsed_config.node_user.or(config.node_user),
-                node_password: parsed_config.node_password.or(config.node_password),
-                max_queue_size: parsed_config.max_queue_size,
-                max_worker_pool_size: parsed_config.max_worker_pool_size,
-                idle_worker_pool_size: parsed_config.idle_worker_pool_size,
-            };
+/// Config overrides, taken from `ZAINO_*` environment variables and command line flags.
+///
+/// Command line flags take precedence over environment variables, which take precedence over the config file.
+/// Table and array fields are given as inline toml, eg. `--rate-limit '{ max_in_flight = 16 }'`.
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct ConfigOverrides {
+    /// Sets the TcpIngestor's status.
+    #[arg(long, env = "ZAINO_TCP_ACTIVE", value_name = "BOOL", global = true)]
+    pub tcp_active: Option<bool>,
+    /// TcpIngestors listen port.
+    #[arg(long, env = "ZAINO_LISTEN_PORT", value_name = "PORT", global = true)]
+    pub listen_port: Option<u16>,
+    /// Full node / validator listen port.
+    #[arg(long, env = "ZAINO_ZEBRAD_PORT", value_name = "PORT", global = true)]
+    pub zebrad_port: Option<u16>,
+    /// Network the validator is running on.
+    #[arg(long, env = "ZAINO_NETWORK", value_name = "NETWORK", global = true)]
+    pub network: Option<NetworkConfig>,
+    /// Full node Username.
+    #[arg(long, env = "ZAINO_NODE_USER", value_name = "USER", global = true)]
+    pub node_user: Option<String>,
+    /// Full node Password.
+    #[arg(
+        long,
+        env = "ZAINO_NODE_PASSWORD",
+        value_name = "PASSWORD",
+        hide_env_values = true,
+        global = true
+    )]
+    pub node_password: Option<String>,
+    /// Send transactions to every reachable validator backend.
+    #[arg(
+        long,
+        env = "ZAINO_BROADCAST_TRANSACTIONS",
+        value_name = "BOOL",
+        global = true
+    )]
+    pub broadcast_transactions: Option<bool>,
+    /// Maximum requests allowed in the request queue.
+    #[arg(long, env = "ZAINO_MAX_QUEUE_SIZE", value_name = "SIZE", global = true)]
+    pub max_queue_size: Option<u16>,
+    /// Maximum workers allowed in the worker pool.
+    #[arg(
+        long,
+        env = "ZAINO_MAX_WORKER_POOL_SIZE",
+        value_name = "SIZE",
+        global = true
+    )]
+    pub max_worker_pool_size: Option<u16>,
+    /// Minimum number of workers held in the workerpool when idle.
+    #[arg(
+        long,
+        env = "ZAINO_IDLE_WORKER_POOL_SIZE",
+        value_name = "SIZE",
+        global = true
+    )]
+    pub idle_worker_pool_size: Option<u16>,
+    /// Time in seconds given to in-flight requests to complete on shutdown.
+    #[arg(
+        long,
+        env = "ZAINO_SHUTDOWN_GRACE_PERIOD",
+        value_name = "SECONDS",
+        global = true
+    )]
+    pub shutdown_grace_period: Option<u64>,
+    /// Log verbosity: "error", "warn", "info" or "debug".
+    #[arg(long, env = "ZAINO_LOG_LEVEL", value_name = "LEVEL", global = true)]
+    pub log_level: Option<LogLevel>,
+    /// Regtest activation heights, as an inline toml table.
+    #[arg(
+        long,
+        env = "ZAINO_REGTEST",
+        value_name = "TOML",
+        value_parser = parse_inline_toml::<RegtestConfig>,
+        global = true
+    )]
+    pub regtest: Option<RegtestConfig>,
+    /// Per-client rate limits, as an inline toml table.
+    #[arg(
+        long,
+        env = "ZAINO_RATE_LIMIT",
+        value_name = "TOML",
+        value_parser = parse_inline_toml::<RateLimitConfig>,
+        global = true
+    )]
+    pub rate_limit: Option<RateLimitConfig>,
+    /// Requires clients to authenticate with an API key.
+    #[arg(long, env = "ZAINO_AUTH_ENABLED", value_name = "BOOL", global = true)]
+    pub auth_enabled: Option<bool>,
+    /// Path to a toml file holding API keys.
+    #[arg(long, env = "ZAINO_AUTH_KEYS_FILE", value_name = "FILE", global = true)]
+    pub auth_keys_file: Option<PathBuf>,
+    /// API key as an inline toml table, replaces the keys listed in the config file.
+    /// May be given multiple times, or as a `;` separated list in ZAINO_AUTH_KEYS.
+    #[arg(
+        long = "auth-key",
+        env = "ZAINO_AUTH_KEYS",
+        value_name = "TOML",
+        value_delimiter = ';',
+        value_parser = parse_inline_toml::<ApiKeyConfig>,
+        hide_env_values = true,
+        global = true
+    )]
+    pub auth_keys: Option<Vec<ApiKeyConfig>>,
+    /// Enables the admin API.
+    #[arg(long, env = "ZAINO_ADMIN_ENABLED", value_name = "BOOL", global = true)]
+    pub admin_enabled: Option<bool>,
+    /// Admin API listen port.
+    #[arg(
+        long,
+        env = "ZAINO_ADMIN_LISTEN_PORT",
+        value_name = "PORT",
+        global = true
+    )]
+    pub admin_listen_port: Option<u16>,
+    /// Bearer token required to access the admin API.
+    #[arg(
+        long,
+        env = "ZAINO_ADMIN_TOKEN",
+        value_name = "TOKEN",
+        hide_env_values = true,
+        global = true
+    )]
+    pub admin_token: Option<String>,
+    /// Additional validator backend as an inline toml table, replaces the backends listed in the config file.
+    /// May be given multiple times, or as a `;` separated list in ZAINO_BACKENDS.
+    #[arg(
+        long = "backend",
+        env = "ZAINO_BACKENDS",
+        value_name = "TOML",
+        value_delimiter = ';',
+        value_parser = parse_inline_toml::<BackendConfig>,
+        hide_env_values = true,
+        global = true
+    )]
+    pub backends: Option<Vec<BackendConfig>>,
+    /// Verifies the merkle root, hash and parent link of blocks fetched from the validator.
+    #[arg(long, env = "ZAINO_VERIFY_BLOCKS", value_name = "BOOL", global = true)]
+    pub verify_blocks: Option<bool>,
+    /// Verifies the Equihash solution and difficulty target of blocks fetched from the validator.
+    #[arg(
+        long,
+        env = "ZAINO_VERIFY_BLOCK_POW",
+        value_name = "BOOL",
+        global = true
+    )]
+    pub verify_block_pow: Option<bool>,
+    /// Returns the serialized block header in compact blocks.
+    #[arg(
+        long,
+        env = "ZAINO_COMPACT_BLOCK_HEADER",
+        value_name = "BOOL",
+        global = true
+    )]
+    pub compact_block_header: Option<bool>,
+    /// Returns the fee of compact transactions.
+    #[arg(
+        long,
+        env = "ZAINO_COMPACT_TX_FEES",
+        value_name = "BOOL",
+        global = true
+    )]
+    pub compact_tx_fees: Option<bool>,
+}
+
+impl ConfigOverrides {
+    /// Applies the overrides to the given config.
+    pub fn apply(&self, config: &mut IndexerConfig) {
+        if let Some(tcp_active) = self.tcp_active {
+            config.tcp_active = tcp_active;
+        }
+        if let Some(listen_port) = self.listen_port {
+            config.listen_port = Some(listen_port);
+        }
+        if let Some(zebrad_port) = self.zebrad_port {
+            config.zebrad_port = zebrad_port;
+        }
+        if let Some(network) = self.network {
+            config.network = network;
+        }
+        if let Some(node_user) = &self.node_user {
+            config.node_user = Some(node_user.clone());
+        }
+        if let Some(node_password) = &self.node_password {
+            config.node_password = Some(node_password.clone());
+        }
+        if let Some(broadcast_transactions) = self.broadcast_transactions {
+            config.broadcast_transactions = broadcast_transactions;
+        }
+        if let Some(max_queue_size) = self.max_queue_size {
+            config.max_queue_size = max_queue_size;
+        }
+        if let Some(max_worker_pool_size) = self.max_worker_pool_size {
+            config.max_worker_pool_size = max_worker_pool_size;
+        }
+        if let Some(idle_worker_pool_size) = self.idle_worker_pool_size {
+            config.idle_worker_pool_size = idle_worker_pool_size;
+        }
+        if let Some(shutdown_grace_period) = self.shutdown_grace_period {
+            config.shutdown_grace_period = shutdown_grace_period;
+        }
+        if let Some(log_level) = self.log_level {
+            config.log_level = log_level;
+        }
+        if let Some(regtest) = &self.regtest {
+            config.regtest = regtest.clone();
+        }
+        if let Some(rate_limit) = &self.rate_limit {
+            config.rate_limit = rate_limit.clone();
+        }
+        if let Some(auth_enabled) = self.auth_enabled {
+            config.auth.enabled = auth_enabled;
         }
+        if let Some(auth_keys_file) = &self.auth_keys_file {
+            config.auth.keys_file = Some(auth_keys_file.clone());
+        }
+        if let Some(auth_keys) = &self.auth_keys {
+            config.auth.keys = auth_keys.clone();
+        }
+        if let Some(admin_enabled) = self.admin_enabled {
+            config.admin.enabled = admin_enabled;
+        }
+        if let Some(admin_listen_port) = self.admin_listen_port {
+            config.admin.listen_port = admin_listen_port;
+        }
+        if let Some(admin_token) = &self.admin_token {
+            config.admin.token = Some(admin_token.clone());
+        }
+        if let Some(backends) = &self.backends {
+            config.backends = backends.clone();
+        }
+        if let Some(verify_blocks) = self.verify_blocks {
+            config.blocks.verify = verify_blocks;
+        }
+        if let Some(verify_block_pow) = self.verify_block_pow {
+            config.blocks.verify_pow = verify_block_pow;
+        }
+        if let Some(compact_block_header) = self.compact_block_header {
+            config.blocks.compact_header = compact_block_header;
+        }
+        if let Some(compact_tx_fees) = self.compact_tx_fees {
+            config.blocks.fees = compact_tx_fees;
+        }
+    }
+}
+
+/// Parses a value given as inline toml, eg. `{ burst = 10, per_second = 1.0 }`.
+fn parse_inline_toml<T: serde::de::DeserializeOwned>(value: &str) -> Result<T, String> {
+    #[derive(serde::Deserialize)]
+    struct Inline<T> {
+        value: T,
+    }
+    toml::from_str::<Inline<T>>(&format!("value = {}", value))
+        .map(|inline| inline.value)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(contents: &str) -> Result<IndexerConfig, toml::de::Error> {
+        toml::from_str::<IndexerConfig>(contents)
+    }
+
+    fn to_toml(config: &IndexerConfig) -> String {
+        toml::to_string(config).unwrap()
+    }
+
+    #[test]
+    fn empty_config_is_default() {
+        assert_eq!(
+            to_toml(&parse("").unwrap()),
+            to_toml(&IndexerConfig::default())
+        );
+    }
+
+    #[test]
+    fn generated_config_is_default() {
+        assert_eq!(
+            to_toml(&parse(&default_config_toml()).unwrap()),
+            to_toml(&IndexerConfig::default())
+        );
     }
 
-    config
+    #[test]
+    fn partial_config_takes_defaults() {
+        let config = parse(
+            r#"
+zebrad_port = 8232
+network = "mainnet"
+
+[admin]
+enabled = true
+"#,
+        )
+        .unwrap();
+        let default = IndexerConfig::default();
+        assert_eq!(config.zebrad_port, 8232);
+        assert_eq!(config.network, NetworkConfig::Mainnet);
+        assert!(config.admin.enabled);
+        assert_eq!(config.admin.listen_port, default.admin.listen_port);
+        assert_eq!(config.listen_port, default.listen_port);
+        assert_eq!(config.max_worker_pool_size, default.max_worker_pool_size);
+        assert_eq!(config.blocks, default.blocks);
+    }
+
+    #[test]
+    fn missing_credentials_are_not_set() {
+        let config = parse("zebrad_port = 8232").unwrap();
+        assert_eq!(config.node_user, None);
+        assert_eq!(config.node_password, None);
+    }
+
+    #[test]
+    fn shipped_config_has_no_credentials() {
+        let config = parse(include_str!("../zindexer.toml")).unwrap();
+        assert_eq!(config.node_user, None);
+        assert_eq!(config.node_password, None);
+    }
+
+    #[test]
+    fn reject_unknown_fields() {
+        let error = parse("zebra_port = 8232").unwrap_err();
+        assert!(error.to_string().contains("zebra_port"));
+        assert!(parse("[admin]\nenabled = true\nport = 8138").is_err());
+        assert!(parse("[regtest]\nnu7_activation_height = 1").is_err());
+        assert!(
+            parse("[[backends]]\nuri = \"http://127.0.0.1:8232\"\nusername = \"zaino\"").is_err()
+        );
+    }
+
+    #[test]
+    fn toml_error_message_quotes_line() {
+        let contents = "zebrad_port = 8232\nmax_queue_size = \"large\"\n";
+        let error = parse(contents).unwrap_err();
+        let message = toml_error_message(std::path::Path::new("zindexer.toml"), contents, &error);
+        assert!(message.starts_with("Failed to parse zindexer.toml: "));
+        assert!(message.ends_with("    2 | max_queue_size = \"large\""));
+    }
+
+    #[test]
+    fn reject_key_connection_limits() {
+        let config = parse(
+            r#"
+            [auth]
+            enabled = true
+
+            [[auth.keys]]
+            name = "wallet"
+            token = "secret"
+            rate_limit = { max_in_flight = 4 }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.auth.load_keys().unwrap().unwrap().len(), 1);
+
+        let config = parse(
+            r#"
+            [auth]
+            enabled = true
+
+            [[auth.keys]]
+            name = "wallet"
+            token = "secret"
+            rate_limit = { connections = { burst = 1, per_second = 1.0 } }
+            "#,
+        )
+        .unwrap();
+        let error = config.auth.load_keys().unwrap_err().to_string();
+        assert!(error.contains("rate_limit.connections"));
+    }
+
+    #[test]
+    fn reject_unknown_method_names() {
+        let config = parse(
+            r#"
+            [rate_limit.methods]
+            GetBlockrange = { burst = 1, per_second = 1.0 }
+            "#,
+        )
+        .unwrap();
+        assert!(config
+            .check_config()
+            .unwrap_err()
+            .to_string()
+            .contains("GetBlockrange"));
+
+        let config = parse(
+            r#"
+            [auth]
+            enabled = true
+
+            [[auth.keys]]
+            name = "wallet"
+            token = "secret"
+            allowed_rpcs = ["GetLatestBlock", "GetMempoolTX"]
+            "#,
+        )
+        .unwrap();
+        let error = config.auth.load_keys().unwrap_err().to_string();
+        assert!(error.contains("GetMempoolTX"));
+    }
+
+    #[test]
+    fn load_missing_config_file() {
+        match load_config(&PathBuf::from("/nonexistent/zindexer.toml")) {
+            Err(IndexerError::ConfigError(message)) => {
+                assert!(message.starts_with("Failed to read config file /nonexistent/zindexer.toml"))
+            }
+            other => panic!("expected a config error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[derive(clap::Parser)]
+    struct Args {
+        #[command(flatten)]
+        overrides: ConfigOverrides,
+    }
+
+    /// Serializes parsing overrides, clap reads every `ZAINO_*` environment variable while parsing.
+    static ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    /// Parses overrides from the given command line flags, with the given `ZAINO_*` environment variables set.
+    ///
+    /// The variables are removed once parsed, every test parses overrides through this function so no other test
+    /// reads the environment while they are set.
+    fn try_parse_overrides(
+        env: &[(&str, &str)],
+        args: &[&str],
+    ) -> Result<ConfigOverrides, clap::Error> {
+        let _lock = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        for (name, value) in env {
+            std::env::set_var(name, value);
+        }
+        let args = <Args as clap::Parser>::try_parse_from(
+            std::iter::once("zainod").chain(args.iter().copied()),
+        );
+        for (name, _) in env {
+            std::env::remove_var(name);
+        }
+        args.map(|args| args.overrides)
+    }
+
+    fn parse_overrides(args: &[&str]) -> ConfigOverrides {
+        try_parse_overrides(&[], args).unwrap()
+    }
+
+    #[test]
+    fn override_precedence() {
+        // The file overrides defaults.
+        let mut config = parse(
+            "max_queue_size = 10\nmax_worker_pool_size = 20\nidle_worker_pool_size = 2\nnetwork = \"mainnet\"",
+        )
+        .unwrap();
+        assert_eq!(config.zebrad_port, IndexerConfig::default().zebrad_port);
+
+        // Environment variables override the file, command line flags override both.
+        let overrides = try_parse_overrides(
+            &[
+                ("ZAINO_MAX_WORKER_POOL_SIZE", "30"),
+                ("ZAINO_IDLE_WORKER_POOL_SIZE", "3"),
+            ],
+            &["--idle-worker-pool-size", "4", "--network", "regtest"],
+        )
+        .unwrap();
+        overrides.apply(&mut config);
+
+        assert_eq!(config.max_queue_size, 10);
+        assert_eq!(config.max_worker_pool_size, 30);
+        assert_eq!(config.idle_worker_pool_size, 4);
+        assert_eq!(config.network, NetworkConfig::Regtest);
+        assert_eq!(config.zebrad_port, IndexerConfig::default().zebrad_port);
+    }
+
+    #[test]
+    fn inline_toml_overrides() {
+        let mut config = parse("[[backends]]\nuri = \"http://10.0.0.1:18232\"").unwrap();
+        let overrides = parse_overrides(&[
+            "--backend",
+            "{ uri = \"http://10.0.0.2:18232\" }",
+            "--backend",
+            "{ uri = \"http://10.0.0.3:18232\", user = \"zaino\", password = \"secret\" }",
+            "--regtest",
+            "{ nu5_activation_height = 10 }",
+        ]);
+        overrides.apply(&mut config);
+        let uris: Vec<&str> = config
+            .backends
+            .iter()
+            .map(|backend| backend.uri.as_str())
+            .collect();
+        assert_eq!(uris, ["http://10.0.0.2:18232", "http://10.0.0.3:18232"]);
+        assert_eq!(config.regtest.nu5_activation_height, Some(10));
+        assert!(try_parse_overrides(&[], &["--regtest", "{ nu7 = 1 }"]).is_err());
+    }
+
+    #[test]
+    fn redacted_hides_secrets() {
+        let config = parse(
+            r#"
+node_user = "zaino"
+node_password = "node-secret"
+
+[admin]
+token = "admin-secret"
+
+[[auth.keys]]
+name = "wallet"
+token = "key-secret"
+
+[[backends]]
+uri = "http://10.0.0.2:18232"
+user = "backup"
+password = "backend-secret"
+"#,
+        )
+        .unwrap();
+        let printed = config.to_redacted_toml().unwrap();
+        for secret in [
+            "node-secret",
+            "admin-secret",
+            "key-secret",
+            "backend-secret",
+        ] {
+            assert!(!printed.contains(secret), "{} printed", secret);
+        }
+        for name in ["zaino", "wallet", "backup", REDACTED] {
+            assert!(printed.contains(name), "{} not printed", name);
+        }
+        assert_eq!(config.node_password.as_deref(), Some("node-secret"));
+
+        let redacted = IndexerConfig::default().redacted();
+        assert_eq!(redacted.node_password, None);
+        assert_eq!(redacted.admin.token, None);
+    }
 }
@@ -21,6 +21,9 @@ pub enum IndexerError {
     /// Returned from tokio joinhandles..
     #[error("Join handle error: Invalid URI {0}")]
     TokioJoinError(#[from] tokio::task::JoinError),
+    /// Admin API errors.
+    #[error("Admin API error: {0}")]
+    AdminError(String),
     /// Custom indexor errors.
     #[error("Misc indexer error: {0}")]
     MiscIndexerError(String),
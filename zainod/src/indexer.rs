@@ -9,14 +9,25 @@ use std::{
     },
 };
 
-use zaino_fetch::jsonrpc::connector::test_node_and_return_uri;
-use zaino_serve::server::{
-    director::{Server, ServerStatus},
-    error::ServerError,
-    AtomicStatus, StatusType,
+use zaino_fetch::jsonrpc::{
+    backend::{BackendConfig, BackendManager},
+    connector::{test_node_and_return_uri, JsonRpcConnector},
 };
+use zaino_serve::{
+    logging::set_log_level,
+    server::{
+        director::{ConfigUpdateRequest, Server, ServerStatus},
+        error::ServerError,
+        AtomicStatus, StatusType,
+    },
+};
+use zebra_chain::parameters::{Network, NetworkUpgrade};
 
-use crate::{config::IndexerConfig, error::IndexerError};
+use crate::{
+    admin::{AdminHandle, AdminServer},
+    config::{ConfigSource, IndexerConfig},
+    error::IndexerError,
+};
 
 /// Holds the status of the server and all its components.
 #[derive(Debug, Clone)]
@@ -41,6 +52,21 @@ impl IndexerStatus {
         self.server_status.load();
         self.clone()
     }
+
+    /// Returns the status of the indexer.
+    pub fn indexer_status(&self) -> StatusType {
+        self.indexer_status.load().into()
+    }
+
+    /// Returns the status of the server and its components.
+    pub fn server_status(&self) -> &ServerStatus {
+        &self.server_status
+    }
+
+    /// Sets the indexer to close gracefully.
+    pub(crate) fn shutdown(&self) {
+        self.indexer_status.store(4)
+    }
 }
 
 /// Zingo-Indexer.
@@ -49,6 +75,10 @@ pub struct Indexer {
     config: IndexerConfig,
     /// GRPC server.
     server: Option<Server>,
+    /// Admin API server.
+    admin: Option<AdminServer>,
+    /// Validator backends.
+    backends: BackendManager,
     // /// Internal block cache.
     // block_cache: BlockCache,
     /// Indexers status.
@@ -60,13 +90,25 @@ pub struct Indexer {
 impl Indexer {
     /// Starts Indexer service.
     ///
-    /// Currently only takes an IndexerConfig.
-    pub async fn start(config: IndexerConfig) -> Result<(), IndexerError> {
+    /// If a config source is given the config is reloaded from it on SIGHUP.
+    pub async fn start(
+        config: IndexerConfig,
+        config_source: Option<ConfigSource>,
+    ) -> Result<(), IndexerError> {
         let online = Arc::new(AtomicBool::new(true));
-        set_ctrlc(online.clone());
         startup_message();
         println!("Launching Zaino..");
         let indexer: Indexer = Indexer::new(config, online.clone()).await?;
+        set_shutdown_signal_handler(indexer.status.clone());
+        if let Some(config_source) = config_source {
+            if let Some(server) = &indexer.server {
+                set_reload_signal_handler(
+                    config_source,
+                    indexer.config.clone(),
+                    server.config_update_sender(),
+                );
+            }
+        }
         indexer.serve().await?.await?
     }
 
@@ -75,35 +117,73 @@ impl Indexer {
     /// Currently only takes an IndexerConfig.
     pub async fn new(config: IndexerConfig, online: Arc<AtomicBool>) -> Result<Self, IndexerError> {
         config.check_config()?;
+        set_log_level(config.log_level);
+        let api_keys = config.auth.load_keys()?;
         let status = IndexerStatus::new(config.max_worker_pool_size);
         let tcp_ingestor_listen_addr: Option<SocketAddr> = config
             .listen_port
             .map(|port| SocketAddr::new(std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST), port));
-        println!("Checking connection with node..");
-        let zebrad_uri = test_node_and_return_uri(
-            &config.zebrad_port,
-            config.node_user.clone(),
-            config.node_password.clone(),
+        println!("Checking connection with validator backends..");
+        let backends = BackendManager::spawn(
+            backend_configs(&config).await,
+            config.broadcast_transactions,
         )
         .await?;
+        println!("Checking validator network..");
+        let mut network = None;
+        for backend in backends.backends() {
+            if backend.health().reachable {
+                network =
+                    Some(check_validator_network(&config, &backend.connector().await?).await?);
+            }
+        }
+        let network = network.ok_or_else(|| {
+            IndexerError::MiscIndexerError("No reachable validator backends.".to_string())
+        })?;
         status.indexer_status.store(0);
-        let server = Some(
-            Server::spawn(
-                config.tcp_active,
-                tcp_ingestor_listen_addr,
-                zebrad_uri,
-                config.max_queue_size,
-                config.max_worker_pool_size,
-                config.idle_worker_pool_size,
-                status.server_status.clone(),
-                online.clone(),
+        let server = Server::spawn(
+            config.tcp_active,
+            tcp_ingestor_listen_addr,
+            backends.clone(),
+            network,
+            config.blocks,
+            config.max_queue_size,
+            config.max_worker_pool_size,
+            config.idle_worker_pool_size,
+            config.rate_limit.clone(),
+            api_keys,
+            std::time::Duration::from_secs(config.shutdown_grace_period),
+            status.server_status.clone(),
+            online.clone(),
+        )
+        .await?;
+        let admin = if config.admin.enabled {
+            println!("Launching Admin API..");
+            Some(
+                AdminServer::spawn(
+                    SocketAddr::new(
+                        std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST),
+                        config.admin.listen_port,
+                    ),
+                    config.admin.token.clone().unwrap_or_default(),
+                    AdminHandle::new(
+                        status.clone(),
+                        server.worker_pool_bounds(),
+                        server.config_update_sender(),
+                    ),
+                    online.clone(),
+                )
+                .await?,
             )
-            .await?,
-        );
+        } else {
+            None
+        };
         println!("Server Ready.");
         Ok(Indexer {
             config,
-            server,
+            server: Some(server),
+            admin,
+            backends,
             status,
             online,
         })
@@ -123,6 +203,11 @@ impl Indexer {
                     "Server Missing! Fatal Error!.".to_string(),
                 ));
             };
+            let backends_handle = self.backends.serve().await;
+            let admin_handle = match self.admin.take() {
+                Some(admin) => Some(admin.serve().await),
+                None => None,
+            };
 
             self.status.indexer_status.store(2);
             println!("Zaino listening on port {:?}.", self.config.listen_port);
@@ -131,7 +216,8 @@ impl Indexer {
                 // indexer.log_status();
                 if self.check_for_shutdown() {
                     self.status.indexer_status.store(4);
-                    self.shutdown_components(server_handle).await;
+                    self.shutdown_components(server_handle, admin_handle, backends_handle)
+                        .await;
                     self.status.indexer_status.store(5);
                     return Ok(());
                 }
@@ -160,11 +246,19 @@ impl Indexer {
     async fn shutdown_components(
         &mut self,
         server_handle: Option<tokio::task::JoinHandle<Result<(), ServerError>>>,
+        admin_handle: Option<tokio::task::JoinHandle<Result<(), IndexerError>>>,
+        backends_handle: tokio::task::JoinHandle<()>,
     ) {
         if let Some(handle) = server_handle {
             self.status.server_status.server_status.store(4);
             handle.await.ok();
         }
+        self.backends.shutdown();
+        backends_handle.abort();
+        // TODO: Flush the block cache to disk here once it has been added to the indexer.
+        if let Some(handle) = admin_handle {
+            handle.await.ok();
+        }
     }
 
     /// Returns the indexers current status usize.
@@ -189,12 +283,193 @@ impl Indexer {
     }
 }
 
-fn set_ctrlc(online: Arc<AtomicBool>) {
-    ctrlc::set_handler(move || {
-        online.store(false, Ordering::SeqCst);
-        process::exit(0);
-    })
-    .expect("Error setting Ctrl-C handler");
+/// Returns the validator backends, the primary validator (`zebrad_port`, `node_user` and `node_password`) first.
+///
+/// The primary validator is probed over IPv4 and IPv6, retrying while it starts up (see [`test_node_and_return_uri`]).
+/// If it can not be reached its IPv4 address is used, so Zaino can start from the other backends and the
+/// BackendManager routes requests to the primary once it is healthy.
+pub async fn backend_configs(config: &IndexerConfig) -> Vec<BackendConfig> {
+    let primary_uri = match test_node_and_return_uri(
+        &config.zebrad_port,
+        config.node_user.clone(),
+        config.node_password.clone(),
+    )
+    .await
+    {
+        Ok(uri) => uri.to_string(),
+        Err(e) => {
+            eprintln!("Warning: Primary validator unreachable: {}", e);
+            format!("http://127.0.0.1:{}", config.zebrad_port)
+        }
+    };
+    let mut backend_configs = vec![BackendConfig {
+        uri: primary_uri,
+        user: config.node_user.clone(),
+        password: config.node_password.clone(),
+    }];
+    backend_configs.extend(config.backends.iter().cloned());
+    backend_configs
+}
+
+/// Checks the validator is running on the configured network, returning the network's parameters.
+///
+/// The chain name and network upgrade activation heights reported by the validator's `getblockchaininfo` must match
+/// the configured network.
+pub async fn check_validator_network(
+    config: &IndexerConfig,
+    zebrad_client: &JsonRpcConnector,
+) -> Result<Network, IndexerError> {
+    let blockchain_info = zebrad_client.get_blockchain_info().await?;
+    if !config
+        .network
+        .chain_names()
+        .contains(&blockchain_info.chain.as_str())
+    {
+        return Err(IndexerError::ConfigError(format!(
+            "Network mismatch: Zaino is configured for {} but the validator is on chain \"{}\".",
+            config.network, blockchain_info.chain
+        )));
+    }
+    let validator_heights: Vec<(NetworkUpgrade, zebra_chain::block::Height)> = blockchain_info
+        .upgrades
+        .values()
+        .map(|upgrade_info| {
+            let (upgrade, height, _) = upgrade_info.into_parts();
+            (upgrade, height)
+        })
+        .collect();
+    let network = config.network.to_network(&config.regtest, |upgrade| {
+        validator_heights
+            .iter()
+            .find(|(validator_upgrade, _)| *validator_upgrade == upgrade)
+            .map(|(_, height)| height.0)
+    });
+    for (upgrade, height) in validator_heights.iter() {
+        if let Some(expected_height) = upgrade.activation_height(&network) {
+            if expected_height != *height {
+                return Err(IndexerError::ConfigError(format!(
+                    "Network mismatch: {:?} activates at height {} on the validator but at height {} on the configured {} network.",
+                    upgrade, height.0, expected_height.0, config.network
+                )));
+            }
+        }
+    }
+    Ok(network)
+}
+
+/// Listens for SIGINT / SIGTERM.
+///
+/// The indexer is shut down gracefully on the first signal received, a second signal exits the process immediately.
+fn set_shutdown_signal_handler(status: IndexerStatus) {
+    tokio::task::spawn(async move {
+        wait_for_shutdown_signal().await;
+        println!(
+            "Shutdown signal received, shutting down gracefully. Send again to exit immediately."
+        );
+        status.shutdown();
+        wait_for_shutdown_signal().await;
+        eprintln!("Second shutdown signal received, exiting immediately.");
+        process::exit(1);
+    });
+}
+
+/// Listens for SIGHUP, reloading the config from the config source.
+///
+/// The worker pool sizes, log level and rate limits are applied live, changes to any other field are reported as
+/// requiring a restart. Invalid configs are rejected and the running config is kept.
+#[cfg(unix)]
+fn set_reload_signal_handler(
+    config_source: ConfigSource,
+    mut running_config: IndexerConfig,
+    config_update_tx: tokio::sync::mpsc::Sender<ConfigUpdateRequest>,
+) {
+    tokio::task::spawn(async move {
+        let mut sighup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+        {
+            Ok(sighup) => sighup,
+            Err(e) => {
+                eprintln!("Failed to listen for SIGHUP, config reload disabled: {}", e);
+                return;
+            }
+        };
+        while sighup.recv().await.is_some() {
+            println!(
+                "SIGHUP received, reloading config from {}.",
+                config_source.path.display()
+            );
+            let new_config = match config_source
+                .load()
+                .and_then(|config| config.check_config().map(|_| config))
+            {
+                Ok(config) => config,
+                Err(e) => {
+                    eprintln!("Config reload failed, keeping current config: {}", e);
+                    continue;
+                }
+            };
+            let restart_required = running_config.restart_required_changes(&new_config);
+            if !restart_required.is_empty() {
+                eprintln!(
+                    "Changes to the following fields require a restart and have not been applied: {}.",
+                    restart_required.join(", ")
+                );
+            }
+            let (ack_tx, ack_rx) = tokio::sync::oneshot::channel();
+            if config_update_tx
+                .send(ConfigUpdateRequest {
+                    update: new_config.server_config_update(),
+                    ack: ack_tx,
+                })
+                .await
+                .is_err()
+            {
+                return;
+            }
+            match ack_rx.await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => {
+                    eprintln!("Config reload rejected, keeping current config: {}", e);
+                    continue;
+                }
+                Err(_) => return,
+            }
+            set_log_level(new_config.log_level);
+            running_config = running_config.with_live_changes(&new_config);
+            println!("Config reloaded.");
+        }
+    });
+}
+
+/// Config reload on SIGHUP is only supported on unix platforms.
+#[cfg(not(unix))]
+fn set_reload_signal_handler(
+    _config_source: ConfigSource,
+    _running_config: IndexerConfig,
+    _config_update_tx: tokio::sync::mpsc::Sender<ConfigUpdateRequest>,
+) {
+}
+
+/// Waits for a SIGINT or SIGTERM signal.
+async fn wait_for_shutdown_signal() {
+    #[cfg(unix)]
+    {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = sigterm.recv() => {}
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to listen for SIGTERM: {}", e);
+                tokio::signal::ctrl_c().await.ok();
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c().await.ok();
+    }
 }
 
 fn startup_message() {
@@ -1,24 +1,169 @@
 //! Zingo-Indexer daemon
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use std::path::PathBuf;
-use zainodlib::{config::load_config, indexer::Indexer};
+use zaino_fetch::jsonrpc::backend::BackendManager;
+use zaino_serve::utils::get_build_info;
+use zainodlib::{
+    config::{default_config_toml, ConfigOverrides, ConfigSource, IndexerConfig},
+    error::IndexerError,
+    indexer::{backend_configs, check_validator_network, Indexer},
+};
 
 #[derive(Parser, Debug)]
 #[command(name = "zindexer", about = "A server for Zingo-Indexer")]
 struct Args {
     /// Path to the configuration file
-    #[arg(short, long, value_name = "FILE")]
+    #[arg(short, long, value_name = "FILE", env = "ZAINO_CONFIG", global = true)]
     config: Option<PathBuf>,
+
+    /// Overrides for individual config fields.
+    #[command(flatten)]
+    overrides: ConfigOverrides,
+
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-#[tokio::main]
-async fn main() {
-    Indexer::start(load_config(
-        &Args::parse()
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Starts the indexer (default).
+    Start,
+    /// Validates the config and checks the connection with the validator, exits non-zero on failure.
+    Check,
+    /// Prints build information.
+    Version,
+    /// Writes a commented default config file.
+    GenerateConfig {
+        /// Path to write the config file to, the config is printed to stdout if not given.
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+        /// Overwrite the file if it already exists.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Config utilities.
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigCommand {
+    /// Prints the effective config (defaults < file < env < CLI), with secrets redacted.
+    Print,
+}
+
+/// Returns the config file location and environment / command line overrides.
+fn config_source(args: &Args) -> ConfigSource {
+    ConfigSource {
+        path: args
             .config
+            .clone()
             .unwrap_or_else(|| PathBuf::from("./zainod/zindexer.toml")),
-    ))
-    .await
-    .unwrap();
+        overrides: args.overrides.clone(),
+    }
+}
+
+/// Loads the config file and applies any environment / command line overrides.
+fn effective_config(args: &Args) -> Result<IndexerConfig, IndexerError> {
+    config_source(args).load()
+}
+
+/// Validates the config, and checks the connection with each validator backend and its network.
+async fn check(config: &IndexerConfig) -> Result<(), IndexerError> {
+    config.check_config()?;
+    config.auth.load_keys()?;
+    println!("Config OK.");
+    let backends =
+        BackendManager::spawn(backend_configs(config).await, config.broadcast_transactions).await?;
+    let mut unreachable = Vec::new();
+    for backend in backends.backends() {
+        let health = backend.health();
+        if !health.reachable {
+            unreachable.push(backend.uri().to_string());
+            continue;
+        }
+        check_validator_network(config, &backend.connector().await?).await?;
+        println!(
+            "Validator at {} is running on {}.",
+            backend.uri(),
+            config.network
+        );
+        if !health.is_synced() {
+            eprintln!(
+                "Warning: Validator at {} is syncing (height {} of ~{}).",
+                backend.uri(),
+                health.tip_height,
+                health.estimated_height
+            );
+        }
+    }
+    if !unreachable.is_empty() {
+        return Err(IndexerError::MiscIndexerError(format!(
+            "Unreachable validator backends: {}.",
+            unreachable.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// Writes the default config to the given path, or stdout.
+fn generate_config(output: Option<PathBuf>, force: bool) -> Result<(), IndexerError> {
+    let config = default_config_toml();
+    match output {
+        Some(path) => {
+            if path.exists() && !force {
+                return Err(IndexerError::ConfigError(format!(
+                    "{} already exists, use --force to overwrite.",
+                    path.display()
+                )));
+            }
+            std::fs::write(&path, config).map_err(|e| {
+                IndexerError::ConfigError(format!("Failed to write {}: {}", path.display(), e))
+            })?;
+            println!("Config written to {}.", path.display());
+        }
+        None => print!("{}", config),
+    }
+    Ok(())
+}
+
+/// Prints the error and exits with a non-zero exit code.
+fn exit_with_error(e: IndexerError) -> ! {
+    eprintln!("{}", e);
+    std::process::exit(1);
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+    match args.command {
+        Some(Command::Version) => {
+            println!("zainod\n{}", get_build_info());
+        }
+        Some(Command::GenerateConfig { ref output, force }) => {
+            generate_config(output.clone(), force).unwrap_or_else(|e| exit_with_error(e));
+        }
+        Some(Command::Check) => {
+            let config = effective_config(&args).unwrap_or_else(|e| exit_with_error(e));
+            check(&config).await.unwrap_or_else(|e| exit_with_error(e));
+        }
+        Some(Command::Config {
+            command: ConfigCommand::Print,
+        }) => {
+            let config = effective_config(&args).unwrap_or_else(|e| exit_with_error(e));
+            match config.to_redacted_toml() {
+                Ok(config) => println!("{}", config),
+                Err(e) => exit_with_error(e),
+            }
+        }
+        Some(Command::Start) | None => {
+            let config = effective_config(&args).unwrap_or_else(|e| exit_with_error(e));
+            Indexer::start(config, Some(config_source(&args)))
+                .await
+                .unwrap_or_else(|e| exit_with_error(e));
+        }
+    }
 }